@@ -17,6 +17,7 @@ use pinocchio::{
     ProgramResult,
 };
 use pinocchio_tkn::common::{Burn, MintTo, Transfer};
+use pinocchio_tkn::{state::{Mint, AccountState, TokenAccount, MINT_SIZE, TOKEN_ACCOUNT_SIZE}, TOKEN_PROGRAM_ID};
 
 program_entrypoint!(process_instruction);
 
@@ -37,8 +38,61 @@ fn panic_handler(_info: &core::panic::PanicInfo<'_>) -> ! {
 #[cfg(not(target_arch = "bpf"))]
 extern crate std;
 
-#[derive(Clone, Copy)]
-struct PoolState {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AmmError {
+    /// Arithmetic overflow
+    Overflow = 1000,
+    /// Division by zero
+    DivideByZero = 1001,
+    /// Unknown instruction discriminator
+    UnknownInstruction = 1002,
+    /// Account must be writable
+    AccountNotWritable = 1003,
+    /// Account does not match its PDA seeds
+    ConstraintSeeds = 1004,
+    /// Account is not owned by the expected program
+    ConstraintOwner = 1005,
+    /// Token account owner does not match
+    ConstraintTokenOwner = 1006,
+    /// Token account mint does not match
+    ConstraintMint = 1007,
+    /// Account address does not match
+    ConstraintAddress = 1008,
+    /// Program account does not match the expected program
+    ConstraintProgram = 1009,
+    /// Program account is not executable
+    ProgramNotExecutable = 1010,
+    /// Account discriminator does not match
+    AccountDiscriminatorMismatch = 1011,
+    /// Account is already initialized
+    AccountAlreadyInitialized = 1012,
+    /// The same account was passed twice as mutable state
+    DuplicateMutableAccount = 1013,
+    /// An account does not match the key it must equal
+    ConstraintHasOne = 1014,
+    /// A vec argument index is out of bounds
+    IndexOutOfBounds = 1015,
+    /// Token account is frozen, or not frozen for a thaw
+    ConstraintTokenState = 1016,
+    /// Instruction data does not decode as the instruction args
+    InvalidArgs = 1017,
+    /// Account data does not decode as its layout
+    InvalidAccountLayout = 1018,
+    /// Account does not match its equals constraint
+    ConstraintEquals = 1019,
+    /// Swap output is below the minimum amount out
+    SlippageExceeded = 6000,
+}
+
+impl From<AmmError> for ProgramError {
+    fn from(error: AmmError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolState {
     pub admin: Pubkey,
     pub token_mint_a: Pubkey,
     pub token_mint_b: Pubkey,
@@ -50,22 +104,64 @@ struct PoolState {
 }
 
 impl PoolState {
-    const LEN: usize = 153;
+    pub const LEN: usize = 154;
+    pub const DISCRIMINATOR: [u8; 1] = [247];
+
+    // Freshly allocated accounts are zeroed, so any non-zero discriminator byte
+    // means the account was initialized, possibly as another account type.
+    fn is_initialized(account: &AccountInfo) -> Result<bool, ProgramError> {
+        let data = account.try_borrow_data()?;
+        Ok(data.iter().take(Self::DISCRIMINATOR.len()).any(|byte| *byte != 0))
+    }
 
     fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
         let data = account.try_borrow_data()?;
+        Self::unpack(&data)
+    }
+
+    fn store(account: &AccountInfo, state: &Self) -> Result<(), ProgramError> {
+        let mut data = account.try_borrow_mut_data()?;
+        state.pack(&mut data)
+    }
+
+    /// Decodes account data as `load` does, so host code can read raw account bytes.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
         if data.len() < Self::LEN {
             return Err(ProgramError::AccountDataTooSmall);
         }
-        let admin = read_pubkey(&data, 0)?;
-        let token_mint_a = read_pubkey(&data, 32)?;
-        let token_mint_b = read_pubkey(&data, 64)?;
-        let lp_mint = read_pubkey(&data, 96)?;
-        let reserve_a = read_u64(&data, 128)?;
-        let reserve_b = read_u64(&data, 136)?;
-        let total_lp = read_u64(&data, 144)?;
-        let bump = read_u8(&data, 152)?;
-        Ok(Self {
+        if data[..Self::DISCRIMINATOR.len()] != Self::DISCRIMINATOR {
+            return Err(AmmError::AccountDiscriminatorMismatch.into());
+        }
+        Self::read(data).ok_or(AmmError::InvalidAccountLayout.into())
+    }
+
+    /// Writes the discriminator and fields to the first `LEN` bytes, as `store` does.
+    pub fn pack(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[..Self::DISCRIMINATOR.len()].copy_from_slice(&Self::DISCRIMINATOR);
+        write_pubkey(data, 1, &self.admin)?;
+        write_pubkey(data, 33, &self.token_mint_a)?;
+        write_pubkey(data, 65, &self.token_mint_b)?;
+        write_pubkey(data, 97, &self.lp_mint)?;
+        write_u64(data, 129, self.reserve_a)?;
+        write_u64(data, 137, self.reserve_b)?;
+        write_u64(data, 145, self.total_lp)?;
+        write_u8(data, 153, self.bump)?;
+        Ok(())
+    }
+
+    fn read(data: &[u8]) -> Option<Self> {
+        let admin = read_pubkey(data, 1)?;
+        let token_mint_a = read_pubkey(data, 33)?;
+        let token_mint_b = read_pubkey(data, 65)?;
+        let lp_mint = read_pubkey(data, 97)?;
+        let reserve_a = read_u64(data, 129)?;
+        let reserve_b = read_u64(data, 137)?;
+        let total_lp = read_u64(data, 145)?;
+        let bump = read_u8(data, 153)?;
+        Some(Self {
             admin,
             token_mint_a,
             token_mint_b,
@@ -76,22 +172,159 @@ impl PoolState {
             bump,
         })
     }
+}
 
-    fn store(account: &AccountInfo, state: &Self) -> Result<(), ProgramError> {
-        let mut data = account.try_borrow_mut_data()?;
-        if data.len() < Self::LEN {
-            return Err(ProgramError::AccountDataTooSmall);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Swap {
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+impl Swap {
+    pub const DISCRIMINATOR: [u8; 8] = [81, 108, 227, 190, 205, 208, 10, 196];
+    pub const LEN: usize = 24;
+
+    fn emit(&self) -> ProgramResult {
+        let mut data = [0u8; Self::LEN];
+        data[..Self::DISCRIMINATOR.len()].copy_from_slice(&Self::DISCRIMINATOR);
+        write_u64(&mut data, 8, self.amount_in)?;
+        write_u64(&mut data, 16, self.amount_out)?;
+        pinocchio::log::sol_log_data(&[&data]);
+        Ok(())
+    }
+
+    /// Decodes one `sol_log_data` payload; `None` if it is another event.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::LEN || data[..Self::DISCRIMINATOR.len()] != Self::DISCRIMINATOR {
+            return None;
+        }
+        Some(Self {
+            amount_in: read_u64(data, 8)?,
+            amount_out: read_u64(data, 16)?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ratio {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapAforBReturn {
+    pub amount_out: u64,
+}
+
+impl SwapAforBReturn {
+    pub const LEN: usize = 8;
+
+    fn set_return_data(&self) -> ProgramResult {
+        let mut data = [0u8; Self::LEN];
+        write_u64(&mut data, 0, self.amount_out)?;
+        pinocchio::cpi::set_return_data(&data);
+        Ok(())
+    }
+
+    /// Decodes the return data of `swapAForB`. RPC nodes strip trailing zero bytes,
+    /// so shorter input is zero-padded.
+    pub fn decode(return_data: &[u8]) -> Option<Self> {
+        if return_data.len() > Self::LEN {
+            return None;
         }
-        write_pubkey(&mut data, 0, &state.admin)?;
-        write_pubkey(&mut data, 32, &state.token_mint_a)?;
-        write_pubkey(&mut data, 64, &state.token_mint_b)?;
-        write_pubkey(&mut data, 96, &state.lp_mint)?;
-        write_u64(&mut data, 128, state.reserve_a)?;
-        write_u64(&mut data, 136, state.reserve_b)?;
-        write_u64(&mut data, 144, state.total_lp)?;
-        write_u8(&mut data, 152, state.bump)?;
+        let mut data = [0u8; Self::LEN];
+        data[..return_data.len()].copy_from_slice(return_data);
+        Some(Self {
+            amount_out: read_u64(&data, 0)?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapBforAReturn {
+    pub amount_out: u64,
+}
+
+impl SwapBforAReturn {
+    pub const LEN: usize = 8;
+
+    fn set_return_data(&self) -> ProgramResult {
+        let mut data = [0u8; Self::LEN];
+        write_u64(&mut data, 0, self.amount_out)?;
+        pinocchio::cpi::set_return_data(&data);
         Ok(())
     }
+
+    /// Decodes the return data of `swapBForA`. RPC nodes strip trailing zero bytes,
+    /// so shorter input is zero-padded.
+    pub fn decode(return_data: &[u8]) -> Option<Self> {
+        if return_data.len() > Self::LEN {
+            return None;
+        }
+        let mut data = [0u8; Self::LEN];
+        data[..return_data.len()].copy_from_slice(return_data);
+        Some(Self {
+            amount_out: read_u64(&data, 0)?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolSummary {
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub total_lp: u64,
+    pub spot_price_a: Ratio,
+    pub spot_price_b: Ratio,
+}
+
+impl PoolSummary {
+    pub const LEN: usize = 152;
+
+    fn set_return_data(&self) -> ProgramResult {
+        let mut data = [0u8; Self::LEN];
+        write_pubkey(&mut data, 0, &self.token_mint_a)?;
+        write_pubkey(&mut data, 32, &self.token_mint_b)?;
+        write_pubkey(&mut data, 64, &self.lp_mint)?;
+        write_u64(&mut data, 96, self.reserve_a)?;
+        write_u64(&mut data, 104, self.reserve_b)?;
+        write_u64(&mut data, 112, self.total_lp)?;
+        write_u64(&mut data, 120, self.spot_price_a.numerator)?;
+        write_u64(&mut data, 128, self.spot_price_a.denominator)?;
+        write_u64(&mut data, 136, self.spot_price_b.numerator)?;
+        write_u64(&mut data, 144, self.spot_price_b.denominator)?;
+        pinocchio::cpi::set_return_data(&data);
+        Ok(())
+    }
+
+    /// Decodes the return data of `poolSummary`. RPC nodes strip trailing zero bytes,
+    /// so shorter input is zero-padded.
+    pub fn decode(return_data: &[u8]) -> Option<Self> {
+        if return_data.len() > Self::LEN {
+            return None;
+        }
+        let mut data = [0u8; Self::LEN];
+        data[..return_data.len()].copy_from_slice(return_data);
+        Some(Self {
+            token_mint_a: read_pubkey(&data, 0)?,
+            token_mint_b: read_pubkey(&data, 32)?,
+            lp_mint: read_pubkey(&data, 64)?,
+            reserve_a: read_u64(&data, 96)?,
+            reserve_b: read_u64(&data, 104)?,
+            total_lp: read_u64(&data, 112)?,
+            spot_price_a: Ratio {
+                numerator: read_u64(&data, 120)?,
+                denominator: read_u64(&data, 128)?,
+            },
+            spot_price_b: Ratio {
+                numerator: read_u64(&data, 136)?,
+                denominator: read_u64(&data, 144)?,
+            },
+        })
+    }
 }
 
 
@@ -136,35 +369,81 @@ fn create_program_account(
     Ok(())
 }
 
+fn check_token_layout(data: &[u8], base_len: usize, account_type: u8) -> ProgramResult {
+    if data.len() == base_len || (data.len() > TOKEN_ACCOUNT_SIZE && data[TOKEN_ACCOUNT_SIZE] == account_type) {
+        return Ok(());
+    }
+    Err(AmmError::InvalidAccountLayout.into())
+}
 
-fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey, ProgramError> {
+fn check_token_account(account: &AccountInfo, owner: &Pubkey, mint: &Pubkey, token_programs: &[Pubkey], state: AccountState) -> ProgramResult {
+    if !token_programs.iter().any(|token_program| account.is_owned_by(token_program)) {
+        return Err(AmmError::ConstraintOwner.into());
+    }
+    let data = account.try_borrow_data()?;
+    check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2)?;
+    let token_account = TokenAccount::from_bytes(&data)?;
+    if token_account.mint() != mint {
+        return Err(AmmError::ConstraintMint.into());
+    }
+    if token_account.owner() != owner {
+        return Err(AmmError::ConstraintTokenOwner.into());
+    }
+    // `from_bytes` already rejects uninitialized accounts.
+    if token_account.state() != state {
+        return Err(AmmError::ConstraintTokenState.into());
+    }
+    Ok(())
+}
+
+fn check_mint_account(account: &AccountInfo, address: &Pubkey, token_programs: &[Pubkey]) -> ProgramResult {
+    if account.key() != address {
+        return Err(AmmError::ConstraintAddress.into());
+    }
+    if !token_programs.iter().any(|token_program| account.is_owned_by(token_program)) {
+        return Err(AmmError::ConstraintOwner.into());
+    }
+    let data = account.try_borrow_data()?;
+    check_token_layout(&data, MINT_SIZE, 1)?;
+    Mint::from_bytes(&data)?;
+    Ok(())
+}
+
+fn check_program_account(account: &AccountInfo, program_ids: &[Pubkey]) -> ProgramResult {
+    if !program_ids.contains(account.key()) {
+        return Err(AmmError::ConstraintProgram.into());
+    }
+    if !account.executable() {
+        return Err(AmmError::ProgramNotExecutable.into());
+    }
+    Ok(())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
     if data.len() < offset + 32 {
-        return Err(ProgramError::InvalidInstructionData);
+        return None;
     }
     let mut out = [0u8; 32];
     out.copy_from_slice(&data[offset..offset + 32]);
-    Ok(out)
+    Some(out)
 }
 
-fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
     if data.len() < offset + 8 {
-        return Err(ProgramError::InvalidInstructionData);
+        return None;
     }
     let mut buf = [0u8; 8];
     buf.copy_from_slice(&data[offset..offset + 8]);
-    Ok(u64::from_le_bytes(buf))
+    Some(u64::from_le_bytes(buf))
 }
 
-fn read_u8(data: &[u8], offset: usize) -> Result<u8, ProgramError> {
-    if data.len() <= offset {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    Ok(data[offset])
+fn read_u8(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).copied()
 }
 
 fn write_pubkey(data: &mut [u8], offset: usize, value: &Pubkey) -> Result<(), ProgramError> {
     if data.len() < offset + 32 {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(AmmError::InvalidAccountLayout.into());
     }
     data[offset..offset + 32].copy_from_slice(value);
     Ok(())
@@ -172,7 +451,7 @@ fn write_pubkey(data: &mut [u8], offset: usize, value: &Pubkey) -> Result<(), Pr
 
 fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Result<(), ProgramError> {
     if data.len() < offset + 8 {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(AmmError::InvalidAccountLayout.into());
     }
     data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
     Ok(())
@@ -180,52 +459,77 @@ fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Result<(), ProgramEr
 
 fn write_u8(data: &mut [u8], offset: usize, value: u8) -> Result<(), ProgramError> {
     if data.len() <= offset {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(AmmError::InvalidAccountLayout.into());
     }
     data[offset] = value;
     Ok(())
 }
 
-#[allow(dead_code)]
-fn checked_add(a: u64, b: u64) -> Result<u64, ProgramError> {
-    a.checked_add(b).ok_or(ProgramError::InvalidInstructionData)
+// Lets the checked helpers below take any integer type the DSL supports.
+trait CheckedInt: Copy + PartialEq {
+    fn add_checked(self, other: Self) -> Option<Self>;
+    fn sub_checked(self, other: Self) -> Option<Self>;
 }
 
-#[allow(dead_code)]
-fn checked_sub(a: u64, b: u64) -> Result<u64, ProgramError> {
-    a.checked_sub(b).ok_or(ProgramError::InvalidInstructionData)
+macro_rules! impl_checked_int {
+    ($($ty:ty),*) => {$(
+        impl CheckedInt for $ty {
+            fn add_checked(self, other: Self) -> Option<Self> {
+                self.checked_add(other)
+            }
+            fn sub_checked(self, other: Self) -> Option<Self> {
+                self.checked_sub(other)
+            }
+        }
+    )*};
 }
 
-#[allow(dead_code)]
-fn checked_mul(a: u64, b: u64) -> Result<u64, ProgramError> {
-    let value = (a as u128).checked_mul(b as u128).ok_or(ProgramError::InvalidInstructionData)?;
-    if value > u64::MAX as u128 {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    Ok(value as u64)
+impl_checked_int!(u8, u16, u32, u64, u128, i64);
+
+fn checked_add<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.add_checked(b).ok_or(AmmError::Overflow)?)
 }
 
-#[allow(dead_code)]
-fn checked_div(a: u64, b: u64) -> Result<u64, ProgramError> {
-    if b == 0 {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    Ok(a / b)
+fn checked_sub<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.sub_checked(b).ok_or(AmmError::Overflow)?)
 }
 
-#[allow(dead_code)]
 fn checked_mul_div(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
     if c == 0 {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(AmmError::DivideByZero.into());
+    }
+    let value = (a as u128) * (b as u128) / (c as u128);
+    Ok(u64::try_from(value).map_err(|_| AmmError::Overflow)?)
+}
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+fn checked_bps(value: u64, bps: u64) -> Result<u64, ProgramError> {
+    checked_mul_div(value, bps, BPS_DENOMINATOR)
+}
+
+// Floor square root, bit by bit. The root of any u128 fits in a u64.
+fn isqrt(value: u128) -> u64 {
+    let mut remainder = value;
+    let mut root: u128 = 0;
+    let mut bit: u128 = 1 << 126;
+    while bit > remainder {
+        bit >>= 2;
     }
-    let value = (a as u128)
-        .checked_mul(b as u128)
-        .ok_or(ProgramError::InvalidInstructionData)?
-        / (c as u128);
-    if value > u64::MAX as u128 {
-        return Err(ProgramError::InvalidInstructionData);
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
     }
-    Ok(value as u64)
+    root as u64
+}
+
+fn sqrt_mul(a: u64, b: u64) -> u64 {
+    isqrt((a as u128) * (b as u128))
 }
 
 pub fn process_instruction(
@@ -234,7 +538,7 @@ pub fn process_instruction(
     data: &[u8],
 ) -> ProgramResult {
     if data.is_empty() {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(AmmError::UnknownInstruction.into());
     }
 
     match data[0] {
@@ -243,7 +547,8 @@ pub fn process_instruction(
         2 => handle_remove_liquidity(program_id, accounts, &data[1..]),
         3 => handle_swap_afor_b(program_id, accounts, &data[1..]),
         4 => handle_swap_bfor_a(program_id, accounts, &data[1..]),
-        _ => Err(ProgramError::InvalidInstructionData),
+        5 => handle_pool_summary(program_id, accounts, &data[1..]),
+        _ => Err(AmmError::UnknownInstruction.into()),
     }
 }
 
@@ -255,9 +560,9 @@ struct CreatePoolArgs {
 }
 
 fn decode_create_pool_args(data: &[u8]) -> Result<CreatePoolArgs, ProgramError> {
-    let token_mint_a = read_pubkey(data, 0)?;
-    let token_mint_b = read_pubkey(data, 32)?;
-    let lp_mint = read_pubkey(data, 64)?;
+    let token_mint_a = read_pubkey(data, 0).ok_or(AmmError::InvalidArgs)?;
+    let token_mint_b = read_pubkey(data, 32).ok_or(AmmError::InvalidArgs)?;
+    let lp_mint = read_pubkey(data, 64).ok_or(AmmError::InvalidArgs)?;
     Ok(CreatePoolArgs {
         token_mint_a,
         token_mint_b,
@@ -276,28 +581,31 @@ fn handle_create_pool(
     let args = decode_create_pool_args(data)?;
     let payer = &accounts[0];
     if !payer.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
-    if !payer.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !payer.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let pool = &accounts[1];
-    if !pool.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !pool.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let (pool_key, pool_bump) = pubkey::find_program_address(&[b"pool", args.token_mint_a.as_ref(), args.token_mint_b.as_ref()], program_id);
-    if pool.key() != &pool_key { return Err(ProgramError::InvalidSeeds); }
+    if pool.key() != &pool_key { return Err(AmmError::ConstraintSeeds.into()); }
     let pool_authority = &accounts[2];
     let (pool_authority_key, _pool_authority_bump) = pubkey::find_program_address(&[b"authority", pool.key().as_ref()], program_id);
-    if pool_authority.key() != &pool_authority_key { return Err(ProgramError::InvalidSeeds); }
+    if pool_authority.key() != &pool_authority_key { return Err(AmmError::ConstraintSeeds.into()); }
     let token_mint_a = &accounts[3];
-    let _ = token_mint_a;
     let token_mint_b = &accounts[4];
-    let _ = token_mint_b;
     let lp_mint = &accounts[5];
-    if !lp_mint.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !lp_mint.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_a = &accounts[6];
-    if !vault_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_b = &accounts[7];
-    if !vault_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let token_program = &accounts[8];
-    let _ = token_program;
     let system_program = &accounts[9];
-    let _ = system_program;
+    check_mint_account(token_mint_a, &args.token_mint_a, &[TOKEN_PROGRAM_ID])?;
+    check_mint_account(token_mint_b, &args.token_mint_b, &[TOKEN_PROGRAM_ID])?;
+    check_mint_account(lp_mint, &args.lp_mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(vault_a, pool_authority.key(), &args.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_b, pool_authority.key(), &args.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    check_program_account(system_program, &[SYSTEM_PROGRAM_ID])?;
     
     if !pool.is_owned_by(program_id) {
         let pool_bump_ref = [pool_bump];
@@ -305,16 +613,20 @@ fn handle_create_pool(
         let pool_signer = Signer::from(&pool_seeds);
         create_program_account(payer, pool, program_id, PoolState::LEN, Some(&pool_signer))?;
     }
-    if !pool.is_owned_by(program_id) { return Err(ProgramError::IncorrectProgramId); }
-    let mut pool_state = PoolState::load(pool)?;
-    pool_state.admin = *payer.key();
-    pool_state.token_mint_a = args.token_mint_a;
-    pool_state.token_mint_b = args.token_mint_b;
-    pool_state.lp_mint = args.lp_mint;
-    pool_state.reserve_a = 0u64;
-    pool_state.reserve_b = 0u64;
-    pool_state.total_lp = 0u64;
-    pool_state.bump = pool_bump;
+    if !pool.is_owned_by(program_id) { return Err(AmmError::ConstraintOwner.into()); }
+    if PoolState::is_initialized(pool)? {
+        return Err(AmmError::AccountAlreadyInitialized.into());
+    }
+    let pool_state = PoolState {
+        admin: *payer.key(),
+        token_mint_a: args.token_mint_a,
+        token_mint_b: args.token_mint_b,
+        lp_mint: args.lp_mint,
+        reserve_a: 0u64,
+        reserve_b: 0u64,
+        total_lp: 0u64,
+        bump: pool_bump,
+    };
     PoolState::store(pool, &pool_state)?;
     Ok(())
 }
@@ -326,8 +638,8 @@ struct AddLiquidityArgs {
 }
 
 fn decode_add_liquidity_args(data: &[u8]) -> Result<AddLiquidityArgs, ProgramError> {
-    let amount_a = read_u64(data, 0)?;
-    let amount_b = read_u64(data, 8)?;
+    let amount_a = read_u64(data, 0).ok_or(AmmError::InvalidArgs)?;
+    let amount_b = read_u64(data, 8).ok_or(AmmError::InvalidArgs)?;
     Ok(AddLiquidityArgs {
         amount_a,
         amount_b,
@@ -346,31 +658,38 @@ fn handle_add_liquidity(
     let user = &accounts[0];
     if !user.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
     let pool = &accounts[1];
-    if !pool.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !pool.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let pool_authority = &accounts[2];
     let (pool_authority_key, pool_authority_bump) = pubkey::find_program_address(&[b"authority", pool.key().as_ref()], program_id);
-    if pool_authority.key() != &pool_authority_key { return Err(ProgramError::InvalidSeeds); }
+    if pool_authority.key() != &pool_authority_key { return Err(AmmError::ConstraintSeeds.into()); }
     let user_a = &accounts[3];
-    if !user_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let user_b = &accounts[4];
-    if !user_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_a = &accounts[5];
-    if !vault_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_b = &accounts[6];
-    if !vault_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let lp_mint = &accounts[7];
-    if !lp_mint.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !lp_mint.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let user_lp = &accounts[8];
-    if !user_lp.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_lp.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let token_program = &accounts[9];
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
     let pool_authority_bump_ref = [pool_authority_bump];
     let pool_authority_seeds = seeds!(b"authority", pool.key().as_ref(), &pool_authority_bump_ref);
     let pool_authority_signer = Signer::from(&pool_authority_seeds);
     // State account must be owned by this program
     if !pool.is_owned_by(program_id) {
-        return Err(ProgramError::IncorrectProgramId);
+        return Err(AmmError::ConstraintOwner.into());
     }
     let mut pool_state = PoolState::load(pool)?;
+    check_token_account(user_a, user.key(), &pool_state.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(user_b, user.key(), &pool_state.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_a, pool_authority.key(), &pool_state.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_b, pool_authority.key(), &pool_state.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_mint_account(lp_mint, &pool_state.lp_mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(user_lp, user.key(), &pool_state.lp_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
     Transfer {
         source: user_a,
         destination: vault_a,
@@ -385,16 +704,17 @@ fn handle_add_liquidity(
         amount: args.amount_b,
         program_id: Some(token_program.key()),
     }.invoke()?;
+    let cse_0 = if pool_state.total_lp == 0u64 { sqrt_mul(args.amount_a, args.amount_b) } else { checked_mul_div(args.amount_a, pool_state.total_lp, pool_state.reserve_a)? };
     MintTo {
         mint: lp_mint,
         destination: user_lp,
         authority: pool_authority,
-        amount: if pool_state.total_lp == 0u64 { args.amount_a } else { checked_mul_div(args.amount_a, pool_state.total_lp, pool_state.reserve_a)? },
+        amount: cse_0,
         program_id: Some(token_program.key()),
-    }.invoke_signed(&[pool_authority_signer.clone()])?;
+    }.invoke_signed(core::slice::from_ref(&pool_authority_signer))?;
     let next_reserve_a = checked_add(pool_state.reserve_a, args.amount_a)?;
     let next_reserve_b = checked_add(pool_state.reserve_b, args.amount_b)?;
-    let next_total_lp = checked_add(pool_state.total_lp, if pool_state.total_lp == 0u64 { args.amount_a } else { checked_mul_div(args.amount_a, pool_state.total_lp, pool_state.reserve_a)? })?;
+    let next_total_lp = checked_add(pool_state.total_lp, cse_0)?;
     pool_state.reserve_a = next_reserve_a;
     pool_state.reserve_b = next_reserve_b;
     pool_state.total_lp = next_total_lp;
@@ -408,7 +728,7 @@ struct RemoveLiquidityArgs {
 }
 
 fn decode_remove_liquidity_args(data: &[u8]) -> Result<RemoveLiquidityArgs, ProgramError> {
-    let lp_amount = read_u64(data, 0)?;
+    let lp_amount = read_u64(data, 0).ok_or(AmmError::InvalidArgs)?;
     Ok(RemoveLiquidityArgs {
         lp_amount,
     })
@@ -426,31 +746,38 @@ fn handle_remove_liquidity(
     let user = &accounts[0];
     if !user.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
     let pool = &accounts[1];
-    if !pool.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !pool.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let pool_authority = &accounts[2];
     let (pool_authority_key, pool_authority_bump) = pubkey::find_program_address(&[b"authority", pool.key().as_ref()], program_id);
-    if pool_authority.key() != &pool_authority_key { return Err(ProgramError::InvalidSeeds); }
+    if pool_authority.key() != &pool_authority_key { return Err(AmmError::ConstraintSeeds.into()); }
     let user_lp = &accounts[3];
-    if !user_lp.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_lp.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let lp_mint = &accounts[4];
-    if !lp_mint.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !lp_mint.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let user_a = &accounts[5];
-    if !user_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let user_b = &accounts[6];
-    if !user_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_a = &accounts[7];
-    if !vault_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_b = &accounts[8];
-    if !vault_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let token_program = &accounts[9];
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
     let pool_authority_bump_ref = [pool_authority_bump];
     let pool_authority_seeds = seeds!(b"authority", pool.key().as_ref(), &pool_authority_bump_ref);
     let pool_authority_signer = Signer::from(&pool_authority_seeds);
     // State account must be owned by this program
     if !pool.is_owned_by(program_id) {
-        return Err(ProgramError::IncorrectProgramId);
+        return Err(AmmError::ConstraintOwner.into());
     }
     let mut pool_state = PoolState::load(pool)?;
+    check_token_account(user_lp, user.key(), &pool_state.lp_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_mint_account(lp_mint, &pool_state.lp_mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(user_a, user.key(), &pool_state.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(user_b, user.key(), &pool_state.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_a, pool_authority.key(), &pool_state.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_b, pool_authority.key(), &pool_state.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
     Burn {
         source: user_lp,
         mint: lp_mint,
@@ -458,22 +785,24 @@ fn handle_remove_liquidity(
         amount: args.lp_amount,
         program_id: Some(token_program.key()),
     }.invoke()?;
+    let cse_0 = checked_mul_div(args.lp_amount, pool_state.reserve_a, pool_state.total_lp)?;
     Transfer {
         source: vault_a,
         destination: user_a,
         authority: pool_authority,
-        amount: checked_mul_div(args.lp_amount, pool_state.reserve_a, pool_state.total_lp)?,
+        amount: cse_0,
         program_id: Some(token_program.key()),
-    }.invoke_signed(&[pool_authority_signer.clone()])?;
+    }.invoke_signed(core::slice::from_ref(&pool_authority_signer))?;
+    let cse_1 = checked_mul_div(args.lp_amount, pool_state.reserve_b, pool_state.total_lp)?;
     Transfer {
         source: vault_b,
         destination: user_b,
         authority: pool_authority,
-        amount: checked_mul_div(args.lp_amount, pool_state.reserve_b, pool_state.total_lp)?,
+        amount: cse_1,
         program_id: Some(token_program.key()),
-    }.invoke_signed(&[pool_authority_signer.clone()])?;
-    let next_reserve_a = checked_sub(pool_state.reserve_a, checked_mul_div(args.lp_amount, pool_state.reserve_a, pool_state.total_lp)?)?;
-    let next_reserve_b = checked_sub(pool_state.reserve_b, checked_mul_div(args.lp_amount, pool_state.reserve_b, pool_state.total_lp)?)?;
+    }.invoke_signed(core::slice::from_ref(&pool_authority_signer))?;
+    let next_reserve_a = checked_sub(pool_state.reserve_a, cse_0)?;
+    let next_reserve_b = checked_sub(pool_state.reserve_b, cse_1)?;
     let next_total_lp = checked_sub(pool_state.total_lp, args.lp_amount)?;
     pool_state.reserve_a = next_reserve_a;
     pool_state.reserve_b = next_reserve_b;
@@ -485,12 +814,15 @@ fn handle_remove_liquidity(
 
 struct SwapAforBArgs {
     pub amount_in: u64,
+    pub min_amount_out: u64,
 }
 
 fn decode_swap_afor_b_args(data: &[u8]) -> Result<SwapAforBArgs, ProgramError> {
-    let amount_in = read_u64(data, 0)?;
+    let amount_in = read_u64(data, 0).ok_or(AmmError::InvalidArgs)?;
+    let min_amount_out = read_u64(data, 8).ok_or(AmmError::InvalidArgs)?;
     Ok(SwapAforBArgs {
         amount_in,
+        min_amount_out,
     })
 }
 
@@ -506,27 +838,37 @@ fn handle_swap_afor_b(
     let user = &accounts[0];
     if !user.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
     let pool = &accounts[1];
-    if !pool.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !pool.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let pool_authority = &accounts[2];
     let (pool_authority_key, pool_authority_bump) = pubkey::find_program_address(&[b"authority", pool.key().as_ref()], program_id);
-    if pool_authority.key() != &pool_authority_key { return Err(ProgramError::InvalidSeeds); }
+    if pool_authority.key() != &pool_authority_key { return Err(AmmError::ConstraintSeeds.into()); }
     let user_a = &accounts[3];
-    if !user_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let user_b = &accounts[4];
-    if !user_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_a = &accounts[5];
-    if !vault_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_b = &accounts[6];
-    if !vault_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let token_program = &accounts[7];
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
     let pool_authority_bump_ref = [pool_authority_bump];
     let pool_authority_seeds = seeds!(b"authority", pool.key().as_ref(), &pool_authority_bump_ref);
     let pool_authority_signer = Signer::from(&pool_authority_seeds);
     // State account must be owned by this program
     if !pool.is_owned_by(program_id) {
-        return Err(ProgramError::IncorrectProgramId);
+        return Err(AmmError::ConstraintOwner.into());
     }
     let mut pool_state = PoolState::load(pool)?;
+    check_token_account(user_a, user.key(), &pool_state.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(user_b, user.key(), &pool_state.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_a, pool_authority.key(), &pool_state.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_b, pool_authority.key(), &pool_state.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    let cse_0 = checked_sub(args.amount_in, checked_bps(args.amount_in, 30u64)?)?;
+    let amount_out = checked_mul_div(cse_0, pool_state.reserve_b, checked_add(pool_state.reserve_a, cse_0)?)?;
+    if amount_out < args.min_amount_out {
+        return Err(AmmError::SlippageExceeded.into());
+    }
     Transfer {
         source: user_a,
         destination: vault_a,
@@ -538,26 +880,36 @@ fn handle_swap_afor_b(
         source: vault_b,
         destination: user_b,
         authority: pool_authority,
-        amount: checked_mul_div(args.amount_in, pool_state.reserve_b, checked_add(pool_state.reserve_a, args.amount_in)?)?,
+        amount: amount_out,
         program_id: Some(token_program.key()),
-    }.invoke_signed(&[pool_authority_signer.clone()])?;
+    }.invoke_signed(core::slice::from_ref(&pool_authority_signer))?;
     let next_reserve_a = checked_add(pool_state.reserve_a, args.amount_in)?;
-    let next_reserve_b = checked_sub(pool_state.reserve_b, checked_mul_div(args.amount_in, pool_state.reserve_b, checked_add(pool_state.reserve_a, args.amount_in)?)?)?;
+    let next_reserve_b = checked_sub(pool_state.reserve_b, amount_out)?;
     pool_state.reserve_a = next_reserve_a;
     pool_state.reserve_b = next_reserve_b;
     PoolState::store(pool, &pool_state)?;
+    Swap {
+        amount_in: args.amount_in,
+        amount_out,
+    }.emit()?;
+    SwapAforBReturn {
+        amount_out,
+    }.set_return_data()?;
     Ok(())
 }
 
 
 struct SwapBforAArgs {
     pub amount_in: u64,
+    pub min_amount_out: u64,
 }
 
 fn decode_swap_bfor_a_args(data: &[u8]) -> Result<SwapBforAArgs, ProgramError> {
-    let amount_in = read_u64(data, 0)?;
+    let amount_in = read_u64(data, 0).ok_or(AmmError::InvalidArgs)?;
+    let min_amount_out = read_u64(data, 8).ok_or(AmmError::InvalidArgs)?;
     Ok(SwapBforAArgs {
         amount_in,
+        min_amount_out,
     })
 }
 
@@ -573,27 +925,37 @@ fn handle_swap_bfor_a(
     let user = &accounts[0];
     if !user.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
     let pool = &accounts[1];
-    if !pool.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !pool.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let pool_authority = &accounts[2];
     let (pool_authority_key, pool_authority_bump) = pubkey::find_program_address(&[b"authority", pool.key().as_ref()], program_id);
-    if pool_authority.key() != &pool_authority_key { return Err(ProgramError::InvalidSeeds); }
+    if pool_authority.key() != &pool_authority_key { return Err(AmmError::ConstraintSeeds.into()); }
     let user_a = &accounts[3];
-    if !user_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let user_b = &accounts[4];
-    if !user_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_a = &accounts[5];
-    if !vault_a.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_a.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let vault_b = &accounts[6];
-    if !vault_b.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_b.is_writable() { return Err(AmmError::AccountNotWritable.into()); }
     let token_program = &accounts[7];
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
     let pool_authority_bump_ref = [pool_authority_bump];
     let pool_authority_seeds = seeds!(b"authority", pool.key().as_ref(), &pool_authority_bump_ref);
     let pool_authority_signer = Signer::from(&pool_authority_seeds);
     // State account must be owned by this program
     if !pool.is_owned_by(program_id) {
-        return Err(ProgramError::IncorrectProgramId);
+        return Err(AmmError::ConstraintOwner.into());
     }
     let mut pool_state = PoolState::load(pool)?;
+    check_token_account(user_a, user.key(), &pool_state.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(user_b, user.key(), &pool_state.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_a, pool_authority.key(), &pool_state.token_mint_a, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_b, pool_authority.key(), &pool_state.token_mint_b, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    let cse_0 = checked_sub(args.amount_in, checked_bps(args.amount_in, 30u64)?)?;
+    let amount_out = checked_mul_div(cse_0, pool_state.reserve_a, checked_add(pool_state.reserve_b, cse_0)?)?;
+    if amount_out < args.min_amount_out {
+        return Err(AmmError::SlippageExceeded.into());
+    }
     Transfer {
         source: user_b,
         destination: vault_b,
@@ -605,14 +967,127 @@ fn handle_swap_bfor_a(
         source: vault_a,
         destination: user_a,
         authority: pool_authority,
-        amount: checked_mul_div(args.amount_in, pool_state.reserve_a, checked_add(pool_state.reserve_b, args.amount_in)?)?,
+        amount: amount_out,
         program_id: Some(token_program.key()),
-    }.invoke_signed(&[pool_authority_signer.clone()])?;
-    let next_reserve_a = checked_sub(pool_state.reserve_a, checked_mul_div(args.amount_in, pool_state.reserve_a, checked_add(pool_state.reserve_b, args.amount_in)?)?)?;
+    }.invoke_signed(core::slice::from_ref(&pool_authority_signer))?;
+    let next_reserve_a = checked_sub(pool_state.reserve_a, amount_out)?;
     let next_reserve_b = checked_add(pool_state.reserve_b, args.amount_in)?;
     pool_state.reserve_a = next_reserve_a;
     pool_state.reserve_b = next_reserve_b;
     PoolState::store(pool, &pool_state)?;
+    Swap {
+        amount_in: args.amount_in,
+        amount_out,
+    }.emit()?;
+    SwapBforAReturn {
+        amount_out,
+    }.set_return_data()?;
     Ok(())
 }
 
+
+fn handle_pool_summary(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    if accounts.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let pool = &accounts[0];
+    
+    // State account must be owned by this program
+    if !pool.is_owned_by(program_id) {
+        return Err(AmmError::ConstraintOwner.into());
+    }
+    let pool_state = PoolState::load(pool)?;
+    PoolSummary {
+        token_mint_a: pool_state.token_mint_a,
+        token_mint_b: pool_state.token_mint_b,
+        lp_mint: pool_state.lp_mint,
+        reserve_a: pool_state.reserve_a,
+        reserve_b: pool_state.reserve_b,
+        total_lp: pool_state.total_lp,
+        spot_price_a: Ratio {
+            numerator: pool_state.reserve_b,
+            denominator: pool_state.reserve_a,
+        },
+        spot_price_b: Ratio {
+            numerator: pool_state.reserve_a,
+            denominator: pool_state.reserve_b,
+        },
+    }.set_return_data()?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod math_tests {
+    use super::*;
+
+    fn error(error: AmmError) -> ProgramError {
+        error.into()
+    }
+
+    #[test]
+    fn add_checks_every_integer_width() {
+        assert_eq!(checked_add(u8::MAX, 1), Err(error(AmmError::Overflow)));
+        assert_eq!(checked_add(u128::MAX - 1, 1), Ok(u128::MAX));
+        assert_eq!(checked_add(u128::MAX, 1), Err(error(AmmError::Overflow)));
+    }
+
+    #[test]
+    fn sub_checks_every_integer_width() {
+        assert_eq!(checked_sub(0u32, 1), Err(error(AmmError::Overflow)));
+        assert_eq!(checked_sub(0i64, 5), Ok(-5));
+    }
+
+    #[test]
+    fn mul_div_uses_wide_intermediates() {
+        assert_eq!(checked_mul_div(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(checked_mul_div(7, 3, 2), Ok(10));
+        assert_eq!(checked_mul_div(u64::MAX, 2, 1), Err(error(AmmError::Overflow)));
+        assert_eq!(checked_mul_div(1, 1, 0), Err(error(AmmError::DivideByZero)));
+    }
+
+    #[test]
+    fn bps_takes_a_fraction_of_ten_thousand() {
+        assert_eq!(checked_bps(1_000_000, 30), Ok(3_000));
+        assert_eq!(checked_bps(333, 10_000), Ok(333));
+        assert_eq!(checked_bps(9_999, 1), Ok(0));
+        assert_eq!(checked_bps(u64::MAX, 20_000), Err(error(AmmError::Overflow)));
+    }
+
+    #[test]
+    fn isqrt_floors() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(u128::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn sqrt_mul_uses_a_wide_product() {
+        assert_eq!(sqrt_mul(1_000_000, 2_000_000), 1_414_213);
+        assert_eq!(sqrt_mul(u64::MAX, u64::MAX), u64::MAX);
+    }
+}
+
+#[cfg(test)]
+mod token_layout_tests {
+    use super::*;
+    use pinocchio_tkn::state::MINT_SIZE;
+
+    #[test]
+    fn token_layout_reads_the_token_2022_account_type() {
+        let mut data = [0u8; TOKEN_ACCOUNT_SIZE + 5];
+        assert_eq!(check_token_layout(&data[..TOKEN_ACCOUNT_SIZE], TOKEN_ACCOUNT_SIZE, 2), Ok(()));
+        assert_eq!(check_token_layout(&data[..MINT_SIZE], MINT_SIZE, 1), Ok(()));
+        assert_eq!(check_token_layout(&data[..MINT_SIZE + 1], MINT_SIZE, 1), Err(AmmError::InvalidAccountLayout.into()));
+        data[TOKEN_ACCOUNT_SIZE] = 2;
+        assert_eq!(check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2), Ok(()));
+        assert_eq!(check_token_layout(&data, MINT_SIZE, 1), Err(AmmError::InvalidAccountLayout.into()));
+        data[TOKEN_ACCOUNT_SIZE] = 1;
+        assert_eq!(check_token_layout(&data, MINT_SIZE, 1), Ok(()));
+        assert_eq!(check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2), Err(AmmError::InvalidAccountLayout.into()));
+    }
+}
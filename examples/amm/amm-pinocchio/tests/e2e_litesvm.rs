@@ -1,3 +1,8 @@
+mod common;
+
+use amm_pinocchio::{PoolSummary, Ratio, Swap, SwapAforBReturn};
+use common::pool_state;
+use base64::{prelude::BASE64_STANDARD, Engine};
 use litesvm::LiteSVM;
 use solana_keypair::Keypair;
 use solana_program_pack::Pack;
@@ -16,25 +21,13 @@ use spl_token_interface::{
   ID as TOKEN_PROGRAM_ID,
 };
 
-#[derive(Debug)]
-struct PoolState {
-    reserve_a: u64,
-    reserve_b: u64,
-    total_lp: u64,
-}
-
-fn decode_pool_state(data: &[u8]) -> PoolState {
-    let mut reserve_a_bytes = [0u8; 8];
-    reserve_a_bytes.copy_from_slice(&data[128..136]);
-    let mut reserve_b_bytes = [0u8; 8];
-    reserve_b_bytes.copy_from_slice(&data[136..144]);
-    let mut total_lp_bytes = [0u8; 8];
-    total_lp_bytes.copy_from_slice(&data[144..152]);
-    PoolState {
-        reserve_a: u64::from_le_bytes(reserve_a_bytes),
-        reserve_b: u64::from_le_bytes(reserve_b_bytes),
-        total_lp: u64::from_le_bytes(total_lp_bytes),
-    }
+// `sol_log_data` payloads show up in the logs as `Program data: <base64>`.
+fn decode_swap_events(logs: &[String]) -> Vec<Swap> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|data| BASE64_STANDARD.decode(data).ok())
+        .filter_map(|data| Swap::decode(&data))
+        .collect()
 }
 
 fn encode_create_pool(token_mint_a: Pubkey, token_mint_b: Pubkey, lp_mint: Pubkey) -> Vec<u8> {
@@ -52,9 +45,10 @@ fn encode_add_liquidity(amount_a: u64, amount_b: u64) -> Vec<u8> {
     data
 }
 
-fn encode_u64(ix_discriminator: u8, value: u64) -> Vec<u8> {
+fn encode_swap(ix_discriminator: u8, amount_in: u64, min_amount_out: u64) -> Vec<u8> {
     let mut data = vec![ix_discriminator];
-    data.extend_from_slice(&value.to_le_bytes());
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
     data
 }
 
@@ -317,7 +311,7 @@ fn e2e_create_add_and_swap() {
             AccountMeta::new(vault_b.pubkey(), false),
             AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
         ],
-        data: encode_u64(3, 100_000),
+        data: encode_swap(3, 100_000, 1),
     };
 
     let tx = Transaction::new_signed_with_payer(
@@ -326,15 +320,31 @@ fn e2e_create_add_and_swap() {
         &[&payer],
         svm.latest_blockhash(),
     );
-    svm.send_transaction(tx).unwrap();
+    let meta = svm.send_transaction(tx).unwrap();
 
     let pool_account = svm.get_account(&pool).unwrap();
-    let state = decode_pool_state(&pool_account.data);
-    let expected_swap_out = ((100_000u128) * (2_000_000u128) / (1_000_000u128 + 100_000u128)) as u64;
+    let state = pool_state(&pool_account);
+    // 100_000 in, less the 0.3% fee of 300
+    let expected_swap_out = ((99_700u128) * (2_000_000u128) / (1_000_000u128 + 99_700u128)) as u64;
+    // floor(sqrt(1_000_000 * 2_000_000))
+    let expected_lp = 1_414_213;
 
     assert_eq!(state.reserve_a, 1_000_000 + 100_000);
     assert_eq!(state.reserve_b, 2_000_000 - expected_swap_out);
-    assert_eq!(state.total_lp, 1_000_000);
+    assert_eq!(state.total_lp, expected_lp);
+    assert_eq!(
+        decode_swap_events(&meta.logs),
+        vec![Swap {
+            amount_in: 100_000,
+            amount_out: expected_swap_out,
+        }]
+    );
+    assert_eq!(
+        SwapAforBReturn::decode(&meta.return_data.data),
+        Some(SwapAforBReturn {
+            amount_out: expected_swap_out,
+        })
+    );
 
     let user_a_account = svm.get_account(&user_a.pubkey()).unwrap();
     let user_a_state = TokenAccount::unpack(&user_a_account.data).unwrap();
@@ -349,7 +359,33 @@ fn e2e_create_add_and_swap() {
 
     assert_eq!(user_a_state.amount, 100_000);
     assert_eq!(user_b_state.amount, expected_swap_out);
-    assert_eq!(user_lp_state.amount, 1_000_000);
+    assert_eq!(user_lp_state.amount, expected_lp);
     assert_eq!(vault_a_state.amount, 1_000_000 + 100_000);
     assert_eq!(vault_b_state.amount, 2_000_000 - expected_swap_out);
+
+    let pool_summary_ix = Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new_readonly(pool, false)],
+        data: vec![5],
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[pool_summary_ix],
+        Some(&payer_pubkey),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    let simulated = svm.simulate_transaction(tx).unwrap();
+    let summary = PoolSummary::decode(&simulated.meta.return_data.data).unwrap();
+
+    assert_eq!(summary.token_mint_a, token_mint_a.pubkey().to_bytes());
+    assert_eq!(summary.reserve_a, state.reserve_a);
+    assert_eq!(summary.reserve_b, state.reserve_b);
+    assert_eq!(summary.total_lp, expected_lp);
+    assert_eq!(
+        summary.spot_price_a,
+        Ratio {
+            numerator: state.reserve_b,
+            denominator: state.reserve_a,
+        }
+    );
 }
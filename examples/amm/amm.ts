@@ -5,6 +5,10 @@ import {
   arg,
   ata,
   bump,
+  compute,
+  emit,
+  errors,
+  events,
   expr,
   field,
   mint,
@@ -12,6 +16,8 @@ import {
   program,
   programAccount,
   pubkey,
+  ratio,
+  require,
   state,
   token,
   tx,
@@ -25,6 +31,14 @@ export const Amm = program({
   programId: '4mm1hQK4R7c1yD4g8MsjvKZxQxJ7f5WmB1a2c3d4e5f6',
 });
 
+Amm.errors = errors({
+  SlippageExceeded: 'Swap output is below the minimum amount out',
+});
+
+Amm.events = events({
+  Swap: { amountIn: u64(), amountOut: u64() },
+});
+
 Amm.accounts.pool = account({
   name: 'PoolState',
   schema: {
@@ -61,7 +75,7 @@ Amm.createPool = tx({
     mint('lpMint', arg('lpMint'), { writable: true }),
     ata('vaultA', accountRef('poolAuthority'), arg('tokenMintA'), { writable: true }),
     ata('vaultB', accountRef('poolAuthority'), arg('tokenMintB'), { writable: true }),
-    programAccount('tokenProgram'),
+    programAccount('tokenProgram', { address: 'token' }),
   ],
   ops: [
     state.init('pool', {
@@ -79,7 +93,7 @@ Amm.createPool = tx({
 
 const lpToMint = expr.if(
   expr.eq(field('pool', 'totalLp'), expr.const(0)),
-  expr.arg('amountA'),
+  expr.sqrt(expr.mul(expr.arg('amountA'), expr.arg('amountB'))),
   expr.div(
     expr.mul(expr.arg('amountA'), field('pool', 'totalLp')),
     field('pool', 'reserveA'),
@@ -104,7 +118,7 @@ Amm.addLiquidity = tx({
     ata('vaultB', accountRef('poolAuthority'), field('pool', 'tokenMintB'), { writable: true }),
     mint('lpMint', field('pool', 'lpMint'), { writable: true }),
     ata('userLp', accountRef('user'), field('pool', 'lpMint'), { writable: true }),
-    programAccount('tokenProgram'),
+    programAccount('tokenProgram', { address: 'token' }),
   ],
   ops: [
     token.transfer({
@@ -164,7 +178,7 @@ Amm.removeLiquidity = tx({
     ata('userB', accountRef('user'), field('pool', 'tokenMintB'), { writable: true }),
     ata('vaultA', accountRef('poolAuthority'), field('pool', 'tokenMintA'), { writable: true }),
     ata('vaultB', accountRef('poolAuthority'), field('pool', 'tokenMintB'), { writable: true }),
-    programAccount('tokenProgram'),
+    programAccount('tokenProgram', { address: 'token' }),
   ],
   ops: [
     token.burn({
@@ -198,15 +212,22 @@ Amm.removeLiquidity = tx({
   ],
 });
 
+const amountOut = expr.local('amountOut');
+
+// Swaps price the input net of a 0.3% fee, which stays in the pool: the reserve still
+// grows by the whole `amountIn`.
+const amountInAfterFee = expr.sub(expr.arg('amountIn'), expr.bps(expr.arg('amountIn'), expr.const(30)));
+
 const swapAOut = expr.div(
-  expr.mul(expr.arg('amountIn'), field('pool', 'reserveB')),
-  expr.add(field('pool', 'reserveA'), expr.arg('amountIn')),
+  expr.mul(amountInAfterFee, field('pool', 'reserveB')),
+  expr.add(field('pool', 'reserveA'), amountInAfterFee),
 );
 
 Amm.swapAForB = tx({
   name: 'swapAForB',
   args: {
     amountIn: u64(),
+    minAmountOut: u64(),
   },
   accounts: [
     accountMeta('user', { signer: true }),
@@ -218,9 +239,11 @@ Amm.swapAForB = tx({
     ata('userB', accountRef('user'), field('pool', 'tokenMintB'), { writable: true }),
     ata('vaultA', accountRef('poolAuthority'), field('pool', 'tokenMintA'), { writable: true }),
     ata('vaultB', accountRef('poolAuthority'), field('pool', 'tokenMintB'), { writable: true }),
-    programAccount('tokenProgram'),
+    programAccount('tokenProgram', { address: 'token' }),
   ],
   ops: [
+    compute('amountOut', swapAOut),
+    require(expr.gte(amountOut, expr.arg('minAmountOut')), 'SlippageExceeded'),
     token.transfer({
       from: 'userA',
       to: 'vaultA',
@@ -233,25 +256,28 @@ Amm.swapAForB = tx({
       to: 'userB',
       authority: 'poolAuthority',
       signer: 'poolAuthority',
-      amount: swapAOut,
+      amount: amountOut,
       program: 'tokenProgram',
     }),
     state.update('pool', {
       reserveA: expr.add(field('pool', 'reserveA'), expr.arg('amountIn')),
-      reserveB: expr.sub(field('pool', 'reserveB'), swapAOut),
+      reserveB: expr.sub(field('pool', 'reserveB'), amountOut),
     }),
+    emit('Swap', { amountIn: expr.arg('amountIn'), amountOut }),
   ],
+  returns: { amountOut },
 });
 
 const swapBOut = expr.div(
-  expr.mul(expr.arg('amountIn'), field('pool', 'reserveA')),
-  expr.add(field('pool', 'reserveB'), expr.arg('amountIn')),
+  expr.mul(amountInAfterFee, field('pool', 'reserveA')),
+  expr.add(field('pool', 'reserveB'), amountInAfterFee),
 );
 
 Amm.swapBForA = tx({
   name: 'swapBForA',
   args: {
     amountIn: u64(),
+    minAmountOut: u64(),
   },
   accounts: [
     accountMeta('user', { signer: true }),
@@ -263,9 +289,11 @@ Amm.swapBForA = tx({
     ata('userB', accountRef('user'), field('pool', 'tokenMintB'), { writable: true }),
     ata('vaultA', accountRef('poolAuthority'), field('pool', 'tokenMintA'), { writable: true }),
     ata('vaultB', accountRef('poolAuthority'), field('pool', 'tokenMintB'), { writable: true }),
-    programAccount('tokenProgram'),
+    programAccount('tokenProgram', { address: 'token' }),
   ],
   ops: [
+    compute('amountOut', swapBOut),
+    require(expr.gte(amountOut, expr.arg('minAmountOut')), 'SlippageExceeded'),
     token.transfer({
       from: 'userB',
       to: 'vaultB',
@@ -278,14 +306,16 @@ Amm.swapBForA = tx({
       to: 'userA',
       authority: 'poolAuthority',
       signer: 'poolAuthority',
-      amount: swapBOut,
+      amount: amountOut,
       program: 'tokenProgram',
     }),
     state.update('pool', {
-      reserveA: expr.sub(field('pool', 'reserveA'), swapBOut),
+      reserveA: expr.sub(field('pool', 'reserveA'), amountOut),
       reserveB: expr.add(field('pool', 'reserveB'), expr.arg('amountIn')),
     }),
+    emit('Swap', { amountIn: expr.arg('amountIn'), amountOut }),
   ],
+  returns: { amountOut },
 });
 
 Amm.poolSummary = view({
@@ -300,7 +330,7 @@ Amm.poolSummary = view({
     reserveA: u64(),
     reserveB: u64(),
     totalLp: u64(),
-    priceAInB: 'ratio',
-    priceBInA: 'ratio',
+    spotPriceA: ratio(field('pool', 'reserveB'), field('pool', 'reserveA')),
+    spotPriceB: ratio(field('pool', 'reserveA'), field('pool', 'reserveB')),
   },
 });
@@ -0,0 +1,1044 @@
+// AUTO-GENERATED - DO NOT EDIT
+#![no_std]
+
+use pinocchio::{
+    account_info::AccountInfo,
+    cpi::invoke_signed,
+    instruction::{AccountMeta, Instruction, Signer},
+    program_entrypoint,
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+    seeds,
+    sysvars::{
+        rent::{Rent, ACCOUNT_STORAGE_OVERHEAD, DEFAULT_EXEMPTION_THRESHOLD, DEFAULT_LAMPORTS_PER_BYTE_YEAR},
+        Sysvar,
+    },
+    ProgramResult,
+};
+
+program_entrypoint!(process_instruction);
+
+#[cfg(target_arch = "bpf")]
+use pinocchio::default_allocator;
+
+#[cfg(target_arch = "bpf")]
+default_allocator!();
+
+#[cfg(target_arch = "bpf")]
+#[panic_handler]
+fn panic_handler(_info: &core::panic::PanicInfo<'_>) -> ! {
+    unsafe {
+        pinocchio::syscalls::abort();
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+extern crate std;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum RegistryError {
+    /// Arithmetic overflow
+    Overflow = 1000,
+    /// Division by zero
+    DivideByZero = 1001,
+    /// Unknown instruction discriminator
+    UnknownInstruction = 1002,
+    /// Account must be writable
+    AccountNotWritable = 1003,
+    /// Account does not match its PDA seeds
+    ConstraintSeeds = 1004,
+    /// Account is not owned by the expected program
+    ConstraintOwner = 1005,
+    /// Token account owner does not match
+    ConstraintTokenOwner = 1006,
+    /// Token account mint does not match
+    ConstraintMint = 1007,
+    /// Account address does not match
+    ConstraintAddress = 1008,
+    /// Program account does not match the expected program
+    ConstraintProgram = 1009,
+    /// Program account is not executable
+    ProgramNotExecutable = 1010,
+    /// Account discriminator does not match
+    AccountDiscriminatorMismatch = 1011,
+    /// Account is already initialized
+    AccountAlreadyInitialized = 1012,
+    /// The same account was passed twice as mutable state
+    DuplicateMutableAccount = 1013,
+    /// An account does not match the key it must equal
+    ConstraintHasOne = 1014,
+    /// A vec argument index is out of bounds
+    IndexOutOfBounds = 1015,
+    /// Token account is frozen, or not frozen for a thaw
+    ConstraintTokenState = 1016,
+    /// Instruction data does not decode as the instruction args
+    InvalidArgs = 1017,
+    /// Account data does not decode as its layout
+    InvalidAccountLayout = 1018,
+    /// Account does not match its equals constraint
+    ConstraintEquals = 1019,
+    /// Reward reason is empty
+    EmptyReason = 6000,
+    /// Reward proof must be 32 bytes
+    InvalidProof = 6001,
+    /// Club is archived
+    ClubArchived = 6002,
+}
+
+impl From<RegistryError> for ProgramError {
+    fn from(error: RegistryError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemberState {
+    pub authority: Pubkey,
+    pub points: u64,
+    pub bump: u8,
+}
+
+impl MemberState {
+    pub const LEN: usize = 42;
+    pub const DISCRIMINATOR: [u8; 1] = [41];
+
+    // Freshly allocated accounts are zeroed, so any non-zero discriminator byte
+    // means the account was initialized, possibly as another account type.
+    fn is_initialized(account: &AccountInfo) -> Result<bool, ProgramError> {
+        let data = account.try_borrow_data()?;
+        Ok(data.iter().take(Self::DISCRIMINATOR.len()).any(|byte| *byte != 0))
+    }
+
+    fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = account.try_borrow_data()?;
+        Self::unpack(&data)
+    }
+
+    fn store(account: &AccountInfo, state: &Self) -> Result<(), ProgramError> {
+        let mut data = account.try_borrow_mut_data()?;
+        state.pack(&mut data)
+    }
+
+    /// Decodes account data as `load` does, so host code can read raw account bytes.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        if data[..Self::DISCRIMINATOR.len()] != Self::DISCRIMINATOR {
+            return Err(RegistryError::AccountDiscriminatorMismatch.into());
+        }
+        Self::read(data).ok_or(RegistryError::InvalidAccountLayout.into())
+    }
+
+    /// Writes the discriminator and fields to the first `LEN` bytes, as `store` does.
+    pub fn pack(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[..Self::DISCRIMINATOR.len()].copy_from_slice(&Self::DISCRIMINATOR);
+        write_pubkey(data, 1, &self.authority)?;
+        write_u64(data, 33, self.points)?;
+        write_u8(data, 41, self.bump)?;
+        Ok(())
+    }
+
+    fn read(data: &[u8]) -> Option<Self> {
+        let authority = read_pubkey(data, 1)?;
+        let points = read_u64(data, 33)?;
+        let bump = read_u8(data, 41)?;
+        Some(Self {
+            authority,
+            points,
+            bump,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ClubStateFeaturedTier {
+    #[default]
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl ClubStateFeaturedTier {
+    fn read(data: &[u8], offset: usize) -> Option<Self> {
+        match data.get(offset) {
+            Some(0) => Some(Self::Bronze),
+            Some(1) => Some(Self::Silver),
+            Some(2) => Some(Self::Gold),
+            _ => None,
+        }
+    }
+
+    fn write(&self, data: &mut [u8], offset: usize) -> Result<(), ProgramError> {
+        let Some(tag) = data.get_mut(offset) else {
+            return Err(RegistryError::InvalidAccountLayout.into());
+        };
+        *tag = *self as u8;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ClubStateStatus {
+    #[default]
+    Recruiting,
+    Full,
+    Archived,
+}
+
+impl ClubStateStatus {
+    fn read(data: &[u8], offset: usize) -> Option<Self> {
+        match data.get(offset) {
+            Some(0) => Some(Self::Recruiting),
+            Some(1) => Some(Self::Full),
+            Some(2) => Some(Self::Archived),
+            _ => None,
+        }
+    }
+
+    fn write(&self, data: &mut [u8], offset: usize) -> Result<(), ProgramError> {
+        let Some(tag) = data.get_mut(offset) else {
+            return Err(RegistryError::InvalidAccountLayout.into());
+        };
+        *tag = *self as u8;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClubStateSettings {
+    pub open: bool,
+    pub max_members: u32,
+}
+
+impl ClubStateSettings {
+    fn read(data: &[u8], offset: usize) -> Option<Self> {
+        let open = read_bool(data, offset)?;
+        let max_members = read_u32(data, offset + 1)?;
+        Some(Self {
+            open,
+            max_members,
+        })
+    }
+
+    fn write(&self, data: &mut [u8], offset: usize) -> Result<(), ProgramError> {
+        write_bool(data, offset, self.open)?;
+        write_u32(data, offset + 1, self.max_members)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClubStateTiers {
+    pub min_points: u64,
+    pub bonus_bps: u16,
+}
+
+impl ClubStateTiers {
+    fn read(data: &[u8], offset: usize) -> Option<Self> {
+        let min_points = read_u64(data, offset)?;
+        let bonus_bps = read_u16(data, offset + 8)?;
+        Some(Self {
+            min_points,
+            bonus_bps,
+        })
+    }
+
+    fn write(&self, data: &mut [u8], offset: usize) -> Result<(), ProgramError> {
+        write_u64(data, offset, self.min_points)?;
+        write_u16(data, offset + 8, self.bonus_bps)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClubState {
+    pub admin: Pubkey,
+    pub members: u64,
+    pub settings: ClubStateSettings,
+    pub tiers: [ClubStateTiers; 3],
+    pub featured_tier: Option<ClubStateFeaturedTier>,
+    pub status: ClubStateStatus,
+    pub successor: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl ClubState {
+    pub const LEN: usize = 113;
+    pub const DISCRIMINATOR: [u8; 1] = [228];
+
+    // Freshly allocated accounts are zeroed, so any non-zero discriminator byte
+    // means the account was initialized, possibly as another account type.
+    fn is_initialized(account: &AccountInfo) -> Result<bool, ProgramError> {
+        let data = account.try_borrow_data()?;
+        Ok(data.iter().take(Self::DISCRIMINATOR.len()).any(|byte| *byte != 0))
+    }
+
+    fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = account.try_borrow_data()?;
+        Self::unpack(&data)
+    }
+
+    fn store(account: &AccountInfo, state: &Self) -> Result<(), ProgramError> {
+        let mut data = account.try_borrow_mut_data()?;
+        state.pack(&mut data)
+    }
+
+    /// Decodes account data as `load` does, so host code can read raw account bytes.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        if data[..Self::DISCRIMINATOR.len()] != Self::DISCRIMINATOR {
+            return Err(RegistryError::AccountDiscriminatorMismatch.into());
+        }
+        Self::read(data).ok_or(RegistryError::InvalidAccountLayout.into())
+    }
+
+    /// Writes the discriminator and fields to the first `LEN` bytes, as `store` does.
+    pub fn pack(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[..Self::DISCRIMINATOR.len()].copy_from_slice(&Self::DISCRIMINATOR);
+        write_pubkey(data, 1, &self.admin)?;
+        write_u64(data, 33, self.members)?;
+        self.settings.write(data, 41)?;
+        for (index, item) in self.tiers.iter().enumerate() {
+            item.write(data, 46 + index * 10)?;
+        }
+        write_option(data, 76, 1, self.featured_tier, |data, offset, value| value.write(data, offset))?;
+        self.status.write(data, 78)?;
+        write_option(data, 79, 32, self.successor, |data, offset, value| write_pubkey(data, offset, &value))?;
+        write_u8(data, 112, self.bump)?;
+        Ok(())
+    }
+
+    fn read(data: &[u8]) -> Option<Self> {
+        let admin = read_pubkey(data, 1)?;
+        let members = read_u64(data, 33)?;
+        let settings = ClubStateSettings::read(data, 41)?;
+        let mut tiers = <[ClubStateTiers; 3]>::default();
+        for (index, item) in tiers.iter_mut().enumerate() {
+            *item = ClubStateTiers::read(data, 46 + index * 10)?;
+        }
+        let featured_tier = read_option(data, 76, ClubStateFeaturedTier::read)?;
+        let status = ClubStateStatus::read(data, 78)?;
+        let successor = read_option(data, 79, read_pubkey)?;
+        let bump = read_u8(data, 112)?;
+        Some(Self {
+            admin,
+            members,
+            settings,
+            tiers,
+            featured_tier,
+            status,
+            successor,
+            bump,
+        })
+    }
+}
+
+
+const SYSTEM_PROGRAM_ID: Pubkey = [0u8; 32];
+
+fn minimum_balance(space: usize) -> u64 {
+    if let Ok(rent) = Rent::get() {
+        return rent.minimum_balance(space);
+    }
+    let bytes = space as u64 + ACCOUNT_STORAGE_OVERHEAD;
+    ((bytes * DEFAULT_LAMPORTS_PER_BYTE_YEAR) as f64 * DEFAULT_EXEMPTION_THRESHOLD) as u64
+}
+
+fn create_program_account(
+    payer: &AccountInfo,
+    new_account: &AccountInfo,
+    owner: &Pubkey,
+    space: usize,
+    signer: Option<&Signer>,
+) -> ProgramResult {
+    let lamports = minimum_balance(space);
+    let mut data = [0u8; 4 + 8 + 8 + 32];
+    data[..4].copy_from_slice(&0u32.to_le_bytes());
+    data[4..12].copy_from_slice(&lamports.to_le_bytes());
+    data[12..20].copy_from_slice(&(space as u64).to_le_bytes());
+    data[20..52].copy_from_slice(owner);
+    let ix_accounts = [
+        AccountMeta::writable_signer(payer.key()),
+        AccountMeta::writable_signer(new_account.key()),
+    ];
+    let ix = Instruction {
+        program_id: &SYSTEM_PROGRAM_ID,
+        data: &data,
+        accounts: &ix_accounts,
+    };
+    if let Some(signer) = signer {
+        let signers = [signer.clone()];
+        invoke_signed::<2>(&ix, &[payer, new_account], &signers)?;
+    } else {
+        invoke_signed::<2>(&ix, &[payer, new_account], &[])?;
+    }
+    Ok(())
+}
+
+fn check_program_account(account: &AccountInfo, program_ids: &[Pubkey]) -> ProgramResult {
+    if !program_ids.contains(account.key()) {
+        return Err(RegistryError::ConstraintProgram.into());
+    }
+    if !account.executable() {
+        return Err(RegistryError::ProgramNotExecutable.into());
+    }
+    Ok(())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    if data.len() < offset + 32 {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&data[offset..offset + 32]);
+    Some(out)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    if data.len() < offset + 8 {
+        return None;
+    }
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data[offset..offset + 8]);
+    Some(u64::from_le_bytes(buf))
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).copied()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    if data.len() < offset + 2 {
+        return None;
+    }
+    let mut buf = [0u8; 2];
+    buf.copy_from_slice(&data[offset..offset + 2]);
+    Some(u16::from_le_bytes(buf))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    if data.len() < offset + 4 {
+        return None;
+    }
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&data[offset..offset + 4]);
+    Some(u32::from_le_bytes(buf))
+}
+
+fn read_bool(data: &[u8], offset: usize) -> Option<bool> {
+    match data.get(offset) {
+        Some(0) => Some(false),
+        Some(1) => Some(true),
+        _ => None,
+    }
+}
+
+fn write_pubkey(data: &mut [u8], offset: usize, value: &Pubkey) -> Result<(), ProgramError> {
+    if data.len() < offset + 32 {
+        return Err(RegistryError::InvalidAccountLayout.into());
+    }
+    data[offset..offset + 32].copy_from_slice(value);
+    Ok(())
+}
+
+fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Result<(), ProgramError> {
+    if data.len() < offset + 8 {
+        return Err(RegistryError::InvalidAccountLayout.into());
+    }
+    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+fn write_u8(data: &mut [u8], offset: usize, value: u8) -> Result<(), ProgramError> {
+    if data.len() <= offset {
+        return Err(RegistryError::InvalidAccountLayout.into());
+    }
+    data[offset] = value;
+    Ok(())
+}
+
+fn write_u16(data: &mut [u8], offset: usize, value: u16) -> Result<(), ProgramError> {
+    if data.len() < offset + 2 {
+        return Err(RegistryError::InvalidAccountLayout.into());
+    }
+    data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) -> Result<(), ProgramError> {
+    if data.len() < offset + 4 {
+        return Err(RegistryError::InvalidAccountLayout.into());
+    }
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+fn write_bool(data: &mut [u8], offset: usize, value: bool) -> Result<(), ProgramError> {
+    if data.len() <= offset {
+        return Err(RegistryError::InvalidAccountLayout.into());
+    }
+    data[offset] = u8::from(value);
+    Ok(())
+}
+
+fn read_option<T>(
+    data: &[u8],
+    offset: usize,
+    read: fn(&[u8], usize) -> Option<T>,
+) -> Option<Option<T>> {
+    match data.get(offset) {
+        Some(0) => Some(None),
+        Some(1) => read(data, offset + 1).map(Some),
+        _ => None,
+    }
+}
+
+fn write_option<T>(
+    data: &mut [u8],
+    offset: usize,
+    size: usize,
+    value: Option<T>,
+    write: fn(&mut [u8], usize, T) -> Result<(), ProgramError>,
+) -> Result<(), ProgramError> {
+    let Some(bytes) = data.get_mut(offset..offset + 1 + size) else {
+        return Err(RegistryError::InvalidAccountLayout.into());
+    };
+    bytes.fill(0);
+    match value {
+        Some(value) => {
+            bytes[0] = 1;
+            write(data, offset + 1, value)
+        }
+        None => Ok(()),
+    }
+}
+
+// Instruction data lengths fit in usize on every Solana target.
+fn arg_len(len: usize) -> u64 {
+    len as u64
+}
+
+// Borsh-style length prefix: a little-endian u32 count, capped at `max_len`.
+fn read_len(data: &[u8], offset: usize, max_len: usize) -> Result<usize, ProgramError> {
+    let len = read_u32(data, offset).ok_or(RegistryError::InvalidArgs)? as usize;
+    if len > max_len {
+        return Err(RegistryError::InvalidArgs.into());
+    }
+    Ok(len)
+}
+
+fn read_bytes(data: &[u8], offset: usize, max_len: usize) -> Result<&[u8], ProgramError> {
+    let len = read_len(data, offset, max_len)?;
+    data.get(offset + 4..offset + 4 + len)
+        .ok_or(RegistryError::InvalidArgs.into())
+}
+
+fn read_string(data: &[u8], offset: usize, max_len: usize) -> Result<&str, ProgramError> {
+    core::str::from_utf8(read_bytes(data, offset, max_len)?)
+        .map_err(|_| RegistryError::InvalidArgs.into())
+}
+
+// A vec arg stays in the instruction data. That data has no alignment guarantee, so a
+// `&[u64]` cannot borrow it; elements are decoded on access instead.
+#[derive(Clone, Copy)]
+struct ArgVec<'a, T> {
+    data: &'a [u8],
+    size: usize,
+    read: fn(&[u8], usize) -> Option<T>,
+}
+
+impl<T> ArgVec<'_, T> {
+    fn len(&self) -> usize {
+        self.data.len() / self.size
+    }
+
+    fn get(&self, index: u64) -> Result<T, ProgramError> {
+        let index = usize::try_from(index).map_err(|_| RegistryError::IndexOutOfBounds)?;
+        if index >= self.len() {
+            return Err(RegistryError::IndexOutOfBounds.into());
+        }
+        (self.read)(self.data, index * self.size).ok_or(RegistryError::InvalidArgs.into())
+    }
+}
+
+fn read_vec<T>(
+    data: &[u8],
+    offset: usize,
+    max_len: usize,
+    size: usize,
+    read: fn(&[u8], usize) -> Option<T>,
+) -> Result<ArgVec<'_, T>, ProgramError> {
+    let len = read_len(data, offset, max_len)?;
+    let data = data
+        .get(offset + 4..offset + 4 + len * size)
+        .ok_or(RegistryError::InvalidArgs)?;
+    Ok(ArgVec { data, size, read })
+}
+
+// Lets the checked helpers below take any integer type the DSL supports.
+trait CheckedInt: Copy + PartialEq {
+    fn add_checked(self, other: Self) -> Option<Self>;
+    fn sub_checked(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_int {
+    ($($ty:ty),*) => {$(
+        impl CheckedInt for $ty {
+            fn add_checked(self, other: Self) -> Option<Self> {
+                self.checked_add(other)
+            }
+            fn sub_checked(self, other: Self) -> Option<Self> {
+                self.checked_sub(other)
+            }
+        }
+    )*};
+}
+
+impl_checked_int!(u8, u16, u32, u64, u128, i64);
+
+fn checked_add<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.add_checked(b).ok_or(RegistryError::Overflow)?)
+}
+
+fn checked_sub<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.sub_checked(b).ok_or(RegistryError::Overflow)?)
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data.is_empty() {
+        return Err(RegistryError::UnknownInstruction.into());
+    }
+
+    match data[0] {
+        0 => handle_join(program_id, accounts, &data[1..]),
+        1 => handle_earn(program_id, accounts, &data[1..]),
+        2 => handle_found_club(program_id, accounts, &data[1..]),
+        3 => handle_gift(program_id, accounts, &data[1..]),
+        4 => handle_configure_club(program_id, accounts, &data[1..]),
+        5 => handle_claim_reward(program_id, accounts, &data[1..]),
+        6 => handle_nominate_successor(program_id, accounts, &data[1..]),
+        7 => handle_archive_club(program_id, accounts, &data[1..]),
+        _ => Err(RegistryError::UnknownInstruction.into()),
+    }
+}
+
+
+fn handle_join(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 3 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let authority = &accounts[0];
+    if !authority.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    if !authority.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    let member = &accounts[1];
+    if !member.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    let (member_key, member_bump) = pubkey::find_program_address(&[b"member", authority.key().as_ref()], program_id);
+    if member.key() != &member_key { return Err(RegistryError::ConstraintSeeds.into()); }
+    let system_program = &accounts[2];
+    check_program_account(system_program, &[SYSTEM_PROGRAM_ID])?;
+    
+    if !member.is_owned_by(program_id) {
+        let member_bump_ref = [member_bump];
+        let member_seeds = seeds!(b"member", authority.key().as_ref(), &member_bump_ref);
+        let member_signer = Signer::from(&member_seeds);
+        create_program_account(authority, member, program_id, MemberState::LEN, Some(&member_signer))?;
+    }
+    if !member.is_owned_by(program_id) { return Err(RegistryError::ConstraintOwner.into()); }
+    let member_initialized = MemberState::is_initialized(member)?;
+    let member_state = if member_initialized {
+        MemberState::load(member)?
+    } else {
+        MemberState {
+            authority: *authority.key(),
+            points: 100u64,
+            bump: member_bump,
+        }
+    };
+    if !member_initialized {
+        MemberState::store(member, &member_state)?;
+    }
+    Ok(())
+}
+
+
+struct EarnArgs {
+    pub points: u64,
+}
+
+fn decode_earn_args(data: &[u8]) -> Result<EarnArgs, ProgramError> {
+    let points = read_u64(data, 0).ok_or(RegistryError::InvalidArgs)?;
+    Ok(EarnArgs {
+        points,
+    })
+}
+
+fn handle_earn(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_earn_args(data)?;
+    let authority = &accounts[0];
+    if !authority.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let member = &accounts[1];
+    if !member.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    
+    // State account must be owned by this program
+    if !member.is_owned_by(program_id) {
+        return Err(RegistryError::ConstraintOwner.into());
+    }
+    let mut member_state = MemberState::load(member)?;
+    if *authority.key() != member_state.authority {
+        return Err(RegistryError::ConstraintHasOne.into());
+    }
+    let next_points = checked_add(member_state.points, args.points)?;
+    member_state.points = next_points;
+    MemberState::store(member, &member_state)?;
+    Ok(())
+}
+
+
+fn handle_found_club(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    if !admin.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    let club = &accounts[1];
+    if !club.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    let (club_key, club_bump) = pubkey::find_program_address(&[b"club", admin.key().as_ref()], program_id);
+    if club.key() != &club_key { return Err(RegistryError::ConstraintSeeds.into()); }
+    let member = &accounts[2];
+    if !member.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    let (member_key, member_bump) = pubkey::find_program_address(&[b"member", admin.key().as_ref()], program_id);
+    if member.key() != &member_key { return Err(RegistryError::ConstraintSeeds.into()); }
+    let system_program = &accounts[3];
+    check_program_account(system_program, &[SYSTEM_PROGRAM_ID])?;
+    
+    if !club.is_owned_by(program_id) {
+        let club_bump_ref = [club_bump];
+        let club_seeds = seeds!(b"club", admin.key().as_ref(), &club_bump_ref);
+        let club_signer = Signer::from(&club_seeds);
+        create_program_account(admin, club, program_id, ClubState::LEN, Some(&club_signer))?;
+    }
+    if !club.is_owned_by(program_id) { return Err(RegistryError::ConstraintOwner.into()); }
+    if ClubState::is_initialized(club)? {
+        return Err(RegistryError::AccountAlreadyInitialized.into());
+    }
+    let club_state = ClubState {
+        admin: *admin.key(),
+        members: 1u64,
+        bump: club_bump,
+        ..ClubState::default()
+    };
+    ClubState::store(club, &club_state)?;
+    if !member.is_owned_by(program_id) {
+        let member_bump_ref = [member_bump];
+        let member_seeds = seeds!(b"member", admin.key().as_ref(), &member_bump_ref);
+        let member_signer = Signer::from(&member_seeds);
+        create_program_account(admin, member, program_id, MemberState::LEN, Some(&member_signer))?;
+    }
+    if !member.is_owned_by(program_id) { return Err(RegistryError::ConstraintOwner.into()); }
+    if MemberState::is_initialized(member)? {
+        return Err(RegistryError::AccountAlreadyInitialized.into());
+    }
+    let member_state = MemberState {
+        authority: *admin.key(),
+        points: 100u64,
+        bump: member_bump,
+    };
+    MemberState::store(member, &member_state)?;
+    Ok(())
+}
+
+
+struct GiftArgs {
+    pub points: u64,
+}
+
+fn decode_gift_args(data: &[u8]) -> Result<GiftArgs, ProgramError> {
+    let points = read_u64(data, 0).ok_or(RegistryError::InvalidArgs)?;
+    Ok(GiftArgs {
+        points,
+    })
+}
+
+fn handle_gift(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 3 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_gift_args(data)?;
+    let authority = &accounts[0];
+    if !authority.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let from = &accounts[1];
+    if !from.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    let to = &accounts[2];
+    if !to.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    
+    // State account must be owned by this program
+    if !from.is_owned_by(program_id) {
+        return Err(RegistryError::ConstraintOwner.into());
+    }
+    // State account must be owned by this program
+    if !to.is_owned_by(program_id) {
+        return Err(RegistryError::ConstraintOwner.into());
+    }
+    if from.key() == to.key() {
+        return Err(RegistryError::DuplicateMutableAccount.into());
+    }
+    let mut from_state = MemberState::load(from)?;
+    let mut to_state = MemberState::load(to)?;
+    if *authority.key() != from_state.authority {
+        return Err(RegistryError::ConstraintHasOne.into());
+    }
+    let next_points = checked_sub(from_state.points, args.points)?;
+    from_state.points = next_points;
+    MemberState::store(from, &from_state)?;
+    let next_points = checked_add(to_state.points, args.points)?;
+    to_state.points = next_points;
+    MemberState::store(to, &to_state)?;
+    Ok(())
+}
+
+
+struct ConfigureClubArgs {
+    pub open: bool,
+    pub max_members: u32,
+    pub gold_min_points: u64,
+    pub gold_bonus_bps: u16,
+}
+
+fn decode_configure_club_args(data: &[u8]) -> Result<ConfigureClubArgs, ProgramError> {
+    let open = read_bool(data, 0).ok_or(RegistryError::InvalidArgs)?;
+    let max_members = read_u32(data, 1).ok_or(RegistryError::InvalidArgs)?;
+    let gold_min_points = read_u64(data, 5).ok_or(RegistryError::InvalidArgs)?;
+    let gold_bonus_bps = read_u16(data, 13).ok_or(RegistryError::InvalidArgs)?;
+    Ok(ConfigureClubArgs {
+        open,
+        max_members,
+        gold_min_points,
+        gold_bonus_bps,
+    })
+}
+
+fn handle_configure_club(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_configure_club_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let club = &accounts[1];
+    if !club.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    
+    // State account must be owned by this program
+    if !club.is_owned_by(program_id) {
+        return Err(RegistryError::ConstraintOwner.into());
+    }
+    let mut club_state = ClubState::load(club)?;
+    if *admin.key() != club_state.admin {
+        return Err(RegistryError::ConstraintHasOne.into());
+    }
+    let next_settings_open = args.open;
+    let next_settings_max_members = args.max_members;
+    let next_tiers_2_min_points = args.gold_min_points;
+    let next_tiers_2_bonus_bps = args.gold_bonus_bps;
+    let next_featured_tier = Some(ClubStateFeaturedTier::Gold);
+    club_state.settings.open = next_settings_open;
+    club_state.settings.max_members = next_settings_max_members;
+    club_state.tiers[2].min_points = next_tiers_2_min_points;
+    club_state.tiers[2].bonus_bps = next_tiers_2_bonus_bps;
+    club_state.featured_tier = next_featured_tier;
+    ClubState::store(club, &club_state)?;
+    Ok(())
+}
+
+
+struct ClaimRewardArgs<'a> {
+    pub reason: &'a str,
+    pub proof: &'a [u8],
+    pub rewards: ArgVec<'a, u64>,
+    pub pick: u64,
+}
+
+fn decode_claim_reward_args(data: &[u8]) -> Result<ClaimRewardArgs<'_>, ProgramError> {
+    let reason = read_string(data, 0, 32)?;
+    let proof = read_bytes(data, 4 + reason.len(), 32)?;
+    let rewards = read_vec(data, 8 + reason.len() + proof.len(), 8, 8, read_u64)?;
+    let pick = read_u64(data, 12 + reason.len() + proof.len() + rewards.len() * 8).ok_or(RegistryError::InvalidArgs)?;
+    Ok(ClaimRewardArgs {
+        reason,
+        proof,
+        rewards,
+        pick,
+    })
+}
+
+fn handle_claim_reward(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_claim_reward_args(data)?;
+    let authority = &accounts[0];
+    if !authority.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let member = &accounts[1];
+    if !member.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    
+    // State account must be owned by this program
+    if !member.is_owned_by(program_id) {
+        return Err(RegistryError::ConstraintOwner.into());
+    }
+    let mut member_state = MemberState::load(member)?;
+    if *authority.key() != member_state.authority {
+        return Err(RegistryError::ConstraintHasOne.into());
+    }
+    if arg_len(args.reason.len()) == 0u64 {
+        return Err(RegistryError::EmptyReason.into());
+    }
+    if arg_len(args.proof.len()) != 32u64 {
+        return Err(RegistryError::InvalidProof.into());
+    }
+    let next_points = checked_add(member_state.points, args.rewards.get(args.pick)?)?;
+    member_state.points = next_points;
+    MemberState::store(member, &member_state)?;
+    Ok(())
+}
+
+
+struct NominateSuccessorArgs {
+    pub successor: Pubkey,
+}
+
+fn decode_nominate_successor_args(data: &[u8]) -> Result<NominateSuccessorArgs, ProgramError> {
+    let successor = read_pubkey(data, 0).ok_or(RegistryError::InvalidArgs)?;
+    Ok(NominateSuccessorArgs {
+        successor,
+    })
+}
+
+fn handle_nominate_successor(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_nominate_successor_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let club = &accounts[1];
+    if !club.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    
+    // State account must be owned by this program
+    if !club.is_owned_by(program_id) {
+        return Err(RegistryError::ConstraintOwner.into());
+    }
+    let mut club_state = ClubState::load(club)?;
+    if *admin.key() != club_state.admin {
+        return Err(RegistryError::ConstraintHasOne.into());
+    }
+    if club_state.status == ClubStateStatus::Archived {
+        return Err(RegistryError::ClubArchived.into());
+    }
+    let next_successor = Some(args.successor);
+    club_state.successor = next_successor;
+    ClubState::store(club, &club_state)?;
+    Ok(())
+}
+
+
+fn handle_archive_club(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let club = &accounts[1];
+    if !club.is_writable() { return Err(RegistryError::AccountNotWritable.into()); }
+    
+    // State account must be owned by this program
+    if !club.is_owned_by(program_id) {
+        return Err(RegistryError::ConstraintOwner.into());
+    }
+    let mut club_state = ClubState::load(club)?;
+    if *admin.key() != club_state.admin {
+        return Err(RegistryError::ConstraintHasOne.into());
+    }
+    let next_status = ClubStateStatus::Archived;
+    let next_successor = None;
+    let next_featured_tier = None;
+    club_state.status = next_status;
+    club_state.successor = next_successor;
+    club_state.featured_tier = next_featured_tier;
+    ClubState::store(club, &club_state)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod math_tests {
+    use super::*;
+
+    fn error(error: RegistryError) -> ProgramError {
+        error.into()
+    }
+
+    #[test]
+    fn add_checks_every_integer_width() {
+        assert_eq!(checked_add(u8::MAX, 1), Err(error(RegistryError::Overflow)));
+        assert_eq!(checked_add(u128::MAX - 1, 1), Ok(u128::MAX));
+        assert_eq!(checked_add(u128::MAX, 1), Err(error(RegistryError::Overflow)));
+    }
+
+    #[test]
+    fn sub_checks_every_integer_width() {
+        assert_eq!(checked_sub(0u32, 1), Err(error(RegistryError::Overflow)));
+        assert_eq!(checked_sub(0i64, 5), Ok(-5));
+    }
+}
@@ -0,0 +1,274 @@
+mod common;
+
+use common::{member_state, member_state_account, registry_error, registry_mollusk, system_account};
+use mollusk_svm::result::Check;
+use registry_pinocchio::{MemberState, RegistryError};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+fn push_len(data: &mut Vec<u8>, len: usize) {
+    data.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn encode_claim_reward(reason: &[u8], proof: &[u8], rewards: &[u64], pick: u64) -> Vec<u8> {
+    let mut data = vec![5u8];
+    push_len(&mut data, reason.len());
+    data.extend_from_slice(reason);
+    push_len(&mut data, proof.len());
+    data.extend_from_slice(proof);
+    push_len(&mut data, rewards.len());
+    rewards.iter().for_each(|reward| data.extend_from_slice(&reward.to_le_bytes()));
+    data.extend_from_slice(&pick.to_le_bytes());
+    data
+}
+
+#[test]
+fn claim_reward_adds_the_picked_reward() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let member = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (member, member_state_account(program_id, &state)),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data: encode_claim_reward(b"daily", &[9u8; 32], &[10, 20, 30], 1),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+
+    assert_eq!(member_state(result.get_account(&member).unwrap()).points, 120);
+}
+
+#[test]
+fn claim_reward_rejects_pick_past_the_end() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let member = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (member, member_state_account(program_id, &state)),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data: encode_claim_reward(b"daily", &[9u8; 32], &[10, 20, 30], 3),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(registry_error(RegistryError::IndexOutOfBounds))],
+    );
+}
+
+#[test]
+fn claim_reward_rejects_empty_reason() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let member = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (member, member_state_account(program_id, &state)),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data: encode_claim_reward(b"", &[9u8; 32], &[10], 0),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(registry_error(RegistryError::EmptyReason))],
+    );
+}
+
+#[test]
+fn claim_reward_rejects_truncated_length_prefix() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let member = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (member, member_state_account(program_id, &state)),
+    ];
+
+    // Two of the four bytes of the `reason` length.
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data: vec![5u8, 5, 0],
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(registry_error(RegistryError::InvalidArgs))],
+    );
+}
+
+#[test]
+fn claim_reward_rejects_length_past_the_data() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let member = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (member, member_state_account(program_id, &state)),
+    ];
+
+    let mut data = vec![5u8];
+    push_len(&mut data, 16);
+    data.extend_from_slice(b"daily");
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data,
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(registry_error(RegistryError::InvalidArgs))],
+    );
+}
+
+#[test]
+fn claim_reward_rejects_length_over_max_len() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let member = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (member, member_state_account(program_id, &state)),
+    ];
+
+    let long_reason_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data: encode_claim_reward(&[b'a'; 33], &[9u8; 32], &[10], 0),
+    };
+
+    let many_rewards_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data: encode_claim_reward(b"daily", &[9u8; 32], &[1u64; 9], 0),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &long_reason_ix,
+        &accounts,
+        &[Check::err(registry_error(RegistryError::InvalidArgs))],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &many_rewards_ix,
+        &accounts,
+        &[Check::err(registry_error(RegistryError::InvalidArgs))],
+    );
+}
+
+#[test]
+fn claim_reward_rejects_invalid_utf8_reason() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let member = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (member, member_state_account(program_id, &state)),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data: encode_claim_reward(&[0xff, 0xfe], &[9u8; 32], &[10], 0),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(registry_error(RegistryError::InvalidArgs))],
+    );
+}
@@ -0,0 +1,48 @@
+// Account builders shared by the Mollusk tests; each test binary uses a subset.
+#![allow(dead_code)]
+
+use mollusk_svm::Mollusk;
+use registry_pinocchio::{MemberState, RegistryError};
+use solana_sdk::{account::Account, program_error::ProgramError, pubkey::Pubkey};
+use solana_sdk_ids::system_program;
+
+/// Mollusk with the registry program loaded under `program_id`.
+pub fn registry_mollusk(program_id: &Pubkey) -> Mollusk {
+    let mut mollusk = Mollusk::default();
+    let program_path = format!(
+        "{}/target/deploy/registry_pinocchio",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    mollusk.add_program(program_id, &program_path);
+    mollusk
+}
+
+pub fn registry_error(error: RegistryError) -> ProgramError {
+    ProgramError::Custom(error as u32)
+}
+
+pub fn system_account(lamports: u64) -> Account {
+    Account {
+        lamports,
+        data: vec![],
+        owner: system_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn member_state_account(program_id: Pubkey, state: &MemberState) -> Account {
+    let mut data = vec![0u8; MemberState::LEN];
+    state.pack(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner: program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn member_state(account: &Account) -> MemberState {
+    MemberState::unpack(&account.data).unwrap()
+}
@@ -0,0 +1,239 @@
+mod common;
+
+use common::{member_state, registry_error, registry_mollusk, system_account};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use registry_pinocchio::{
+    ClubState, ClubStateFeaturedTier, ClubStateSettings, ClubStateStatus, ClubStateTiers, RegistryError,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+// Opens the club to 250 members and sets the gold tier to 5_000 points and 300 bps.
+fn configure_club_data() -> Vec<u8> {
+    let mut data = vec![4u8, 1];
+    data.extend_from_slice(&250u32.to_le_bytes());
+    data.extend_from_slice(&5_000u64.to_le_bytes());
+    data.extend_from_slice(&300u16.to_le_bytes());
+    data
+}
+
+#[test]
+fn found_club_initializes_club_and_member() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (club, _) = Pubkey::find_program_address(&[b"club", admin.as_ref()], &program_id);
+    let (member, _) = Pubkey::find_program_address(&[b"member", admin.as_ref()], &program_id);
+    let (system_program_id, system_program_account) = keyed_account_for_system_program();
+    let mollusk = registry_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000_000)),
+        (club, Account::default()),
+        (member, Account::default()),
+        (system_program_id, system_program_account),
+    ];
+
+    let found_club_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(club, false),
+            AccountMeta::new(member, false),
+            AccountMeta::new_readonly(system_program_id, false),
+        ],
+        data: vec![2u8],
+    };
+
+    let result = mollusk.process_and_validate_instruction(&found_club_ix, &accounts, &[Check::success()]);
+
+    let club = ClubState::unpack(&result.get_account(&club).unwrap().data).unwrap();
+    assert_eq!(club.admin, admin.to_bytes());
+    assert_eq!(club.members, 1);
+    assert_eq!(club.settings, ClubStateSettings::default());
+    assert_eq!(club.tiers, [ClubStateTiers::default(); 3]);
+    assert_eq!(club.featured_tier, None);
+    assert_eq!(club.status, ClubStateStatus::Recruiting);
+    assert_eq!(club.successor, None);
+
+    let member = member_state(result.get_account(&member).unwrap());
+    assert_eq!(member.authority, admin.to_bytes());
+    assert_eq!(member.points, 100);
+}
+
+#[test]
+fn found_club_rejects_existing_member() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (club, _) = Pubkey::find_program_address(&[b"club", admin.as_ref()], &program_id);
+    let (member, _) = Pubkey::find_program_address(&[b"member", admin.as_ref()], &program_id);
+    let (system_program_id, system_program_account) = keyed_account_for_system_program();
+    let mollusk = registry_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000_000)),
+        (club, Account::default()),
+        (member, Account::default()),
+        (system_program_id, system_program_account),
+    ];
+
+    let join_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(member, false),
+            AccountMeta::new_readonly(system_program_id, false),
+        ],
+        data: vec![0u8],
+    };
+    let found_club_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(club, false),
+            AccountMeta::new(member, false),
+            AccountMeta::new_readonly(system_program_id, false),
+        ],
+        data: vec![2u8],
+    };
+
+    mollusk.process_and_validate_instruction_chain(
+        &[
+            (&join_ix, &[Check::success()]),
+            (
+                &found_club_ix,
+                &[Check::err(registry_error(RegistryError::AccountAlreadyInitialized))],
+            ),
+        ],
+        &accounts,
+    );
+}
+
+#[test]
+fn configure_club_sets_settings_and_gold_tier() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (club, _) = Pubkey::find_program_address(&[b"club", admin.as_ref()], &program_id);
+    let (member, _) = Pubkey::find_program_address(&[b"member", admin.as_ref()], &program_id);
+    let (system_program_id, system_program_account) = keyed_account_for_system_program();
+    let mollusk = registry_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000_000)),
+        (club, Account::default()),
+        (member, Account::default()),
+        (system_program_id, system_program_account),
+    ];
+
+    let found_club_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(club, false),
+            AccountMeta::new(member, false),
+            AccountMeta::new_readonly(system_program_id, false),
+        ],
+        data: vec![2u8],
+    };
+    let configure_club_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new(club, false),
+        ],
+        data: configure_club_data(),
+    };
+
+    let result = mollusk.process_and_validate_instruction_chain(
+        &[
+            (&found_club_ix, &[Check::success()]),
+            (&configure_club_ix, &[Check::success()]),
+        ],
+        &accounts,
+    );
+
+    let club = ClubState::unpack(&result.get_account(&club).unwrap().data).unwrap();
+    assert_eq!(club.settings, ClubStateSettings { open: true, max_members: 250 });
+    assert_eq!(club.tiers[..2], [ClubStateTiers::default(); 2]);
+    assert_eq!(club.tiers[2], ClubStateTiers { min_points: 5_000, bonus_bps: 300 });
+    assert_eq!(club.featured_tier, Some(ClubStateFeaturedTier::Gold));
+    assert_eq!(club.members, 1);
+}
+
+#[test]
+fn archive_club_clears_successor_and_featured_tier() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let successor = Pubkey::new_unique();
+    let (club, _) = Pubkey::find_program_address(&[b"club", admin.as_ref()], &program_id);
+    let (member, _) = Pubkey::find_program_address(&[b"member", admin.as_ref()], &program_id);
+    let (system_program_id, system_program_account) = keyed_account_for_system_program();
+    let mollusk = registry_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000_000)),
+        (club, Account::default()),
+        (member, Account::default()),
+        (system_program_id, system_program_account),
+    ];
+
+    let found_club_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(club, false),
+            AccountMeta::new(member, false),
+            AccountMeta::new_readonly(system_program_id, false),
+        ],
+        data: vec![2u8],
+    };
+    let admin_metas = vec![
+        AccountMeta::new_readonly(admin, true),
+        AccountMeta::new(club, false),
+    ];
+    let configure_club_ix = Instruction {
+        program_id,
+        accounts: admin_metas.clone(),
+        data: configure_club_data(),
+    };
+    let mut nominate_data = vec![6u8];
+    nominate_data.extend_from_slice(successor.as_ref());
+    let nominate_successor_ix = Instruction {
+        program_id,
+        accounts: admin_metas.clone(),
+        data: nominate_data,
+    };
+    let archive_club_ix = Instruction {
+        program_id,
+        accounts: admin_metas,
+        data: vec![7u8],
+    };
+
+    let result = mollusk.process_and_validate_instruction_chain(
+        &[
+            (&found_club_ix, &[Check::success()]),
+            (&configure_club_ix, &[Check::success()]),
+            (&nominate_successor_ix, &[Check::success()]),
+        ],
+        &accounts,
+    );
+    let state = ClubState::unpack(&result.get_account(&club).unwrap().data).unwrap();
+    assert_eq!(state.successor, Some(successor.to_bytes()));
+
+    let result = mollusk.process_and_validate_instruction_chain(
+        &[
+            (&archive_club_ix, &[Check::success()]),
+            (
+                &nominate_successor_ix,
+                &[Check::err(registry_error(RegistryError::ClubArchived))],
+            ),
+        ],
+        &result.resulting_accounts,
+    );
+    let state = ClubState::unpack(&result.get_account(&club).unwrap().data).unwrap();
+    assert_eq!(state.status, ClubStateStatus::Archived);
+    assert_eq!(state.successor, None);
+    assert_eq!(state.featured_tier, None);
+}
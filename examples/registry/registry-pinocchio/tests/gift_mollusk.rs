@@ -0,0 +1,93 @@
+mod common;
+
+use common::{member_state, member_state_account, registry_error, registry_mollusk, system_account};
+use mollusk_svm::result::Check;
+use registry_pinocchio::{MemberState, RegistryError};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+fn gift_data(points: u64) -> Vec<u8> {
+    let mut data = vec![3u8];
+    data.extend_from_slice(&points.to_le_bytes());
+    data
+}
+
+#[test]
+fn gift_moves_points() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let from_state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let to_state = MemberState {
+        authority: Pubkey::new_unique().to_bytes(),
+        points: 10,
+        bump: 0,
+    };
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (from, member_state_account(program_id, &from_state)),
+        (to, member_state_account(program_id, &to_state)),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(from, false),
+            AccountMeta::new(to, false),
+        ],
+        data: gift_data(40),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+
+    assert_eq!(member_state(result.get_account(&from).unwrap()).points, 60);
+    assert_eq!(member_state(result.get_account(&to).unwrap()).points, 50);
+}
+
+#[test]
+fn gift_to_self_rejects_duplicate_account() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let mollusk = registry_mollusk(&program_id);
+
+    let from_state = MemberState {
+        authority: authority.to_bytes(),
+        points: 100,
+        bump: 0,
+    };
+    let from_account = member_state_account(program_id, &from_state);
+    let accounts = vec![
+        (authority, system_account(1_000_000)),
+        (from, from_account.clone()),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(from, false),
+            AccountMeta::new(from, false),
+        ],
+        data: gift_data(40),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[
+            Check::err(registry_error(RegistryError::DuplicateMutableAccount)),
+            Check::account(&from).data(&from_account.data).build(),
+        ],
+    );
+}
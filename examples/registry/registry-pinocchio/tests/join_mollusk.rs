@@ -0,0 +1,140 @@
+mod common;
+
+use common::{member_state, registry_mollusk, system_account};
+use mollusk_svm::{program::keyed_account_for_system_program, result::Check};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+#[test]
+fn join_initializes_member() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (member, member_bump) =
+        Pubkey::find_program_address(&[b"member", authority.as_ref()], &program_id);
+    let (system_program_id, system_program_account) = keyed_account_for_system_program();
+    let mollusk = registry_mollusk(&program_id);
+
+    let accounts = vec![
+        (authority, system_account(1_000_000_000)),
+        (member, Account::default()),
+        (system_program_id, system_program_account),
+    ];
+
+    let join_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(member, false),
+            AccountMeta::new_readonly(system_program_id, false),
+        ],
+        data: vec![0u8],
+    };
+
+    let result = mollusk.process_and_validate_instruction(
+        &join_ix,
+        &accounts,
+        &[Check::success(), Check::account(&member).owner(&program_id).build()],
+    );
+
+    let state = member_state(result.get_account(&member).unwrap());
+    assert_eq!(state.authority, authority.to_bytes());
+    assert_eq!(state.points, 100);
+    assert_eq!(state.bump, member_bump);
+}
+
+#[test]
+fn second_join_leaves_member_unchanged() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (member, _member_bump) =
+        Pubkey::find_program_address(&[b"member", authority.as_ref()], &program_id);
+    let (system_program_id, system_program_account) = keyed_account_for_system_program();
+    let mollusk = registry_mollusk(&program_id);
+
+    let accounts = vec![
+        (authority, system_account(1_000_000_000)),
+        (member, Account::default()),
+        (system_program_id, system_program_account),
+    ];
+
+    let join_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(member, false),
+            AccountMeta::new_readonly(system_program_id, false),
+        ],
+        data: vec![0u8],
+    };
+
+    let first = mollusk.process_and_validate_instruction(&join_ix, &accounts, &[Check::success()]);
+    let joined = first.get_account(&member).unwrap().clone();
+
+    let second = mollusk.process_and_validate_instruction(
+        &join_ix,
+        &first.resulting_accounts,
+        &[
+            Check::success(),
+            Check::account(&member)
+                .data(&joined.data)
+                .lamports(joined.lamports)
+                .owner(&joined.owner)
+                .build(),
+        ],
+    );
+
+    let authority_before = first.get_account(&authority).unwrap();
+    let authority_after = second.get_account(&authority).unwrap();
+    assert_eq!(authority_after.lamports, authority_before.lamports);
+}
+
+#[test]
+fn join_after_earn_keeps_points() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (member, _member_bump) =
+        Pubkey::find_program_address(&[b"member", authority.as_ref()], &program_id);
+    let (system_program_id, system_program_account) = keyed_account_for_system_program();
+    let mollusk = registry_mollusk(&program_id);
+
+    let accounts = vec![
+        (authority, system_account(1_000_000_000)),
+        (member, Account::default()),
+        (system_program_id, system_program_account),
+    ];
+
+    let join_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(member, false),
+            AccountMeta::new_readonly(system_program_id, false),
+        ],
+        data: vec![0u8],
+    };
+
+    let mut earn_data = vec![1u8];
+    earn_data.extend_from_slice(&50u64.to_le_bytes());
+    let earn_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(member, false),
+        ],
+        data: earn_data,
+    };
+
+    let result = mollusk.process_and_validate_instruction_chain(
+        &[
+            (&join_ix, &[Check::success()]),
+            (&earn_ix, &[Check::success()]),
+            (&join_ix, &[Check::success()]),
+        ],
+        &accounts,
+    );
+
+    assert_eq!(member_state(result.get_account(&member).unwrap()).points, 150);
+}
@@ -0,0 +1,137 @@
+use pinocchio::program_error::ProgramError;
+use registry_pinocchio::{
+    ClubState, ClubStateFeaturedTier, ClubStateSettings, ClubStateStatus, ClubStateTiers, RegistryError,
+};
+
+fn sample_club() -> ClubState {
+    ClubState {
+        admin: [7u8; 32],
+        members: 42,
+        settings: ClubStateSettings {
+            open: true,
+            max_members: 500,
+        },
+        tiers: [
+            ClubStateTiers { min_points: 0, bonus_bps: 0 },
+            ClubStateTiers { min_points: 1_000, bonus_bps: 250 },
+            ClubStateTiers { min_points: u64::MAX, bonus_bps: u16::MAX },
+        ],
+        featured_tier: Some(ClubStateFeaturedTier::Silver),
+        status: ClubStateStatus::Full,
+        successor: Some([3u8; 32]),
+        bump: 254,
+    }
+}
+
+#[test]
+fn club_state_round_trips() {
+    let club = sample_club();
+    let mut data = vec![0u8; ClubState::LEN];
+    club.pack(&mut data).unwrap();
+    assert_eq!(ClubState::unpack(&data).unwrap(), club);
+}
+
+#[test]
+fn club_state_packs_nested_fields_inline() {
+    let mut data = vec![0u8; ClubState::LEN];
+    sample_club().pack(&mut data).unwrap();
+
+    assert_eq!(data[..1], ClubState::DISCRIMINATOR);
+    assert_eq!(data[1..33], [7u8; 32]);
+    assert_eq!(data[33..41], 42u64.to_le_bytes());
+    assert_eq!(data[41], 1);
+    assert_eq!(data[42..46], 500u32.to_le_bytes());
+    let tiers = &data[46..76];
+    assert_eq!(tiers[10..18], 1_000u64.to_le_bytes());
+    assert_eq!(tiers[18..20], 250u16.to_le_bytes());
+    assert_eq!(tiers[20..28], u64::MAX.to_le_bytes());
+    assert_eq!(tiers[28..30], u16::MAX.to_le_bytes());
+    assert_eq!(data[76..78], [1, 1]);
+    assert_eq!(data[78], 1);
+    assert_eq!(data[79], 1);
+    assert_eq!(data[80..112], [3u8; 32]);
+    assert_eq!(data[112], 254);
+}
+
+#[test]
+fn club_state_round_trips_every_option_and_variant() {
+    let statuses = [ClubStateStatus::Recruiting, ClubStateStatus::Full, ClubStateStatus::Archived];
+    let featured_tiers = [
+        None,
+        Some(ClubStateFeaturedTier::Bronze),
+        Some(ClubStateFeaturedTier::Silver),
+        Some(ClubStateFeaturedTier::Gold),
+    ];
+    let successors = [None, Some([3u8; 32])];
+
+    for status in statuses {
+        for featured_tier in featured_tiers {
+            for successor in successors {
+                let club = ClubState {
+                    featured_tier,
+                    status,
+                    successor,
+                    ..sample_club()
+                };
+                let mut data = vec![0u8; ClubState::LEN];
+                club.pack(&mut data).unwrap();
+                assert_eq!(data[78], status as u8);
+                assert_eq!(ClubState::unpack(&data).unwrap(), club);
+            }
+        }
+    }
+}
+
+// The client decodes options as Codama's `optionTypeNode(.., { fixed: true })`: a u8 tag,
+// then the value's bytes, zeroed for None.
+#[test]
+fn club_state_packs_none_as_a_zeroed_fixed_option() {
+    let mut data = vec![0u8; ClubState::LEN];
+    sample_club().pack(&mut data).unwrap();
+
+    let cleared = ClubState {
+        featured_tier: None,
+        successor: None,
+        ..sample_club()
+    };
+    cleared.pack(&mut data).unwrap();
+
+    assert_eq!(data[76..78], [0, 0]);
+    assert_eq!(data[79..112], [0u8; 33]);
+    assert_eq!(ClubState::unpack(&data).unwrap(), cleared);
+}
+
+
+#[test]
+fn club_state_unpack_rejects_bad_data() {
+    let mut data = vec![0u8; ClubState::LEN];
+    sample_club().pack(&mut data).unwrap();
+
+    assert_eq!(
+        ClubState::unpack(&data[..ClubState::LEN - 1]),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+
+    let mut bad_bool = data.clone();
+    bad_bool[41] = 2;
+    assert_eq!(
+        ClubState::unpack(&bad_bool),
+        Err(RegistryError::InvalidAccountLayout.into())
+    );
+
+    for (offset, tag) in [(76, 2), (77, 3), (78, 3), (79, 2)] {
+        let mut bad_tag = data.clone();
+        bad_tag[offset] = tag;
+        assert_eq!(
+            ClubState::unpack(&bad_tag),
+            Err(RegistryError::InvalidAccountLayout.into()),
+            "tag {tag} at offset {offset}"
+        );
+    }
+
+    data[0] = !ClubState::DISCRIMINATOR[0];
+    assert_eq!(
+        ClubState::unpack(&data),
+        Err(RegistryError::AccountDiscriminatorMismatch.into())
+    );
+}
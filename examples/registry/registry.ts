@@ -0,0 +1,227 @@
+import {
+  account,
+  accountMeta,
+  accountRef,
+  array,
+  bool,
+  bump,
+  bytes,
+  enumType,
+  errors,
+  expr,
+  field,
+  option,
+  pda,
+  program,
+  pubkey,
+  require,
+  state,
+  string,
+  struct,
+  tx,
+  u16,
+  u32,
+  u64,
+  u8,
+  vec,
+} from '@solana-ts-transpiler/sdk';
+
+export const Registry = program({
+  name: 'Registry',
+  programId: '7qaEj3zc3iq2DvzbyLUt5DZmQNdJDEt1Zq9QVkLP7moB',
+});
+
+Registry.errors = errors({
+  EmptyReason: 'Reward reason is empty',
+  InvalidProof: 'Reward proof must be 32 bytes',
+  ClubArchived: 'Club is archived',
+});
+
+Registry.accounts.member = account({
+  name: 'MemberState',
+  schema: {
+    authority: pubkey(),
+    points: u64(),
+    bump: u8(),
+  },
+  pda: pda(['member', accountRef('authority')]),
+});
+
+// Joining twice is harmless: the second call loads the existing member and keeps its points.
+Registry.join = tx({
+  name: 'join',
+  args: {},
+  accounts: [
+    accountMeta('authority', { signer: true, writable: true }),
+    accountMeta('member', {
+      writable: true,
+      pda: pda(['member', accountRef('authority')]),
+    }),
+  ],
+  ops: [
+    state.initIfNeeded('member', {
+      authority: accountRef('authority'),
+      points: expr.const(100),
+      bump: bump('member'),
+    }),
+  ],
+});
+
+Registry.earn = tx({
+  name: 'earn',
+  args: {
+    points: u64(),
+  },
+  accounts: [
+    accountMeta('authority', { signer: true }),
+    accountMeta('member', { writable: true, hasOne: ['authority'] }),
+  ],
+  ops: [
+    state.update('member', {
+      points: expr.add(field('member', 'points'), expr.arg('points')),
+    }),
+  ],
+});
+
+Registry.accounts.club = account({
+  name: 'ClubState',
+  schema: {
+    admin: pubkey(),
+    members: u64(),
+    settings: struct({ open: bool(), maxMembers: u32() }),
+    // Bronze, silver and gold: members at or above minPoints earn bonusBps more.
+    tiers: array(struct({ minPoints: u64(), bonusBps: u16() }), 3),
+    featuredTier: option(enumType(['Bronze', 'Silver', 'Gold'])),
+    status: enumType(['Recruiting', 'Full', 'Archived']),
+    successor: option(pubkey()),
+    bump: u8(),
+  },
+  pda: pda(['club', accountRef('admin')]),
+});
+
+// Founding a club also enrolls the founder, so one handler creates two account types.
+Registry.foundClub = tx({
+  name: 'foundClub',
+  args: {},
+  accounts: [
+    accountMeta('admin', { signer: true, writable: true }),
+    accountMeta('club', {
+      writable: true,
+      pda: pda(['club', accountRef('admin')]),
+    }),
+    accountMeta('member', {
+      writable: true,
+      pda: pda(['member', accountRef('admin')]),
+    }),
+  ],
+  ops: [
+    state.init('club', {
+      admin: accountRef('admin'),
+      members: expr.const(1),
+      bump: bump('club'),
+    }),
+    state.init('member', {
+      authority: accountRef('admin'),
+      points: expr.const(100),
+      bump: bump('member'),
+    }),
+  ],
+});
+
+Registry.gift = tx({
+  name: 'gift',
+  args: {
+    points: u64(),
+  },
+  accounts: [
+    accountMeta('authority', { signer: true }),
+    accountMeta('from', { writable: true, state: 'member', hasOne: ['authority'] }),
+    accountMeta('to', { writable: true, state: 'member' }),
+  ],
+  ops: [
+    state.update('from', {
+      points: expr.sub(field('from', 'points'), expr.arg('points')),
+    }),
+    state.update('to', {
+      points: expr.add(field('to', 'points'), expr.arg('points')),
+    }),
+  ],
+});
+
+Registry.configureClub = tx({
+  name: 'configureClub',
+  args: {
+    open: bool(),
+    maxMembers: u32(),
+    goldMinPoints: u64(),
+    goldBonusBps: u16(),
+  },
+  accounts: [
+    accountMeta('admin', { signer: true }),
+    accountMeta('club', { writable: true, hasOne: ['admin'] }),
+  ],
+  ops: [
+    state.update('club', {
+      'settings.open': expr.arg('open'),
+      'settings.maxMembers': expr.arg('maxMembers'),
+      'tiers.2.minPoints': expr.arg('goldMinPoints'),
+      'tiers.2.bonusBps': expr.arg('goldBonusBps'),
+      featuredTier: expr.some(expr.variant('Gold')),
+    }),
+  ],
+});
+
+// The client sends the whole reward menu and picks one entry; `pick` past the end fails
+// with IndexOutOfBounds.
+Registry.claimReward = tx({
+  name: 'claimReward',
+  args: {
+    reason: string(32),
+    proof: bytes(32),
+    rewards: vec(u64(), 8),
+    pick: u64(),
+  },
+  accounts: [
+    accountMeta('authority', { signer: true }),
+    accountMeta('member', { writable: true, hasOne: ['authority'] }),
+  ],
+  ops: [
+    require(expr.gt(expr.len(expr.arg('reason')), expr.const(0)), 'EmptyReason'),
+    require(expr.eq(expr.len(expr.arg('proof')), expr.const(32)), 'InvalidProof'),
+    state.update('member', {
+      points: expr.add(field('member', 'points'), expr.at(expr.arg('rewards'), expr.arg('pick'))),
+    }),
+  ],
+});
+
+Registry.nominateSuccessor = tx({
+  name: 'nominateSuccessor',
+  args: {
+    successor: pubkey(),
+  },
+  accounts: [
+    accountMeta('admin', { signer: true }),
+    accountMeta('club', { writable: true, hasOne: ['admin'] }),
+  ],
+  ops: [
+    require(expr.not(expr.eq(field('club', 'status'), expr.variant('Archived'))), 'ClubArchived'),
+    state.update('club', { successor: expr.some(expr.arg('successor')) }),
+  ],
+});
+
+// Archiving drops the nominated successor and the featured tier.
+Registry.archiveClub = tx({
+  name: 'archiveClub',
+  args: {},
+  accounts: [
+    accountMeta('admin', { signer: true }),
+    accountMeta('club', { writable: true, hasOne: ['admin'] }),
+  ],
+  ops: [
+    state.update('club', {
+      status: expr.variant('Archived'),
+      successor: expr.none(),
+      featuredTier: expr.none(),
+    }),
+  ],
+});
@@ -0,0 +1,745 @@
+// AUTO-GENERATED - DO NOT EDIT
+#![no_std]
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_entrypoint,
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+    seeds,
+    ProgramResult,
+};
+use pinocchio_tkn::common::{Approve, AuthorityType, Burn, CloseAccount, FreezeAccount, Revoke, SetAuthority, SyncNative, ThawAccount, TransferChecked};
+use pinocchio_tkn::{state::{Mint, AccountState, TokenAccount, MINT_SIZE, TOKEN_ACCOUNT_SIZE}, TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID};
+
+program_entrypoint!(process_instruction);
+
+#[cfg(target_arch = "bpf")]
+use pinocchio::default_allocator;
+
+#[cfg(target_arch = "bpf")]
+default_allocator!();
+
+#[cfg(target_arch = "bpf")]
+#[panic_handler]
+fn panic_handler(_info: &core::panic::PanicInfo<'_>) -> ! {
+    unsafe {
+        pinocchio::syscalls::abort();
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+extern crate std;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum TreasuryError {
+    /// Arithmetic overflow
+    Overflow = 1000,
+    /// Division by zero
+    DivideByZero = 1001,
+    /// Unknown instruction discriminator
+    UnknownInstruction = 1002,
+    /// Account must be writable
+    AccountNotWritable = 1003,
+    /// Account does not match its PDA seeds
+    ConstraintSeeds = 1004,
+    /// Account is not owned by the expected program
+    ConstraintOwner = 1005,
+    /// Token account owner does not match
+    ConstraintTokenOwner = 1006,
+    /// Token account mint does not match
+    ConstraintMint = 1007,
+    /// Account address does not match
+    ConstraintAddress = 1008,
+    /// Program account does not match the expected program
+    ConstraintProgram = 1009,
+    /// Program account is not executable
+    ProgramNotExecutable = 1010,
+    /// Account discriminator does not match
+    AccountDiscriminatorMismatch = 1011,
+    /// Account is already initialized
+    AccountAlreadyInitialized = 1012,
+    /// The same account was passed twice as mutable state
+    DuplicateMutableAccount = 1013,
+    /// An account does not match the key it must equal
+    ConstraintHasOne = 1014,
+    /// A vec argument index is out of bounds
+    IndexOutOfBounds = 1015,
+    /// Token account is frozen, or not frozen for a thaw
+    ConstraintTokenState = 1016,
+    /// Instruction data does not decode as the instruction args
+    InvalidArgs = 1017,
+    /// Account data does not decode as its layout
+    InvalidAccountLayout = 1018,
+    /// Account does not match its equals constraint
+    ConstraintEquals = 1019,
+}
+
+impl From<TreasuryError> for ProgramError {
+    fn from(error: TreasuryError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
+
+
+
+
+fn check_token_layout(data: &[u8], base_len: usize, account_type: u8) -> ProgramResult {
+    if data.len() == base_len || (data.len() > TOKEN_ACCOUNT_SIZE && data[TOKEN_ACCOUNT_SIZE] == account_type) {
+        return Ok(());
+    }
+    Err(TreasuryError::InvalidAccountLayout.into())
+}
+
+fn check_token_account(account: &AccountInfo, owner: &Pubkey, mint: &Pubkey, token_programs: &[Pubkey], state: AccountState) -> ProgramResult {
+    if !token_programs.iter().any(|token_program| account.is_owned_by(token_program)) {
+        return Err(TreasuryError::ConstraintOwner.into());
+    }
+    let data = account.try_borrow_data()?;
+    check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2)?;
+    let token_account = TokenAccount::from_bytes(&data)?;
+    if token_account.mint() != mint {
+        return Err(TreasuryError::ConstraintMint.into());
+    }
+    if token_account.owner() != owner {
+        return Err(TreasuryError::ConstraintTokenOwner.into());
+    }
+    // `from_bytes` already rejects uninitialized accounts.
+    if token_account.state() != state {
+        return Err(TreasuryError::ConstraintTokenState.into());
+    }
+    Ok(())
+}
+
+fn check_mint_account(account: &AccountInfo, address: &Pubkey, token_programs: &[Pubkey]) -> ProgramResult {
+    if account.key() != address {
+        return Err(TreasuryError::ConstraintAddress.into());
+    }
+    if !token_programs.iter().any(|token_program| account.is_owned_by(token_program)) {
+        return Err(TreasuryError::ConstraintOwner.into());
+    }
+    let data = account.try_borrow_data()?;
+    check_token_layout(&data, MINT_SIZE, 1)?;
+    Mint::from_bytes(&data)?;
+    Ok(())
+}
+
+fn check_program_account(account: &AccountInfo, program_ids: &[Pubkey]) -> ProgramResult {
+    if !program_ids.contains(account.key()) {
+        return Err(TreasuryError::ConstraintProgram.into());
+    }
+    if !account.executable() {
+        return Err(TreasuryError::ProgramNotExecutable.into());
+    }
+    Ok(())
+}
+
+fn mint_decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    let data = mint.try_borrow_data()?;
+    Ok(Mint::from_bytes(&data)?.decimals())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    if data.len() < offset + 32 {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&data[offset..offset + 32]);
+    Some(out)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    if data.len() < offset + 8 {
+        return None;
+    }
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data[offset..offset + 8]);
+    Some(u64::from_le_bytes(buf))
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data.is_empty() {
+        return Err(TreasuryError::UnknownInstruction.into());
+    }
+
+    match data[0] {
+        0 => handle_approve_spender(program_id, accounts, &data[1..]),
+        1 => handle_revoke_spender(program_id, accounts, &data[1..]),
+        2 => handle_hand_over_mint(program_id, accounts, &data[1..]),
+        3 => handle_freeze_holder(program_id, accounts, &data[1..]),
+        4 => handle_thaw_holder(program_id, accounts, &data[1..]),
+        5 => handle_close_vault(program_id, accounts, &data[1..]),
+        6 => handle_sync_wrapped_sol(program_id, accounts, &data[1..]),
+        7 => handle_pay_out(program_id, accounts, &data[1..]),
+        8 => handle_burn_from_vault(program_id, accounts, &data[1..]),
+        9 => handle_pay_out_token2022(program_id, accounts, &data[1..]),
+        10 => handle_pay_out_either(program_id, accounts, &data[1..]),
+        _ => Err(TreasuryError::UnknownInstruction.into()),
+    }
+}
+
+
+struct ApproveSpenderArgs {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+fn decode_approve_spender_args(data: &[u8]) -> Result<ApproveSpenderArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    let amount = read_u64(data, 32).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(ApproveSpenderArgs {
+        mint,
+        amount,
+    })
+}
+
+fn handle_approve_spender(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 5 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_approve_spender_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let vault = &accounts[2];
+    if !vault.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let spender = &accounts[3];
+    let token_program = &accounts[4];
+    check_token_account(vault, treasury.key(), &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    Approve {
+        source: vault,
+        delegate: spender,
+        owner: treasury,
+        amount: args.amount,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct RevokeSpenderArgs {
+    pub mint: Pubkey,
+}
+
+fn decode_revoke_spender_args(data: &[u8]) -> Result<RevokeSpenderArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(RevokeSpenderArgs {
+        mint,
+    })
+}
+
+fn handle_revoke_spender(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_revoke_spender_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let vault = &accounts[2];
+    if !vault.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[3];
+    check_token_account(vault, treasury.key(), &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    Revoke {
+        source: vault,
+        owner: treasury,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct HandOverMintArgs {
+    pub mint: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+fn decode_hand_over_mint_args(data: &[u8]) -> Result<HandOverMintArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    let new_authority = read_pubkey(data, 32).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(HandOverMintArgs {
+        mint,
+        new_authority,
+    })
+}
+
+fn handle_hand_over_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_hand_over_mint_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let mint = &accounts[2];
+    if !mint.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[3];
+    check_mint_account(mint, &args.mint, &[TOKEN_PROGRAM_ID])?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    SetAuthority {
+        account: mint,
+        current_authority: treasury,
+        authority_type: AuthorityType::MintTokens,
+        new_authority: Some(&args.new_authority),
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct FreezeHolderArgs {
+    pub mint: Pubkey,
+    pub holder: Pubkey,
+}
+
+fn decode_freeze_holder_args(data: &[u8]) -> Result<FreezeHolderArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    let holder = read_pubkey(data, 32).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(FreezeHolderArgs {
+        mint,
+        holder,
+    })
+}
+
+fn handle_freeze_holder(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 5 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_freeze_holder_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let mint = &accounts[2];
+    let holder_tokens = &accounts[3];
+    if !holder_tokens.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[4];
+    check_mint_account(mint, &args.mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(holder_tokens, &args.holder, &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    FreezeAccount {
+        account: holder_tokens,
+        mint,
+        freeze_authority: treasury,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct ThawHolderArgs {
+    pub mint: Pubkey,
+    pub holder: Pubkey,
+}
+
+fn decode_thaw_holder_args(data: &[u8]) -> Result<ThawHolderArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    let holder = read_pubkey(data, 32).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(ThawHolderArgs {
+        mint,
+        holder,
+    })
+}
+
+fn handle_thaw_holder(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 5 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_thaw_holder_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let mint = &accounts[2];
+    let holder_tokens = &accounts[3];
+    if !holder_tokens.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[4];
+    check_mint_account(mint, &args.mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(holder_tokens, &args.holder, &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Frozen)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    ThawAccount {
+        account: holder_tokens,
+        mint,
+        freeze_authority: treasury,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct CloseVaultArgs {
+    pub mint: Pubkey,
+}
+
+fn decode_close_vault_args(data: &[u8]) -> Result<CloseVaultArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(CloseVaultArgs {
+        mint,
+    })
+}
+
+fn handle_close_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_close_vault_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    if !admin.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let vault = &accounts[2];
+    if !vault.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[3];
+    check_token_account(vault, treasury.key(), &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    CloseAccount {
+        account: vault,
+        destination: admin,
+        owner: treasury,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct SyncWrappedSolArgs {
+    pub mint: Pubkey,
+}
+
+fn decode_sync_wrapped_sol_args(data: &[u8]) -> Result<SyncWrappedSolArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(SyncWrappedSolArgs {
+        mint,
+    })
+}
+
+fn handle_sync_wrapped_sol(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_sync_wrapped_sol_args(data)?;
+    let admin = &accounts[0];
+    let _ = admin;
+    let treasury = &accounts[1];
+    let (treasury_key, _treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let vault = &accounts[2];
+    if !vault.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[3];
+    check_token_account(vault, treasury.key(), &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    
+    SyncNative {
+        account: vault,
+        program_id: Some(token_program.key()),
+    }.invoke()?;
+    Ok(())
+}
+
+
+struct PayOutArgs {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+fn decode_pay_out_args(data: &[u8]) -> Result<PayOutArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    let recipient = read_pubkey(data, 32).ok_or(TreasuryError::InvalidArgs)?;
+    let amount = read_u64(data, 64).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(PayOutArgs {
+        mint,
+        recipient,
+        amount,
+    })
+}
+
+fn handle_pay_out(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 6 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_pay_out_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let mint = &accounts[2];
+    let vault = &accounts[3];
+    if !vault.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let recipient_tokens = &accounts[4];
+    if !recipient_tokens.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[5];
+    check_mint_account(mint, &args.mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(vault, treasury.key(), &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(recipient_tokens, &args.recipient, &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    TransferChecked {
+        source: vault,
+        mint,
+        destination: recipient_tokens,
+        authority: treasury,
+        amount: args.amount,
+        decimals: mint_decimals(mint)?,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct BurnFromVaultArgs {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+fn decode_burn_from_vault_args(data: &[u8]) -> Result<BurnFromVaultArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    let amount = read_u64(data, 32).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(BurnFromVaultArgs {
+        mint,
+        amount,
+    })
+}
+
+fn handle_burn_from_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 5 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_burn_from_vault_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let mint = &accounts[2];
+    if !mint.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let vault = &accounts[3];
+    if !vault.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[4];
+    check_mint_account(mint, &args.mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(vault, treasury.key(), &args.mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    Burn {
+        source: vault,
+        mint,
+        authority: treasury,
+        amount: args.amount,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct PayOutToken2022Args {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+fn decode_pay_out_token2022_args(data: &[u8]) -> Result<PayOutToken2022Args, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    let recipient = read_pubkey(data, 32).ok_or(TreasuryError::InvalidArgs)?;
+    let amount = read_u64(data, 64).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(PayOutToken2022Args {
+        mint,
+        recipient,
+        amount,
+    })
+}
+
+fn handle_pay_out_token2022(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 6 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_pay_out_token2022_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let mint = &accounts[2];
+    let vault = &accounts[3];
+    if !vault.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let recipient_tokens = &accounts[4];
+    if !recipient_tokens.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[5];
+    check_mint_account(mint, &args.mint, &[TOKEN_2022_PROGRAM_ID])?;
+    check_token_account(vault, treasury.key(), &args.mint, &[TOKEN_2022_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(recipient_tokens, &args.recipient, &args.mint, &[TOKEN_2022_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_2022_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    TransferChecked {
+        source: vault,
+        mint,
+        destination: recipient_tokens,
+        authority: treasury,
+        amount: args.amount,
+        decimals: mint_decimals(mint)?,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+struct PayOutEitherArgs {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+fn decode_pay_out_either_args(data: &[u8]) -> Result<PayOutEitherArgs, ProgramError> {
+    let mint = read_pubkey(data, 0).ok_or(TreasuryError::InvalidArgs)?;
+    let recipient = read_pubkey(data, 32).ok_or(TreasuryError::InvalidArgs)?;
+    let amount = read_u64(data, 64).ok_or(TreasuryError::InvalidArgs)?;
+    Ok(PayOutEitherArgs {
+        mint,
+        recipient,
+        amount,
+    })
+}
+
+fn handle_pay_out_either(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 6 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_pay_out_either_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let treasury = &accounts[1];
+    let (treasury_key, treasury_bump) = pubkey::find_program_address(&[b"treasury", admin.key().as_ref()], program_id);
+    if treasury.key() != &treasury_key { return Err(TreasuryError::ConstraintSeeds.into()); }
+    let mint = &accounts[2];
+    let vault = &accounts[3];
+    if !vault.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let recipient_tokens = &accounts[4];
+    if !recipient_tokens.is_writable() { return Err(TreasuryError::AccountNotWritable.into()); }
+    let token_program = &accounts[5];
+    check_mint_account(mint, &args.mint, &[TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID])?;
+    check_token_account(vault, treasury.key(), &args.mint, &[TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(recipient_tokens, &args.recipient, &args.mint, &[TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID])?;
+    let treasury_bump_ref = [treasury_bump];
+    let treasury_seeds = seeds!(b"treasury", admin.key().as_ref(), &treasury_bump_ref);
+    let treasury_signer = Signer::from(&treasury_seeds);
+    TransferChecked {
+        source: vault,
+        mint,
+        destination: recipient_tokens,
+        authority: treasury,
+        amount: args.amount,
+        decimals: mint_decimals(mint)?,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&treasury_signer))?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod token_layout_tests {
+    use super::*;
+    use pinocchio_tkn::state::MINT_SIZE;
+
+    #[test]
+    fn token_layout_reads_the_token_2022_account_type() {
+        let mut data = [0u8; TOKEN_ACCOUNT_SIZE + 5];
+        assert_eq!(check_token_layout(&data[..TOKEN_ACCOUNT_SIZE], TOKEN_ACCOUNT_SIZE, 2), Ok(()));
+        assert_eq!(check_token_layout(&data[..MINT_SIZE], MINT_SIZE, 1), Ok(()));
+        assert_eq!(check_token_layout(&data[..MINT_SIZE + 1], MINT_SIZE, 1), Err(TreasuryError::InvalidAccountLayout.into()));
+        data[TOKEN_ACCOUNT_SIZE] = 2;
+        assert_eq!(check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2), Ok(()));
+        assert_eq!(check_token_layout(&data, MINT_SIZE, 1), Err(TreasuryError::InvalidAccountLayout.into()));
+        data[TOKEN_ACCOUNT_SIZE] = 1;
+        assert_eq!(check_token_layout(&data, MINT_SIZE, 1), Ok(()));
+        assert_eq!(check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2), Err(TreasuryError::InvalidAccountLayout.into()));
+    }
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::{
+    decode_mint, decode_token_account, mint_account, system_account, token_account, treasury_error, treasury_mollusk,
+};
+use mollusk_svm::result::Check;
+use mollusk_svm_programs_token::token;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use treasury_pinocchio::TreasuryError;
+
+fn burn_data(mint: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![8u8];
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[test]
+fn burn_from_vault_signs_with_the_treasury_seeds() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    // The vault belongs to the treasury, which the handler must sign for through `invoke_signed`.
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 1_000)),
+        (vault, token_account(treasury, mint, 1_000)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: burn_data(mint, 300),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    assert_eq!(decode_token_account(result.get_account(&vault).unwrap()).amount, 700);
+    assert_eq!(decode_mint(result.get_account(&mint).unwrap()).supply, 700);
+}
+
+#[test]
+fn burn_from_vault_rejects_a_treasury_missing_the_admin_seed() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let (unseeded, _bump) = Pubkey::find_program_address(&[b"treasury"], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (unseeded, Account::default()),
+        (mint, mint_account(treasury, 1_000)),
+        (vault, token_account(unseeded, mint, 1_000)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(unseeded, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: burn_data(mint, 300),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintSeeds))],
+    );
+}
+
+#[test]
+fn burn_from_vault_rejects_the_treasury_of_another_admin() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let (other_treasury, _bump) =
+        Pubkey::find_program_address(&[b"treasury", Pubkey::new_unique().as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (other_treasury, Account::default()),
+        (mint, mint_account(treasury, 1_000)),
+        (vault, token_account(other_treasury, mint, 1_000)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(other_treasury, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: burn_data(mint, 300),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintSeeds))],
+    );
+}
+
+#[test]
+fn burn_from_vault_rejects_a_vault_the_treasury_cannot_sign_for() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let (other_pda, _bump) = Pubkey::find_program_address(&[b"other", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 1_000)),
+        (vault, token_account(other_pda, mint, 1_000)),
+        (other_pda, Account::default()),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: burn_data(mint, 300),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintTokenOwner))],
+    );
+}
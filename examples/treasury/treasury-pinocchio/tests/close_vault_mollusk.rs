@@ -0,0 +1,94 @@
+mod common;
+
+use common::{mint_account, system_account, token_account, treasury_mollusk};
+use mollusk_svm::result::Check;
+use mollusk_svm_programs_token::token;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token_interface::error::TokenError;
+
+fn close_data(mint: Pubkey) -> Vec<u8> {
+    let mut data = vec![5u8];
+    data.extend_from_slice(mint.as_ref());
+    data
+}
+
+#[test]
+fn close_vault_returns_its_rent_to_the_admin() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let vault_account = token_account(treasury, mint, 0);
+    let admin_account = system_account(1_000_000);
+    let expected_admin_lamports = admin_account.lamports + vault_account.lamports;
+    let accounts = vec![
+        (admin, admin_account),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 0)),
+        (vault, vault_account),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: close_data(mint),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[
+            Check::success(),
+            Check::account(&vault).lamports(0).build(),
+            Check::account(&admin).lamports(expected_admin_lamports).build(),
+        ],
+    );
+}
+
+#[test]
+fn close_vault_fails_while_it_holds_tokens() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 10)),
+        (vault, token_account(treasury, mint, 10)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: close_data(mint),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(ProgramError::Custom(TokenError::NonNativeHasBalance as u32))],
+    );
+}
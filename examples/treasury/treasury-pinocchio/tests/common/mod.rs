@@ -0,0 +1,158 @@
+// Account builders shared by the Mollusk tests; each test binary uses a subset.
+#![allow(dead_code)]
+
+use mollusk_svm::Mollusk;
+use mollusk_svm_programs_token::{token, token2022};
+use solana_program_option::COption;
+use solana_program_pack::Pack;
+use solana_sdk::{account::Account, program_error::ProgramError, pubkey::Pubkey};
+use solana_rent::Rent;
+use solana_sdk_ids::system_program;
+use spl_token_interface::state::{Account as TokenAccount, AccountState, Mint};
+use treasury_pinocchio::TreasuryError;
+
+pub const DECIMALS: u8 = 6;
+
+/// Mollusk with the SPL Token program and the treasury program loaded under `program_id`.
+pub fn treasury_mollusk(program_id: &Pubkey) -> Mollusk {
+    let mut mollusk = Mollusk::default();
+    token::add_program(&mut mollusk);
+    let program_path = format!(
+        "{}/target/deploy/treasury_pinocchio",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    mollusk.add_program(program_id, &program_path);
+    mollusk
+}
+
+pub fn system_account(lamports: u64) -> Account {
+    Account {
+        lamports,
+        data: vec![],
+        owner: system_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn mint_account(authority: Pubkey, supply: u64) -> Account {
+    token::create_account_for_mint(Mint {
+        mint_authority: COption::Some(authority),
+        supply,
+        decimals: DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::Some(authority),
+    })
+}
+
+pub fn token_account(owner: Pubkey, mint: Pubkey, amount: u64) -> Account {
+    token_account_in_state(owner, mint, amount, AccountState::Initialized)
+}
+
+pub fn token_account_in_state(owner: Pubkey, mint: Pubkey, amount: u64, state: AccountState) -> Account {
+    token::create_account_for_token_account(TokenAccount {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+// Token-2022 extension types and TLV layout, from the token-2022 program's `extension` module.
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+const TRANSFER_FEE_CONFIG: u16 = 1;
+const TRANSFER_FEE_AMOUNT: u16 = 2;
+const IMMUTABLE_OWNER: u16 = 7;
+
+// Extensions follow the 165 byte token account base, which mints are zero padded to: an
+// account type byte, then one type/length/value entry per extension.
+fn with_extensions(mut base: Vec<u8>, account_type: u8, extensions: &[(u16, Vec<u8>)]) -> Account {
+    base.resize(TokenAccount::LEN, 0);
+    base.push(account_type);
+    extensions.iter().for_each(|(extension_type, value)| {
+        base.extend_from_slice(&extension_type.to_le_bytes());
+        base.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        base.extend_from_slice(value);
+    });
+    Account {
+        lamports: Rent::default().minimum_balance(base.len()),
+        data: base,
+        owner: token2022::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// A Token-2022 mint charging `fee_bps` on every transfer, with no fee authorities.
+pub fn mint_2022_with_transfer_fee(authority: Pubkey, supply: u64, fee_bps: u16) -> Account {
+    let mut base = vec![0u8; Mint::LEN];
+    Mint::pack(
+        Mint {
+            mint_authority: COption::Some(authority),
+            supply,
+            decimals: DECIMALS,
+            is_initialized: true,
+            freeze_authority: COption::Some(authority),
+        },
+        &mut base,
+    )
+    .unwrap();
+    // Older and newer fees alike, from epoch 0 on.
+    let mut fee = Vec::new();
+    fee.extend_from_slice(&0u64.to_le_bytes());
+    fee.extend_from_slice(&u64::MAX.to_le_bytes());
+    fee.extend_from_slice(&fee_bps.to_le_bytes());
+    let mut config = vec![0u8; 64];
+    config.extend_from_slice(&0u64.to_le_bytes());
+    config.extend_from_slice(&fee);
+    config.extend_from_slice(&fee);
+    with_extensions(base, ACCOUNT_TYPE_MINT, &[(TRANSFER_FEE_CONFIG, config)])
+}
+
+// A Token-2022 token account with the immutable owner and withheld fee extensions.
+pub fn token_account_2022(owner: Pubkey, mint: Pubkey, amount: u64) -> Account {
+    let mut base = vec![0u8; TokenAccount::LEN];
+    TokenAccount::pack(
+        TokenAccount {
+            mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        &mut base,
+    )
+    .unwrap();
+    with_extensions(
+        base,
+        ACCOUNT_TYPE_ACCOUNT,
+        &[(IMMUTABLE_OWNER, vec![]), (TRANSFER_FEE_AMOUNT, 0u64.to_le_bytes().to_vec())],
+    )
+}
+
+// The fees withheld on a `token_account_2022`, whose TransferFeeAmount entry comes last.
+pub fn withheld_fees(account: &Account) -> u64 {
+    let data = &account.data;
+    u64::from_le_bytes(data[data.len() - 8..].try_into().unwrap())
+}
+
+// Reads the base state of legacy and Token-2022 token accounts alike.
+pub fn decode_token_account(account: &Account) -> TokenAccount {
+    TokenAccount::unpack_from_slice(&account.data[..TokenAccount::LEN]).unwrap()
+}
+
+pub fn decode_mint(account: &Account) -> Mint {
+    Mint::unpack(&account.data).unwrap()
+}
+
+pub fn treasury_error(error: TreasuryError) -> ProgramError {
+    ProgramError::Custom(error as u32)
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use common::{
+    decode_token_account, mint_account, system_account, token_account_in_state, treasury_error, treasury_mollusk,
+};
+use mollusk_svm::result::Check;
+use mollusk_svm_programs_token::token;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_token_interface::state::AccountState;
+use treasury_pinocchio::TreasuryError;
+
+const FREEZE: u8 = 3;
+const THAW: u8 = 4;
+
+fn holder_data(discriminator: u8, mint: Pubkey, holder: Pubkey) -> Vec<u8> {
+    let mut data = vec![discriminator];
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(holder.as_ref());
+    data
+}
+
+#[test]
+fn freeze_then_thaw_toggles_the_holder_account() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let holder = Pubkey::new_unique();
+    let holder_tokens = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 500)),
+        (holder_tokens, token_account_in_state(holder, mint, 500, AccountState::Initialized)),
+        token::keyed_account(),
+    ];
+
+    let metas = vec![
+        AccountMeta::new_readonly(admin, true),
+        AccountMeta::new_readonly(treasury, false),
+        AccountMeta::new_readonly(mint, false),
+        AccountMeta::new(holder_tokens, false),
+        AccountMeta::new_readonly(token::ID, false),
+    ];
+    let freeze_ix = Instruction {
+        program_id,
+        accounts: metas.clone(),
+        data: holder_data(FREEZE, mint, holder),
+    };
+    let thaw_ix = Instruction {
+        program_id,
+        accounts: metas,
+        data: holder_data(THAW, mint, holder),
+    };
+
+    let frozen = mollusk.process_and_validate_instruction(&freeze_ix, &accounts, &[Check::success()]);
+    let state = decode_token_account(frozen.get_account(&holder_tokens).unwrap());
+    assert_eq!(state.state, AccountState::Frozen);
+    assert_eq!(state.amount, 500);
+
+    let thawed = mollusk.process_and_validate_instruction(&thaw_ix, &frozen.resulting_accounts, &[Check::success()]);
+    let state = decode_token_account(thawed.get_account(&holder_tokens).unwrap());
+    assert_eq!(state.state, AccountState::Initialized);
+}
+
+#[test]
+fn freeze_rejects_an_already_frozen_account() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let holder = Pubkey::new_unique();
+    let holder_tokens = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 500)),
+        (holder_tokens, token_account_in_state(holder, mint, 500, AccountState::Frozen)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(holder_tokens, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: holder_data(FREEZE, mint, holder),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintTokenState))],
+    );
+}
+
+#[test]
+fn thaw_rejects_an_account_that_is_not_frozen() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let holder = Pubkey::new_unique();
+    let holder_tokens = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 500)),
+        (holder_tokens, token_account_in_state(holder, mint, 500, AccountState::Initialized)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(holder_tokens, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: holder_data(THAW, mint, holder),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintTokenState))],
+    );
+}
+
+#[test]
+fn thaw_rejects_a_holder_account_of_another_owner() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let holder = Pubkey::new_unique();
+    let holder_tokens = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 500)),
+        (
+            holder_tokens,
+            token_account_in_state(Pubkey::new_unique(), mint, 500, AccountState::Frozen),
+        ),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(holder_tokens, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: holder_data(THAW, mint, holder),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintTokenOwner))],
+    );
+}
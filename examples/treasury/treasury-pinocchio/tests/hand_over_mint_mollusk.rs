@@ -0,0 +1,85 @@
+mod common;
+
+use common::{decode_mint, mint_account, system_account, treasury_error, treasury_mollusk};
+use mollusk_svm::result::Check;
+use mollusk_svm_programs_token::token;
+use solana_program_option::COption;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use treasury_pinocchio::TreasuryError;
+
+fn hand_over_data(mint: Pubkey, new_authority: Pubkey) -> Vec<u8> {
+    let mut data = vec![2u8];
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(new_authority.as_ref());
+    data
+}
+
+#[test]
+fn hand_over_mint_moves_only_the_mint_authority() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let new_authority = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 1_000)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: hand_over_data(mint, new_authority),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    let state = decode_mint(result.get_account(&mint).unwrap());
+    assert_eq!(state.mint_authority, COption::Some(new_authority));
+    assert_eq!(state.freeze_authority, COption::Some(treasury));
+    assert_eq!(state.supply, 1_000);
+}
+
+#[test]
+fn hand_over_mint_requires_a_writable_mint() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 1_000)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: hand_over_data(mint, Pubkey::new_unique()),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::AccountNotWritable))],
+    );
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{decode_token_account, mint_account, system_account, token_account, treasury_error, treasury_mollusk};
+use mollusk_svm::result::Check;
+use mollusk_svm_programs_token::token;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use treasury_pinocchio::TreasuryError;
+
+fn pay_out_data(mint: Pubkey, recipient: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![7u8];
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(recipient.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[test]
+fn pay_out_moves_tokens_with_the_mint_decimals() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_tokens = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 1_000)),
+        (vault, token_account(treasury, mint, 1_000)),
+        (recipient_tokens, token_account(recipient, mint, 0)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient_tokens, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: pay_out_data(mint, recipient, 400),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    assert_eq!(decode_token_account(result.get_account(&vault).unwrap()).amount, 600);
+    assert_eq!(decode_token_account(result.get_account(&recipient_tokens).unwrap()).amount, 400);
+}
+
+#[test]
+fn pay_out_rejects_a_recipient_account_of_another_mint() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_tokens = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 1_000)),
+        (vault, token_account(treasury, mint, 1_000)),
+        (recipient_tokens, token_account(recipient, Pubkey::new_unique(), 0)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient_tokens, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: pay_out_data(mint, recipient, 400),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintMint))],
+    );
+}
@@ -0,0 +1,229 @@
+mod common;
+
+use common::{
+    decode_token_account, mint_2022_with_transfer_fee, mint_account, system_account, token_account,
+    token_account_2022, treasury_error, treasury_mollusk, withheld_fees,
+};
+use mollusk_svm::result::Check;
+use mollusk_svm_programs_token::{token, token2022};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use treasury_pinocchio::TreasuryError;
+
+const PAY_OUT: u8 = 7;
+const PAY_OUT_TOKEN_2022: u8 = 9;
+const PAY_OUT_EITHER: u8 = 10;
+
+fn pay_out_data(discriminator: u8, mint: Pubkey, recipient: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![discriminator];
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(recipient.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[test]
+fn pay_out_token_2022_withholds_the_transfer_fee() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_tokens = Pubkey::new_unique();
+    let mut mollusk = treasury_mollusk(&program_id);
+    token2022::add_program(&mut mollusk);
+
+    // A 1% transfer fee mint; the vault holds 10_000 tokens.
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_2022_with_transfer_fee(treasury, 10_000, 100)),
+        (vault, token_account_2022(treasury, mint, 10_000)),
+        (recipient_tokens, token_account_2022(recipient, mint, 0)),
+        token2022::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient_tokens, false),
+            AccountMeta::new_readonly(token2022::ID, false),
+        ],
+        data: pay_out_data(PAY_OUT_TOKEN_2022, mint, recipient, 10_000),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    let recipient_account = result.get_account(&recipient_tokens).unwrap();
+    assert_eq!(decode_token_account(result.get_account(&vault).unwrap()).amount, 0);
+    assert_eq!(decode_token_account(recipient_account).amount, 9_900);
+    assert_eq!(withheld_fees(recipient_account), 100);
+}
+
+#[test]
+fn pay_out_either_withholds_the_transfer_fee_on_token_2022() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_tokens = Pubkey::new_unique();
+    let mut mollusk = treasury_mollusk(&program_id);
+    token2022::add_program(&mut mollusk);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_2022_with_transfer_fee(treasury, 10_000, 100)),
+        (vault, token_account_2022(treasury, mint, 10_000)),
+        (recipient_tokens, token_account_2022(recipient, mint, 0)),
+        token2022::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient_tokens, false),
+            AccountMeta::new_readonly(token2022::ID, false),
+        ],
+        data: pay_out_data(PAY_OUT_EITHER, mint, recipient, 10_000),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    let recipient_account = result.get_account(&recipient_tokens).unwrap();
+    assert_eq!(decode_token_account(result.get_account(&vault).unwrap()).amount, 0);
+    assert_eq!(decode_token_account(recipient_account).amount, 9_900);
+    assert_eq!(withheld_fees(recipient_account), 100);
+}
+
+#[test]
+fn pay_out_either_also_runs_on_spl_token() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_tokens = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 10_000)),
+        (vault, token_account(treasury, mint, 10_000)),
+        (recipient_tokens, token_account(recipient, mint, 0)),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient_tokens, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: pay_out_data(PAY_OUT_EITHER, mint, recipient, 4_000),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    assert_eq!(decode_token_account(result.get_account(&recipient_tokens).unwrap()).amount, 4_000);
+}
+
+#[test]
+fn pay_out_token_2022_rejects_the_spl_token_program() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_tokens = Pubkey::new_unique();
+    let mut mollusk = treasury_mollusk(&program_id);
+    token2022::add_program(&mut mollusk);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_2022_with_transfer_fee(treasury, 10_000, 100)),
+        (vault, token_account_2022(treasury, mint, 10_000)),
+        (recipient_tokens, token_account_2022(recipient, mint, 0)),
+        token2022::keyed_account(),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient_tokens, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: pay_out_data(PAY_OUT_TOKEN_2022, mint, recipient, 10_000),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintProgram))],
+    );
+}
+
+#[test]
+fn spl_token_pay_out_rejects_a_token_2022_mint() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_tokens = Pubkey::new_unique();
+    let mut mollusk = treasury_mollusk(&program_id);
+    token2022::add_program(&mut mollusk);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_2022_with_transfer_fee(treasury, 10_000, 100)),
+        (vault, token_account_2022(treasury, mint, 10_000)),
+        (recipient_tokens, token_account_2022(recipient, mint, 0)),
+        token2022::keyed_account(),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient_tokens, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: pay_out_data(PAY_OUT, mint, recipient, 10_000),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintOwner))],
+    );
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{decode_token_account, mint_account, system_account, token_account, treasury_error, treasury_mollusk};
+use mollusk_svm::result::Check;
+use mollusk_svm_programs_token::token;
+use solana_program_option::COption;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use treasury_pinocchio::TreasuryError;
+
+fn approve_data(mint: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8];
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[test]
+fn approve_then_revoke_sets_and_clears_the_delegate() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let spender = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 1_000)),
+        (vault, token_account(treasury, mint, 1_000)),
+        (spender, Account::default()),
+        token::keyed_account(),
+    ];
+
+    let approve_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(spender, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: approve_data(mint, 250),
+    };
+    let mut revoke_data = vec![1u8];
+    revoke_data.extend_from_slice(mint.as_ref());
+    let revoke_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: revoke_data,
+    };
+
+    let approved = mollusk.process_and_validate_instruction(&approve_ix, &accounts, &[Check::success()]);
+    let state = decode_token_account(approved.get_account(&vault).unwrap());
+    assert_eq!(state.delegate, COption::Some(spender));
+    assert_eq!(state.delegated_amount, 250);
+    assert_eq!(state.amount, 1_000);
+
+    let revoked =
+        mollusk.process_and_validate_instruction(&revoke_ix, &approved.resulting_accounts, &[Check::success()]);
+    let state = decode_token_account(revoked.get_account(&vault).unwrap());
+    assert_eq!(state.delegate, COption::None);
+    assert_eq!(state.delegated_amount, 0);
+}
+
+#[test]
+fn approve_rejects_another_admins_treasury() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let intruder = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let spender = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let accounts = vec![
+        (intruder, Account::default()),
+        (treasury, system_account(0)),
+        (mint, mint_account(treasury, 1_000)),
+        (vault, token_account(treasury, mint, 1_000)),
+        (spender, Account::default()),
+        token::keyed_account(),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(intruder, true),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(spender, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data: approve_data(mint, 250),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(treasury_error(TreasuryError::ConstraintSeeds))],
+    );
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{decode_token_account, system_account, treasury_mollusk};
+use mollusk_svm::result::Check;
+use mollusk_svm_programs_token::token;
+use solana_program_option::COption;
+use solana_program_pack::Pack;
+use solana_rent::Rent;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_token_interface::{
+    native_mint,
+    state::{Account as TokenAccount, AccountState},
+};
+
+#[test]
+fn sync_wrapped_sol_counts_lamports_sent_to_the_vault() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (treasury, _bump) = Pubkey::find_program_address(&[b"treasury", admin.as_ref()], &program_id);
+    let vault = Pubkey::new_unique();
+    let mollusk = treasury_mollusk(&program_id);
+
+    let rent_reserve = Rent::default().minimum_balance(TokenAccount::LEN);
+    let mut vault_account = token::create_account_for_token_account(TokenAccount {
+        mint: native_mint::ID,
+        owner: treasury,
+        amount: 0,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::Some(rent_reserve),
+        delegated_amount: 0,
+        close_authority: COption::None,
+    });
+    // A plain lamport transfer into the vault, not yet reflected in its token amount.
+    vault_account.lamports += 2_000;
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (treasury, system_account(0)),
+        (vault, vault_account),
+        token::keyed_account(),
+    ];
+
+    let mut data = vec![6u8];
+    data.extend_from_slice(native_mint::ID.as_ref());
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, false),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(token::ID, false),
+        ],
+        data,
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    let state = decode_token_account(result.get_account(&vault).unwrap());
+    assert_eq!(state.amount, 2_000);
+    assert_eq!(state.is_native, COption::Some(rent_reserve));
+}
@@ -0,0 +1,221 @@
+import {
+  accountMeta,
+  accountRef,
+  arg,
+  ata,
+  expr,
+  mint,
+  pda,
+  program,
+  programAccount,
+  pubkey,
+  token,
+  tx,
+  u64,
+  type TokenProgram,
+} from '@solana-ts-transpiler/sdk';
+
+export const Treasury = program({
+  name: 'Treasury',
+  programId: '3xsn9V9xqnA4MwSNG3AAsxXJzyZcRUkiikqsoCXUC41e',
+});
+
+// Every handler acts for the admin through a PDA of the admin's key, which owns the
+// treasury's token accounts and holds the mint and freeze authorities of its mint.
+const admin = accountMeta('admin', { signer: true });
+const treasury = accountMeta('treasury', { pda: pda(['treasury', accountRef('admin')]) });
+const vault = ata('vault', accountRef('treasury'), arg('mint'), { writable: true });
+const tokenProgram = programAccount('tokenProgram', { address: 'token' });
+
+Treasury.approveSpender = tx({
+  name: 'approveSpender',
+  args: {
+    mint: pubkey(),
+    amount: u64(),
+  },
+  accounts: [admin, treasury, vault, accountMeta('spender', {}), tokenProgram],
+  ops: [
+    token.approve({
+      account: 'vault',
+      delegate: 'spender',
+      authority: 'treasury',
+      amount: expr.arg('amount'),
+      program: 'tokenProgram',
+      signer: 'treasury',
+    }),
+  ],
+});
+
+Treasury.revokeSpender = tx({
+  name: 'revokeSpender',
+  args: {
+    mint: pubkey(),
+  },
+  accounts: [admin, treasury, vault, tokenProgram],
+  ops: [
+    token.revoke({
+      account: 'vault',
+      authority: 'treasury',
+      program: 'tokenProgram',
+      signer: 'treasury',
+    }),
+  ],
+});
+
+Treasury.handOverMint = tx({
+  name: 'handOverMint',
+  args: {
+    mint: pubkey(),
+    newAuthority: pubkey(),
+  },
+  accounts: [admin, treasury, mint('mint', arg('mint'), { writable: true }), tokenProgram],
+  ops: [
+    token.setAuthority({
+      account: 'mint',
+      authority: 'treasury',
+      authorityType: 'mintTokens',
+      newAuthority: expr.arg('newAuthority'),
+      program: 'tokenProgram',
+      signer: 'treasury',
+    }),
+  ],
+});
+
+Treasury.freezeHolder = tx({
+  name: 'freezeHolder',
+  args: {
+    mint: pubkey(),
+    holder: pubkey(),
+  },
+  accounts: [
+    admin,
+    treasury,
+    mint('mint', arg('mint')),
+    ata('holderTokens', arg('holder'), arg('mint'), { writable: true }),
+    tokenProgram,
+  ],
+  ops: [
+    token.freezeAccount({
+      account: 'holderTokens',
+      mint: 'mint',
+      authority: 'treasury',
+      program: 'tokenProgram',
+      signer: 'treasury',
+    }),
+  ],
+});
+
+// The holder account must be frozen; its ata constraint expects that state here.
+Treasury.thawHolder = tx({
+  name: 'thawHolder',
+  args: {
+    mint: pubkey(),
+    holder: pubkey(),
+  },
+  accounts: [
+    admin,
+    treasury,
+    mint('mint', arg('mint')),
+    ata('holderTokens', arg('holder'), arg('mint'), { writable: true }),
+    tokenProgram,
+  ],
+  ops: [
+    token.thawAccount({
+      account: 'holderTokens',
+      mint: 'mint',
+      authority: 'treasury',
+      program: 'tokenProgram',
+      signer: 'treasury',
+    }),
+  ],
+});
+
+// The vault must be empty; its rent goes back to the admin.
+Treasury.closeVault = tx({
+  name: 'closeVault',
+  args: {
+    mint: pubkey(),
+  },
+  accounts: [accountMeta('admin', { signer: true, writable: true }), treasury, vault, tokenProgram],
+  ops: [
+    token.closeAccount({
+      account: 'vault',
+      to: 'admin',
+      authority: 'treasury',
+      program: 'tokenProgram',
+      signer: 'treasury',
+    }),
+  ],
+});
+
+// Anyone may sync: it only brings a wrapped SOL vault's amount up to its lamports.
+Treasury.syncWrappedSol = tx({
+  name: 'syncWrappedSol',
+  args: {
+    mint: pubkey(),
+  },
+  accounts: [accountMeta('admin', {}), treasury, vault, tokenProgram],
+  ops: [
+    token.syncNative({
+      account: 'vault',
+      program: 'tokenProgram',
+    }),
+  ],
+});
+
+// Payouts use TransferChecked, the only transfer Token-2022 takes for mints with transfer
+// fees or hooks, so the same handler works on every token program.
+const payOut = (name: string, tokens: TokenProgram) =>
+  tx({
+    name,
+    args: {
+      mint: pubkey(),
+      recipient: pubkey(),
+      amount: u64(),
+    },
+    accounts: [
+      admin,
+      treasury,
+      mint('mint', arg('mint'), { tokenProgram: tokens }),
+      ata('vault', accountRef('treasury'), arg('mint'), { writable: true, tokenProgram: tokens }),
+      ata('recipientTokens', arg('recipient'), arg('mint'), { writable: true, tokenProgram: tokens }),
+      programAccount('tokenProgram', { tokenProgram: tokens }),
+    ],
+    ops: [
+      token.transferChecked({
+        from: 'vault',
+        mint: 'mint',
+        to: 'recipientTokens',
+        authority: 'treasury',
+        amount: expr.arg('amount'),
+        program: 'tokenProgram',
+        signer: 'treasury',
+      }),
+    ],
+  });
+
+Treasury.payOut = payOut('payOut', 'token');
+
+// The treasury PDA signs the burn with its seeds, as in a buyback-and-burn.
+Treasury.burnFromVault = tx({
+  name: 'burnFromVault',
+  args: {
+    mint: pubkey(),
+    amount: u64(),
+  },
+  accounts: [admin, treasury, mint('mint', arg('mint'), { writable: true }), vault, tokenProgram],
+  ops: [
+    token.burn({
+      from: 'vault',
+      mint: 'mint',
+      authority: 'treasury',
+      amount: expr.arg('amount'),
+      program: 'tokenProgram',
+      signer: 'treasury',
+    }),
+  ],
+});
+
+Treasury.payOutToken2022 = payOut('payOutToken2022', 'token2022');
+
+Treasury.payOutEither = payOut('payOutEither', 'either');
@@ -10,9 +10,9 @@ import {
 } from '@solana/spl-token';
 import {
   VaultClient,
-  decodeVaultState,
   deriveVaultAuthorityPda,
   deriveVaultPda,
+  getVaultStateDecoder,
   vaultSummary,
   type VaultState,
 } from '@solana-ts-transpiler/client';
@@ -52,7 +52,7 @@ export default function App() {
       setVaultState(null);
       return;
     }
-    setVaultState(decodeVaultState(account.data));
+    setVaultState(getVaultStateDecoder().decode(account.data));
   }, [connection, vault]);
 
   const onDeposit = useCallback(async () => {
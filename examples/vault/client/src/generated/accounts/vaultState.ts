@@ -13,14 +13,19 @@ import {
   decodeAccount,
   fetchEncodedAccount,
   fetchEncodedAccounts,
+  fixDecoderSize,
+  fixEncoderSize,
   getAddressDecoder,
   getAddressEncoder,
+  getBytesDecoder,
+  getBytesEncoder,
   getStructDecoder,
   getStructEncoder,
   getU64Decoder,
   getU64Encoder,
   getU8Decoder,
   getU8Encoder,
+  transformEncoder,
   type Account,
   type Address,
   type EncodedAccount,
@@ -31,10 +36,18 @@ import {
   type FixedSizeEncoder,
   type MaybeAccount,
   type MaybeEncodedAccount,
+  type ReadonlyUint8Array,
 } from "@solana/kit";
 import { findVaultPda, VaultSeeds } from "../pdas";
 
+export const VAULT_STATE_DISCRIMINATOR = new Uint8Array([228]);
+
+export function getVaultStateDiscriminatorBytes() {
+  return fixEncoderSize(getBytesEncoder(), 1).encode(VAULT_STATE_DISCRIMINATOR);
+}
+
 export type VaultState = {
+  discriminator: ReadonlyUint8Array;
   admin: Address;
   underlyingMint: Address;
   shareMint: Address;
@@ -54,19 +67,24 @@ export type VaultStateArgs = {
 
 /** Gets the encoder for {@link VaultStateArgs} account data. */
 export function getVaultStateEncoder(): FixedSizeEncoder<VaultStateArgs> {
-  return getStructEncoder([
-    ["admin", getAddressEncoder()],
-    ["underlyingMint", getAddressEncoder()],
-    ["shareMint", getAddressEncoder()],
-    ["totalDeposits", getU64Encoder()],
-    ["totalShares", getU64Encoder()],
-    ["bump", getU8Encoder()],
-  ]);
+  return transformEncoder(
+    getStructEncoder([
+      ["discriminator", fixEncoderSize(getBytesEncoder(), 1)],
+      ["admin", getAddressEncoder()],
+      ["underlyingMint", getAddressEncoder()],
+      ["shareMint", getAddressEncoder()],
+      ["totalDeposits", getU64Encoder()],
+      ["totalShares", getU64Encoder()],
+      ["bump", getU8Encoder()],
+    ]),
+    (value) => ({ ...value, discriminator: VAULT_STATE_DISCRIMINATOR }),
+  );
 }
 
 /** Gets the decoder for {@link VaultState} account data. */
 export function getVaultStateDecoder(): FixedSizeDecoder<VaultState> {
   return getStructDecoder([
+    ["discriminator", fixDecoderSize(getBytesDecoder(), 1)],
     ["admin", getAddressDecoder()],
     ["underlyingMint", getAddressDecoder()],
     ["shareMint", getAddressDecoder()],
@@ -138,7 +156,7 @@ export async function fetchAllMaybeVaultState(
 }
 
 export function getVaultStateSize(): number {
-  return 113;
+  return 114;
 }
 
 export async function fetchVaultStateFromSeeds(
@@ -8,4 +8,6 @@
 
 export * from "./createVault";
 export * from "./deposit";
+export * from "./setAdmin";
 export * from "./withdraw";
+export * from "./withdrawUnderlying";
@@ -8,6 +8,8 @@
 
 import {
   containsBytes,
+  fixEncoderSize,
+  getBytesEncoder,
   getU8Encoder,
   type Address,
   type ReadonlyUint8Array,
@@ -15,7 +17,9 @@ import {
 import {
   type ParsedCreateVaultInstruction,
   type ParsedDepositInstruction,
+  type ParsedSetAdminInstruction,
   type ParsedWithdrawInstruction,
+  type ParsedWithdrawUnderlyingInstruction,
 } from "../instructions";
 
 export const VAULT_PROGRAM_ADDRESS =
@@ -25,10 +29,30 @@ export enum VaultAccount {
   VaultState,
 }
 
+export function identifyVaultAccount(
+  account: { data: ReadonlyUint8Array } | ReadonlyUint8Array,
+): VaultAccount {
+  const data = "data" in account ? account.data : account;
+  if (
+    containsBytes(
+      data,
+      fixEncoderSize(getBytesEncoder(), 1).encode(new Uint8Array([228])),
+      0,
+    )
+  ) {
+    return VaultAccount.VaultState;
+  }
+  throw new Error(
+    "The provided account could not be identified as a vault account.",
+  );
+}
+
 export enum VaultInstruction {
   CreateVault,
   Deposit,
   Withdraw,
+  WithdrawUnderlying,
+  SetAdmin,
 }
 
 export function identifyVaultInstruction(
@@ -44,6 +68,12 @@ export function identifyVaultInstruction(
   if (containsBytes(data, getU8Encoder().encode(2), 0)) {
     return VaultInstruction.Withdraw;
   }
+  if (containsBytes(data, getU8Encoder().encode(3), 0)) {
+    return VaultInstruction.WithdrawUnderlying;
+  }
+  if (containsBytes(data, getU8Encoder().encode(4), 0)) {
+    return VaultInstruction.SetAdmin;
+  }
   throw new Error(
     "The provided instruction could not be identified as a vault instruction.",
   );
@@ -60,4 +90,10 @@ export type ParsedVaultInstruction<
     } & ParsedDepositInstruction<TProgram>)
   | ({
       instructionType: VaultInstruction.Withdraw;
-    } & ParsedWithdrawInstruction<TProgram>);
+    } & ParsedWithdrawInstruction<TProgram>)
+  | ({
+      instructionType: VaultInstruction.WithdrawUnderlying;
+    } & ParsedWithdrawUnderlyingInstruction<TProgram>)
+  | ({
+      instructionType: VaultInstruction.SetAdmin;
+    } & ParsedSetAdminInstruction<TProgram>);
@@ -9,6 +9,8 @@ import {
   getCreateVaultInstruction,
   getDepositInstruction,
   getWithdrawInstruction,
+  getWithdrawUnderlyingInstruction,
+  getSetAdminInstruction,
   type VaultState,
 } from './generated';
 
@@ -139,6 +141,60 @@ export const VaultClient = {
       { pubkey: accounts.tokenProgram, isSigner: false, isWritable: false }
     ]);
   },
+
+  withdrawUnderlying(args: {
+  amount: bigint;
+}, accounts: {
+  user: PublicKey;
+  vault: PublicKey;
+  vaultAuthority: PublicKey;
+  userShares: PublicKey;
+  shareMint: PublicKey;
+  userUnderlying: PublicKey;
+  vaultUnderlying: PublicKey;
+  tokenProgram: PublicKey;
+}): TransactionInstruction {
+    
+    const instruction = getWithdrawUnderlyingInstruction({
+      user: toAddress(accounts.user),
+      vault: toAddress(accounts.vault),
+      vaultAuthority: toAddress(accounts.vaultAuthority),
+      userShares: toAddress(accounts.userShares),
+      shareMint: toAddress(accounts.shareMint),
+      userUnderlying: toAddress(accounts.userUnderlying),
+      vaultUnderlying: toAddress(accounts.vaultUnderlying),
+      tokenProgram: toAddress(accounts.tokenProgram),
+      amount: args.amount,
+    } as unknown as Parameters<typeof getWithdrawUnderlyingInstruction>[0]);
+    return toWeb3Instruction(instruction, [
+      { pubkey: accounts.user, isSigner: true, isWritable: false },
+      { pubkey: accounts.vault, isSigner: false, isWritable: true },
+      { pubkey: accounts.vaultAuthority, isSigner: false, isWritable: false },
+      { pubkey: accounts.userShares, isSigner: false, isWritable: true },
+      { pubkey: accounts.shareMint, isSigner: false, isWritable: true },
+      { pubkey: accounts.userUnderlying, isSigner: false, isWritable: true },
+      { pubkey: accounts.vaultUnderlying, isSigner: false, isWritable: true },
+      { pubkey: accounts.tokenProgram, isSigner: false, isWritable: false }
+    ]);
+  },
+
+  setAdmin(args: {
+  newAdmin: PublicKey;
+}, accounts: {
+  admin: PublicKey;
+  vault: PublicKey;
+}): TransactionInstruction {
+    
+    const instruction = getSetAdminInstruction({
+      admin: toAddress(accounts.admin),
+      vault: toAddress(accounts.vault),
+      newAdmin: toAddress(args.newAdmin),
+    } as unknown as Parameters<typeof getSetAdminInstruction>[0]);
+    return toWeb3Instruction(instruction, [
+      { pubkey: accounts.admin, isSigner: true, isWritable: false },
+      { pubkey: accounts.vault, isSigner: false, isWritable: true }
+    ]);
+  },
   },
 };
 
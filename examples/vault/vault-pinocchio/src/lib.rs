@@ -17,6 +17,7 @@ use pinocchio::{
     ProgramResult,
 };
 use pinocchio_tkn::common::{Burn, MintTo, Transfer};
+use pinocchio_tkn::{state::{Mint, AccountState, TokenAccount, MINT_SIZE, TOKEN_ACCOUNT_SIZE}, TOKEN_PROGRAM_ID};
 
 program_entrypoint!(process_instruction);
 
@@ -37,8 +38,61 @@ fn panic_handler(_info: &core::panic::PanicInfo<'_>) -> ! {
 #[cfg(not(target_arch = "bpf"))]
 extern crate std;
 
-#[derive(Clone, Copy)]
-struct VaultState {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum VaultError {
+    /// Arithmetic overflow
+    Overflow = 1000,
+    /// Division by zero
+    DivideByZero = 1001,
+    /// Unknown instruction discriminator
+    UnknownInstruction = 1002,
+    /// Account must be writable
+    AccountNotWritable = 1003,
+    /// Account does not match its PDA seeds
+    ConstraintSeeds = 1004,
+    /// Account is not owned by the expected program
+    ConstraintOwner = 1005,
+    /// Token account owner does not match
+    ConstraintTokenOwner = 1006,
+    /// Token account mint does not match
+    ConstraintMint = 1007,
+    /// Account address does not match
+    ConstraintAddress = 1008,
+    /// Program account does not match the expected program
+    ConstraintProgram = 1009,
+    /// Program account is not executable
+    ProgramNotExecutable = 1010,
+    /// Account discriminator does not match
+    AccountDiscriminatorMismatch = 1011,
+    /// Account is already initialized
+    AccountAlreadyInitialized = 1012,
+    /// The same account was passed twice as mutable state
+    DuplicateMutableAccount = 1013,
+    /// An account does not match the key it must equal
+    ConstraintHasOne = 1014,
+    /// A vec argument index is out of bounds
+    IndexOutOfBounds = 1015,
+    /// Token account is frozen, or not frozen for a thaw
+    ConstraintTokenState = 1016,
+    /// Instruction data does not decode as the instruction args
+    InvalidArgs = 1017,
+    /// Account data does not decode as its layout
+    InvalidAccountLayout = 1018,
+    /// Account does not match its equals constraint
+    ConstraintEquals = 1019,
+    /// Amount must be greater than zero
+    ZeroAmount = 6000,
+}
+
+impl From<VaultError> for ProgramError {
+    fn from(error: VaultError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VaultState {
     pub admin: Pubkey,
     pub underlying_mint: Pubkey,
     pub share_mint: Pubkey,
@@ -48,20 +102,60 @@ struct VaultState {
 }
 
 impl VaultState {
-    const LEN: usize = 113;
+    pub const LEN: usize = 114;
+    pub const DISCRIMINATOR: [u8; 1] = [228];
+
+    // Freshly allocated accounts are zeroed, so any non-zero discriminator byte
+    // means the account was initialized, possibly as another account type.
+    fn is_initialized(account: &AccountInfo) -> Result<bool, ProgramError> {
+        let data = account.try_borrow_data()?;
+        Ok(data.iter().take(Self::DISCRIMINATOR.len()).any(|byte| *byte != 0))
+    }
 
     fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
         let data = account.try_borrow_data()?;
+        Self::unpack(&data)
+    }
+
+    fn store(account: &AccountInfo, state: &Self) -> Result<(), ProgramError> {
+        let mut data = account.try_borrow_mut_data()?;
+        state.pack(&mut data)
+    }
+
+    /// Decodes account data as `load` does, so host code can read raw account bytes.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
         if data.len() < Self::LEN {
             return Err(ProgramError::AccountDataTooSmall);
         }
-        let admin = read_pubkey(&data, 0)?;
-        let underlying_mint = read_pubkey(&data, 32)?;
-        let share_mint = read_pubkey(&data, 64)?;
-        let total_deposits = read_u64(&data, 96)?;
-        let total_shares = read_u64(&data, 104)?;
-        let bump = read_u8(&data, 112)?;
-        Ok(Self {
+        if data[..Self::DISCRIMINATOR.len()] != Self::DISCRIMINATOR {
+            return Err(VaultError::AccountDiscriminatorMismatch.into());
+        }
+        Self::read(data).ok_or(VaultError::InvalidAccountLayout.into())
+    }
+
+    /// Writes the discriminator and fields to the first `LEN` bytes, as `store` does.
+    pub fn pack(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[..Self::DISCRIMINATOR.len()].copy_from_slice(&Self::DISCRIMINATOR);
+        write_pubkey(data, 1, &self.admin)?;
+        write_pubkey(data, 33, &self.underlying_mint)?;
+        write_pubkey(data, 65, &self.share_mint)?;
+        write_u64(data, 97, self.total_deposits)?;
+        write_u64(data, 105, self.total_shares)?;
+        write_u8(data, 113, self.bump)?;
+        Ok(())
+    }
+
+    fn read(data: &[u8]) -> Option<Self> {
+        let admin = read_pubkey(data, 1)?;
+        let underlying_mint = read_pubkey(data, 33)?;
+        let share_mint = read_pubkey(data, 65)?;
+        let total_deposits = read_u64(data, 97)?;
+        let total_shares = read_u64(data, 105)?;
+        let bump = read_u8(data, 113)?;
+        Some(Self {
             admin,
             underlying_mint,
             share_mint,
@@ -70,20 +164,101 @@ impl VaultState {
             bump,
         })
     }
+}
 
-    fn store(account: &AccountInfo, state: &Self) -> Result<(), ProgramError> {
-        let mut data = account.try_borrow_mut_data()?;
-        if data.len() < Self::LEN {
-            return Err(ProgramError::AccountDataTooSmall);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ratio {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VaultSummary {
+    pub underlying_mint: Pubkey,
+    pub share_mint: Pubkey,
+    pub total_deposits: u64,
+    pub total_shares: u64,
+    pub exchange_rate: Ratio,
+}
+
+impl VaultSummary {
+    pub const LEN: usize = 96;
+
+    fn set_return_data(&self) -> ProgramResult {
+        let mut data = [0u8; Self::LEN];
+        write_pubkey(&mut data, 0, &self.underlying_mint)?;
+        write_pubkey(&mut data, 32, &self.share_mint)?;
+        write_u64(&mut data, 64, self.total_deposits)?;
+        write_u64(&mut data, 72, self.total_shares)?;
+        write_u64(&mut data, 80, self.exchange_rate.numerator)?;
+        write_u64(&mut data, 88, self.exchange_rate.denominator)?;
+        pinocchio::cpi::set_return_data(&data);
+        Ok(())
+    }
+
+    /// Decodes the return data of `vaultSummary`. RPC nodes strip trailing zero bytes,
+    /// so shorter input is zero-padded.
+    pub fn decode(return_data: &[u8]) -> Option<Self> {
+        if return_data.len() > Self::LEN {
+            return None;
         }
-        write_pubkey(&mut data, 0, &state.admin)?;
-        write_pubkey(&mut data, 32, &state.underlying_mint)?;
-        write_pubkey(&mut data, 64, &state.share_mint)?;
-        write_u64(&mut data, 96, state.total_deposits)?;
-        write_u64(&mut data, 104, state.total_shares)?;
-        write_u8(&mut data, 112, state.bump)?;
+        let mut data = [0u8; Self::LEN];
+        data[..return_data.len()].copy_from_slice(return_data);
+        Some(Self {
+            underlying_mint: read_pubkey(&data, 0)?,
+            share_mint: read_pubkey(&data, 32)?,
+            total_deposits: read_u64(&data, 64)?,
+            total_shares: read_u64(&data, 72)?,
+            exchange_rate: Ratio {
+                numerator: read_u64(&data, 80)?,
+                denominator: read_u64(&data, 88)?,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShareValue {
+    pub price_per_share_floor: u64,
+    pub price_per_share_nearest: u64,
+    pub price_per_share_ceil: u64,
+    pub value_floor: u64,
+    pub value_nearest: u64,
+    pub value_ceil: u64,
+}
+
+impl ShareValue {
+    pub const LEN: usize = 48;
+
+    fn set_return_data(&self) -> ProgramResult {
+        let mut data = [0u8; Self::LEN];
+        write_u64(&mut data, 0, self.price_per_share_floor)?;
+        write_u64(&mut data, 8, self.price_per_share_nearest)?;
+        write_u64(&mut data, 16, self.price_per_share_ceil)?;
+        write_u64(&mut data, 24, self.value_floor)?;
+        write_u64(&mut data, 32, self.value_nearest)?;
+        write_u64(&mut data, 40, self.value_ceil)?;
+        pinocchio::cpi::set_return_data(&data);
         Ok(())
     }
+
+    /// Decodes the return data of `shareValue`. RPC nodes strip trailing zero bytes,
+    /// so shorter input is zero-padded.
+    pub fn decode(return_data: &[u8]) -> Option<Self> {
+        if return_data.len() > Self::LEN {
+            return None;
+        }
+        let mut data = [0u8; Self::LEN];
+        data[..return_data.len()].copy_from_slice(return_data);
+        Some(Self {
+            price_per_share_floor: read_u64(&data, 0)?,
+            price_per_share_nearest: read_u64(&data, 8)?,
+            price_per_share_ceil: read_u64(&data, 16)?,
+            value_floor: read_u64(&data, 24)?,
+            value_nearest: read_u64(&data, 32)?,
+            value_ceil: read_u64(&data, 40)?,
+        })
+    }
 }
 
 
@@ -128,35 +303,81 @@ fn create_program_account(
     Ok(())
 }
 
+fn check_token_layout(data: &[u8], base_len: usize, account_type: u8) -> ProgramResult {
+    if data.len() == base_len || (data.len() > TOKEN_ACCOUNT_SIZE && data[TOKEN_ACCOUNT_SIZE] == account_type) {
+        return Ok(());
+    }
+    Err(VaultError::InvalidAccountLayout.into())
+}
+
+fn check_token_account(account: &AccountInfo, owner: &Pubkey, mint: &Pubkey, token_programs: &[Pubkey], state: AccountState) -> ProgramResult {
+    if !token_programs.iter().any(|token_program| account.is_owned_by(token_program)) {
+        return Err(VaultError::ConstraintOwner.into());
+    }
+    let data = account.try_borrow_data()?;
+    check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2)?;
+    let token_account = TokenAccount::from_bytes(&data)?;
+    if token_account.mint() != mint {
+        return Err(VaultError::ConstraintMint.into());
+    }
+    if token_account.owner() != owner {
+        return Err(VaultError::ConstraintTokenOwner.into());
+    }
+    // `from_bytes` already rejects uninitialized accounts.
+    if token_account.state() != state {
+        return Err(VaultError::ConstraintTokenState.into());
+    }
+    Ok(())
+}
+
+fn check_mint_account(account: &AccountInfo, address: &Pubkey, token_programs: &[Pubkey]) -> ProgramResult {
+    if account.key() != address {
+        return Err(VaultError::ConstraintAddress.into());
+    }
+    if !token_programs.iter().any(|token_program| account.is_owned_by(token_program)) {
+        return Err(VaultError::ConstraintOwner.into());
+    }
+    let data = account.try_borrow_data()?;
+    check_token_layout(&data, MINT_SIZE, 1)?;
+    Mint::from_bytes(&data)?;
+    Ok(())
+}
+
+fn check_program_account(account: &AccountInfo, program_ids: &[Pubkey]) -> ProgramResult {
+    if !program_ids.contains(account.key()) {
+        return Err(VaultError::ConstraintProgram.into());
+    }
+    if !account.executable() {
+        return Err(VaultError::ProgramNotExecutable.into());
+    }
+    Ok(())
+}
 
-fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey, ProgramError> {
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
     if data.len() < offset + 32 {
-        return Err(ProgramError::InvalidInstructionData);
+        return None;
     }
     let mut out = [0u8; 32];
     out.copy_from_slice(&data[offset..offset + 32]);
-    Ok(out)
+    Some(out)
 }
 
-fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
     if data.len() < offset + 8 {
-        return Err(ProgramError::InvalidInstructionData);
+        return None;
     }
     let mut buf = [0u8; 8];
     buf.copy_from_slice(&data[offset..offset + 8]);
-    Ok(u64::from_le_bytes(buf))
+    Some(u64::from_le_bytes(buf))
 }
 
-fn read_u8(data: &[u8], offset: usize) -> Result<u8, ProgramError> {
-    if data.len() <= offset {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    Ok(data[offset])
+fn read_u8(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).copied()
 }
 
 fn write_pubkey(data: &mut [u8], offset: usize, value: &Pubkey) -> Result<(), ProgramError> {
     if data.len() < offset + 32 {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VaultError::InvalidAccountLayout.into());
     }
     data[offset..offset + 32].copy_from_slice(value);
     Ok(())
@@ -164,7 +385,7 @@ fn write_pubkey(data: &mut [u8], offset: usize, value: &Pubkey) -> Result<(), Pr
 
 fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Result<(), ProgramError> {
     if data.len() < offset + 8 {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VaultError::InvalidAccountLayout.into());
     }
     data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
     Ok(())
@@ -172,52 +393,102 @@ fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Result<(), ProgramEr
 
 fn write_u8(data: &mut [u8], offset: usize, value: u8) -> Result<(), ProgramError> {
     if data.len() <= offset {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(VaultError::InvalidAccountLayout.into());
     }
     data[offset] = value;
     Ok(())
 }
 
-#[allow(dead_code)]
-fn checked_add(a: u64, b: u64) -> Result<u64, ProgramError> {
-    a.checked_add(b).ok_or(ProgramError::InvalidInstructionData)
+// Lets the checked helpers below take any integer type the DSL supports.
+trait CheckedInt: Copy + PartialOrd {
+    const ZERO: Self;
+    fn add_checked(self, other: Self) -> Option<Self>;
+    fn sub_checked(self, other: Self) -> Option<Self>;
+    fn div_checked(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_int {
+    ($($ty:ty),*) => {$(
+        impl CheckedInt for $ty {
+            const ZERO: Self = 0;
+            fn add_checked(self, other: Self) -> Option<Self> {
+                self.checked_add(other)
+            }
+            fn sub_checked(self, other: Self) -> Option<Self> {
+                self.checked_sub(other)
+            }
+            fn div_checked(self, other: Self) -> Option<Self> {
+                let quotient = self.checked_div(other)?;
+                let remainder = self % other;
+                if remainder != Self::ZERO && (remainder < Self::ZERO) != (other < Self::ZERO) {
+                    Some(quotient - 1)
+                } else {
+                    Some(quotient)
+                }
+            }
+        }
+    )*};
+}
+
+impl_checked_int!(u8, u16, u32, u64, u128, i64);
+
+fn checked_add<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.add_checked(b).ok_or(VaultError::Overflow)?)
+}
+
+fn checked_sub<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.sub_checked(b).ok_or(VaultError::Overflow)?)
 }
 
-#[allow(dead_code)]
-fn checked_sub(a: u64, b: u64) -> Result<u64, ProgramError> {
-    a.checked_sub(b).ok_or(ProgramError::InvalidInstructionData)
+// Floors, on signed types too. Signed division overflows on MIN / -1, so the
+// quotient is checked as well.
+fn checked_div<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    if b == T::ZERO {
+        return Err(VaultError::DivideByZero.into());
+    }
+    Ok(a.div_checked(b).ok_or(VaultError::Overflow)?)
 }
 
-#[allow(dead_code)]
-fn checked_mul(a: u64, b: u64) -> Result<u64, ProgramError> {
-    let value = (a as u128).checked_mul(b as u128).ok_or(ProgramError::InvalidInstructionData)?;
-    if value > u64::MAX as u128 {
-        return Err(ProgramError::InvalidInstructionData);
+fn checked_div_ceil(a: u64, b: u64) -> Result<u64, ProgramError> {
+    if b == 0 {
+        return Err(VaultError::DivideByZero.into());
     }
-    Ok(value as u64)
+    Ok(a.div_ceil(b))
 }
 
-#[allow(dead_code)]
-fn checked_div(a: u64, b: u64) -> Result<u64, ProgramError> {
+// Rounds halves up. `remainder >= b - remainder` avoids overflowing `2 * remainder`.
+fn checked_div_round(a: u64, b: u64) -> Result<u64, ProgramError> {
     if b == 0 {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(VaultError::DivideByZero.into());
     }
-    Ok(a / b)
+    let (quotient, remainder) = (a / b, a % b);
+    Ok(if remainder >= b - remainder { quotient + 1 } else { quotient })
 }
 
-#[allow(dead_code)]
 fn checked_mul_div(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
     if c == 0 {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(VaultError::DivideByZero.into());
     }
-    let value = (a as u128)
-        .checked_mul(b as u128)
-        .ok_or(ProgramError::InvalidInstructionData)?
-        / (c as u128);
-    if value > u64::MAX as u128 {
-        return Err(ProgramError::InvalidInstructionData);
+    let value = (a as u128) * (b as u128) / (c as u128);
+    Ok(u64::try_from(value).map_err(|_| VaultError::Overflow)?)
+}
+
+fn checked_mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
+    if c == 0 {
+        return Err(VaultError::DivideByZero.into());
     }
-    Ok(value as u64)
+    let value = ((a as u128) * (b as u128)).div_ceil(c as u128);
+    Ok(u64::try_from(value).map_err(|_| VaultError::Overflow)?)
+}
+
+fn checked_mul_div_round(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
+    if c == 0 {
+        return Err(VaultError::DivideByZero.into());
+    }
+    let (product, divisor) = ((a as u128) * (b as u128), c as u128);
+    let (quotient, remainder) = (product / divisor, product % divisor);
+    let value = if remainder >= divisor - remainder { quotient + 1 } else { quotient };
+    Ok(u64::try_from(value).map_err(|_| VaultError::Overflow)?)
 }
 
 pub fn process_instruction(
@@ -226,14 +497,18 @@ pub fn process_instruction(
     data: &[u8],
 ) -> ProgramResult {
     if data.is_empty() {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(VaultError::UnknownInstruction.into());
     }
 
     match data[0] {
         0 => handle_create_vault(program_id, accounts, &data[1..]),
         1 => handle_deposit(program_id, accounts, &data[1..]),
         2 => handle_withdraw(program_id, accounts, &data[1..]),
-        _ => Err(ProgramError::InvalidInstructionData),
+        3 => handle_withdraw_underlying(program_id, accounts, &data[1..]),
+        4 => handle_set_admin(program_id, accounts, &data[1..]),
+        5 => handle_vault_summary(program_id, accounts, &data[1..]),
+        6 => handle_share_value(program_id, accounts, &data[1..]),
+        _ => Err(VaultError::UnknownInstruction.into()),
     }
 }
 
@@ -244,8 +519,8 @@ struct CreateVaultArgs {
 }
 
 fn decode_create_vault_args(data: &[u8]) -> Result<CreateVaultArgs, ProgramError> {
-    let underlying_mint = read_pubkey(data, 0)?;
-    let share_mint = read_pubkey(data, 32)?;
+    let underlying_mint = read_pubkey(data, 0).ok_or(VaultError::InvalidArgs)?;
+    let share_mint = read_pubkey(data, 32).ok_or(VaultError::InvalidArgs)?;
     Ok(CreateVaultArgs {
         underlying_mint,
         share_mint,
@@ -263,24 +538,26 @@ fn handle_create_vault(
     let args = decode_create_vault_args(data)?;
     let payer = &accounts[0];
     if !payer.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
-    if !payer.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !payer.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let vault = &accounts[1];
-    if !vault.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let (vault_key, vault_bump) = pubkey::find_program_address(&[b"vault", args.underlying_mint.as_ref()], program_id);
-    if vault.key() != &vault_key { return Err(ProgramError::InvalidSeeds); }
+    if vault.key() != &vault_key { return Err(VaultError::ConstraintSeeds.into()); }
     let vault_authority = &accounts[2];
     let (vault_authority_key, _vault_authority_bump) = pubkey::find_program_address(&[b"authority", vault.key().as_ref()], program_id);
-    if vault_authority.key() != &vault_authority_key { return Err(ProgramError::InvalidSeeds); }
+    if vault_authority.key() != &vault_authority_key { return Err(VaultError::ConstraintSeeds.into()); }
     let underlying_mint = &accounts[3];
-    let _ = underlying_mint;
     let share_mint = &accounts[4];
-    if !share_mint.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !share_mint.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let vault_underlying = &accounts[5];
-    if !vault_underlying.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_underlying.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let token_program = &accounts[6];
-    let _ = token_program;
     let system_program = &accounts[7];
-    let _ = system_program;
+    check_mint_account(underlying_mint, &args.underlying_mint, &[TOKEN_PROGRAM_ID])?;
+    check_mint_account(share_mint, &args.share_mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(vault_underlying, vault_authority.key(), &args.underlying_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    check_program_account(system_program, &[SYSTEM_PROGRAM_ID])?;
     
     if !vault.is_owned_by(program_id) {
         let vault_bump_ref = [vault_bump];
@@ -288,14 +565,18 @@ fn handle_create_vault(
         let vault_signer = Signer::from(&vault_seeds);
         create_program_account(payer, vault, program_id, VaultState::LEN, Some(&vault_signer))?;
     }
-    if !vault.is_owned_by(program_id) { return Err(ProgramError::IncorrectProgramId); }
-    let mut vault_state = VaultState::load(vault)?;
-    vault_state.admin = *payer.key();
-    vault_state.underlying_mint = args.underlying_mint;
-    vault_state.share_mint = args.share_mint;
-    vault_state.total_deposits = 0u64;
-    vault_state.total_shares = 0u64;
-    vault_state.bump = vault_bump;
+    if !vault.is_owned_by(program_id) { return Err(VaultError::ConstraintOwner.into()); }
+    if VaultState::is_initialized(vault)? {
+        return Err(VaultError::AccountAlreadyInitialized.into());
+    }
+    let vault_state = VaultState {
+        admin: *payer.key(),
+        underlying_mint: args.underlying_mint,
+        share_mint: args.share_mint,
+        total_deposits: 0u64,
+        total_shares: 0u64,
+        bump: vault_bump,
+    };
     VaultState::store(vault, &vault_state)?;
     Ok(())
 }
@@ -306,7 +587,7 @@ struct DepositArgs {
 }
 
 fn decode_deposit_args(data: &[u8]) -> Result<DepositArgs, ProgramError> {
-    let amount = read_u64(data, 0)?;
+    let amount = read_u64(data, 0).ok_or(VaultError::InvalidArgs)?;
     Ok(DepositArgs {
         amount,
     })
@@ -324,27 +605,36 @@ fn handle_deposit(
     let user = &accounts[0];
     if !user.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
     let vault = &accounts[1];
-    if !vault.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let vault_authority = &accounts[2];
     let (vault_authority_key, vault_authority_bump) = pubkey::find_program_address(&[b"authority", vault.key().as_ref()], program_id);
-    if vault_authority.key() != &vault_authority_key { return Err(ProgramError::InvalidSeeds); }
+    if vault_authority.key() != &vault_authority_key { return Err(VaultError::ConstraintSeeds.into()); }
     let user_underlying = &accounts[3];
-    if !user_underlying.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_underlying.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let vault_underlying = &accounts[4];
-    if !vault_underlying.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_underlying.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let share_mint = &accounts[5];
-    if !share_mint.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !share_mint.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let user_shares = &accounts[6];
-    if !user_shares.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_shares.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let token_program = &accounts[7];
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
     let vault_authority_bump_ref = [vault_authority_bump];
     let vault_authority_seeds = seeds!(b"authority", vault.key().as_ref(), &vault_authority_bump_ref);
     let vault_authority_signer = Signer::from(&vault_authority_seeds);
-    // Vault must be owned by this program
+    // State account must be owned by this program
     if !vault.is_owned_by(program_id) {
-        return Err(ProgramError::IncorrectProgramId);
+        return Err(VaultError::ConstraintOwner.into());
     }
     let mut vault_state = VaultState::load(vault)?;
+    check_token_account(user_underlying, user.key(), &vault_state.underlying_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_underlying, vault_authority.key(), &vault_state.underlying_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_mint_account(share_mint, &vault_state.share_mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(user_shares, user.key(), &vault_state.share_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    if args.amount == 0u64 {
+        return Err(VaultError::ZeroAmount.into());
+    }
+    let shares_to_mint = if vault_state.total_shares == 0u64 { args.amount } else { checked_mul_div(args.amount, vault_state.total_shares, vault_state.total_deposits)? };
     Transfer {
         source: user_underlying,
         destination: vault_underlying,
@@ -356,11 +646,11 @@ fn handle_deposit(
         mint: share_mint,
         destination: user_shares,
         authority: vault_authority,
-        amount: if vault_state.total_shares == 0u64 { args.amount } else { checked_mul_div(args.amount, vault_state.total_shares, vault_state.total_deposits)? },
+        amount: shares_to_mint,
         program_id: Some(token_program.key()),
-    }.invoke_signed(&[vault_authority_signer])?;
+    }.invoke_signed(core::slice::from_ref(&vault_authority_signer))?;
     let next_total_deposits = checked_add(vault_state.total_deposits, args.amount)?;
-    let next_total_shares = checked_add(vault_state.total_shares, if vault_state.total_shares == 0u64 { args.amount } else { checked_mul_div(args.amount, vault_state.total_shares, vault_state.total_deposits)? })?;
+    let next_total_shares = checked_add(vault_state.total_shares, shares_to_mint)?;
     vault_state.total_deposits = next_total_deposits;
     vault_state.total_shares = next_total_shares;
     VaultState::store(vault, &vault_state)?;
@@ -373,7 +663,7 @@ struct WithdrawArgs {
 }
 
 fn decode_withdraw_args(data: &[u8]) -> Result<WithdrawArgs, ProgramError> {
-    let shares = read_u64(data, 0)?;
+    let shares = read_u64(data, 0).ok_or(VaultError::InvalidArgs)?;
     Ok(WithdrawArgs {
         shares,
     })
@@ -391,27 +681,35 @@ fn handle_withdraw(
     let user = &accounts[0];
     if !user.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
     let vault = &accounts[1];
-    if !vault.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let vault_authority = &accounts[2];
     let (vault_authority_key, vault_authority_bump) = pubkey::find_program_address(&[b"authority", vault.key().as_ref()], program_id);
-    if vault_authority.key() != &vault_authority_key { return Err(ProgramError::InvalidSeeds); }
+    if vault_authority.key() != &vault_authority_key { return Err(VaultError::ConstraintSeeds.into()); }
     let user_shares = &accounts[3];
-    if !user_shares.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_shares.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let share_mint = &accounts[4];
-    if !share_mint.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !share_mint.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let user_underlying = &accounts[5];
-    if !user_underlying.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !user_underlying.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let vault_underlying = &accounts[6];
-    if !vault_underlying.is_writable() { return Err(ProgramError::InvalidAccountData); }
+    if !vault_underlying.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
     let token_program = &accounts[7];
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
     let vault_authority_bump_ref = [vault_authority_bump];
     let vault_authority_seeds = seeds!(b"authority", vault.key().as_ref(), &vault_authority_bump_ref);
     let vault_authority_signer = Signer::from(&vault_authority_seeds);
-    // Vault must be owned by this program
+    // State account must be owned by this program
     if !vault.is_owned_by(program_id) {
-        return Err(ProgramError::IncorrectProgramId);
+        return Err(VaultError::ConstraintOwner.into());
     }
     let mut vault_state = VaultState::load(vault)?;
+    check_token_account(user_shares, user.key(), &vault_state.share_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_mint_account(share_mint, &vault_state.share_mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(user_underlying, user.key(), &vault_state.underlying_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_underlying, vault_authority.key(), &vault_state.underlying_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    if args.shares == 0u64 {
+        return Err(VaultError::ZeroAmount.into());
+    }
     Burn {
         source: user_shares,
         mint: share_mint,
@@ -419,14 +717,15 @@ fn handle_withdraw(
         amount: args.shares,
         program_id: Some(token_program.key()),
     }.invoke()?;
+    let cse_0 = checked_mul_div(args.shares, vault_state.total_deposits, vault_state.total_shares)?;
     Transfer {
         source: vault_underlying,
         destination: user_underlying,
         authority: vault_authority,
-        amount: checked_mul_div(args.shares, vault_state.total_deposits, vault_state.total_shares)?,
+        amount: cse_0,
         program_id: Some(token_program.key()),
-    }.invoke_signed(&[vault_authority_signer])?;
-    let next_total_deposits = checked_sub(vault_state.total_deposits, checked_mul_div(args.shares, vault_state.total_deposits, vault_state.total_shares)?)?;
+    }.invoke_signed(core::slice::from_ref(&vault_authority_signer))?;
+    let next_total_deposits = checked_sub(vault_state.total_deposits, cse_0)?;
     let next_total_shares = checked_sub(vault_state.total_shares, args.shares)?;
     vault_state.total_deposits = next_total_deposits;
     vault_state.total_shares = next_total_shares;
@@ -434,3 +733,285 @@ fn handle_withdraw(
     Ok(())
 }
 
+
+struct WithdrawUnderlyingArgs {
+    pub amount: u64,
+}
+
+fn decode_withdraw_underlying_args(data: &[u8]) -> Result<WithdrawUnderlyingArgs, ProgramError> {
+    let amount = read_u64(data, 0).ok_or(VaultError::InvalidArgs)?;
+    Ok(WithdrawUnderlyingArgs {
+        amount,
+    })
+}
+
+fn handle_withdraw_underlying(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 8 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_withdraw_underlying_args(data)?;
+    let user = &accounts[0];
+    if !user.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let vault = &accounts[1];
+    if !vault.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
+    let vault_authority = &accounts[2];
+    let (vault_authority_key, vault_authority_bump) = pubkey::find_program_address(&[b"authority", vault.key().as_ref()], program_id);
+    if vault_authority.key() != &vault_authority_key { return Err(VaultError::ConstraintSeeds.into()); }
+    let user_shares = &accounts[3];
+    if !user_shares.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
+    let share_mint = &accounts[4];
+    if !share_mint.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
+    let user_underlying = &accounts[5];
+    if !user_underlying.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
+    let vault_underlying = &accounts[6];
+    if !vault_underlying.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
+    let token_program = &accounts[7];
+    check_program_account(token_program, &[TOKEN_PROGRAM_ID])?;
+    let vault_authority_bump_ref = [vault_authority_bump];
+    let vault_authority_seeds = seeds!(b"authority", vault.key().as_ref(), &vault_authority_bump_ref);
+    let vault_authority_signer = Signer::from(&vault_authority_seeds);
+    // State account must be owned by this program
+    if !vault.is_owned_by(program_id) {
+        return Err(VaultError::ConstraintOwner.into());
+    }
+    let mut vault_state = VaultState::load(vault)?;
+    check_token_account(user_shares, user.key(), &vault_state.share_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_mint_account(share_mint, &vault_state.share_mint, &[TOKEN_PROGRAM_ID])?;
+    check_token_account(user_underlying, user.key(), &vault_state.underlying_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    check_token_account(vault_underlying, vault_authority.key(), &vault_state.underlying_mint, &[TOKEN_PROGRAM_ID], AccountState::Initialized)?;
+    if args.amount == 0u64 {
+        return Err(VaultError::ZeroAmount.into());
+    }
+    let shares_to_burn = checked_mul_div_ceil(args.amount, vault_state.total_shares, vault_state.total_deposits)?;
+    Burn {
+        source: user_shares,
+        mint: share_mint,
+        authority: user,
+        amount: shares_to_burn,
+        program_id: Some(token_program.key()),
+    }.invoke()?;
+    Transfer {
+        source: vault_underlying,
+        destination: user_underlying,
+        authority: vault_authority,
+        amount: args.amount,
+        program_id: Some(token_program.key()),
+    }.invoke_signed(core::slice::from_ref(&vault_authority_signer))?;
+    let next_total_deposits = checked_sub(vault_state.total_deposits, args.amount)?;
+    let next_total_shares = checked_sub(vault_state.total_shares, shares_to_burn)?;
+    vault_state.total_deposits = next_total_deposits;
+    vault_state.total_shares = next_total_shares;
+    VaultState::store(vault, &vault_state)?;
+    Ok(())
+}
+
+
+struct SetAdminArgs {
+    pub new_admin: Pubkey,
+}
+
+fn decode_set_admin_args(data: &[u8]) -> Result<SetAdminArgs, ProgramError> {
+    let new_admin = read_pubkey(data, 0).ok_or(VaultError::InvalidArgs)?;
+    Ok(SetAdminArgs {
+        new_admin,
+    })
+}
+
+fn handle_set_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_set_admin_args(data)?;
+    let admin = &accounts[0];
+    if !admin.is_signer() { return Err(ProgramError::MissingRequiredSignature); }
+    let vault = &accounts[1];
+    if !vault.is_writable() { return Err(VaultError::AccountNotWritable.into()); }
+    
+    // State account must be owned by this program
+    if !vault.is_owned_by(program_id) {
+        return Err(VaultError::ConstraintOwner.into());
+    }
+    let mut vault_state = VaultState::load(vault)?;
+    if *admin.key() != vault_state.admin {
+        return Err(VaultError::ConstraintHasOne.into());
+    }
+    let next_admin = args.new_admin;
+    vault_state.admin = next_admin;
+    VaultState::store(vault, &vault_state)?;
+    Ok(())
+}
+
+
+fn handle_vault_summary(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    if accounts.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let vault = &accounts[0];
+    
+    // State account must be owned by this program
+    if !vault.is_owned_by(program_id) {
+        return Err(VaultError::ConstraintOwner.into());
+    }
+    let vault_state = VaultState::load(vault)?;
+    VaultSummary {
+        underlying_mint: vault_state.underlying_mint,
+        share_mint: vault_state.share_mint,
+        total_deposits: vault_state.total_deposits,
+        total_shares: vault_state.total_shares,
+        exchange_rate: Ratio {
+            numerator: vault_state.total_deposits,
+            denominator: vault_state.total_shares,
+        },
+    }.set_return_data()?;
+    Ok(())
+}
+
+
+struct ShareValueArgs {
+    pub shares: u64,
+}
+
+fn decode_share_value_args(data: &[u8]) -> Result<ShareValueArgs, ProgramError> {
+    let shares = read_u64(data, 0).ok_or(VaultError::InvalidArgs)?;
+    Ok(ShareValueArgs {
+        shares,
+    })
+}
+
+fn handle_share_value(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let args = decode_share_value_args(data)?;
+    let vault = &accounts[0];
+    
+    // State account must be owned by this program
+    if !vault.is_owned_by(program_id) {
+        return Err(VaultError::ConstraintOwner.into());
+    }
+    let vault_state = VaultState::load(vault)?;
+    ShareValue {
+        price_per_share_floor: checked_div(vault_state.total_deposits, vault_state.total_shares)?,
+        price_per_share_nearest: checked_div_round(vault_state.total_deposits, vault_state.total_shares)?,
+        price_per_share_ceil: checked_div_ceil(vault_state.total_deposits, vault_state.total_shares)?,
+        value_floor: checked_mul_div(args.shares, vault_state.total_deposits, vault_state.total_shares)?,
+        value_nearest: checked_mul_div_round(args.shares, vault_state.total_deposits, vault_state.total_shares)?,
+        value_ceil: checked_mul_div_ceil(args.shares, vault_state.total_deposits, vault_state.total_shares)?,
+    }.set_return_data()?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod math_tests {
+    use super::*;
+
+    fn error(error: VaultError) -> ProgramError {
+        error.into()
+    }
+
+    #[test]
+    fn add_checks_every_integer_width() {
+        assert_eq!(checked_add(u8::MAX, 1), Err(error(VaultError::Overflow)));
+        assert_eq!(checked_add(u128::MAX - 1, 1), Ok(u128::MAX));
+        assert_eq!(checked_add(u128::MAX, 1), Err(error(VaultError::Overflow)));
+    }
+
+    #[test]
+    fn sub_checks_every_integer_width() {
+        assert_eq!(checked_sub(0u32, 1), Err(error(VaultError::Overflow)));
+        assert_eq!(checked_sub(0i64, 5), Ok(-5));
+    }
+
+    #[test]
+    fn div_checks_every_integer_width() {
+        assert_eq!(checked_div(7u64, 2), Ok(3));
+        assert_eq!(checked_div(-7i64, 2), Ok(-4));
+        assert_eq!(checked_div(7i64, -2), Ok(-4));
+        assert_eq!(checked_div(-7i64, -2), Ok(3));
+        assert_eq!(checked_div(-6i64, 2), Ok(-3));
+        assert_eq!(checked_div(i64::MIN, -1), Err(error(VaultError::Overflow)));
+        assert_eq!(checked_div(1u16, 0), Err(error(VaultError::DivideByZero)));
+    }
+
+    #[test]
+    fn div_ceil_rounds_up_only_on_remainder() {
+        assert_eq!(checked_div_ceil(7, 2), Ok(4));
+        assert_eq!(checked_div_ceil(6, 2), Ok(3));
+        assert_eq!(checked_div_ceil(u64::MAX, 1), Ok(u64::MAX));
+        assert_eq!(checked_div_ceil(1, 0), Err(error(VaultError::DivideByZero)));
+    }
+
+    #[test]
+    fn div_round_rounds_halves_up() {
+        assert_eq!(checked_div_round(5, 2), Ok(3));
+        assert_eq!(checked_div_round(7, 3), Ok(2));
+        assert_eq!(checked_div_round(8, 3), Ok(3));
+        assert_eq!(checked_div_round(u64::MAX, 2), Ok(1 << 63));
+        assert_eq!(checked_div_round(1, 0), Err(error(VaultError::DivideByZero)));
+    }
+
+    #[test]
+    fn mul_div_uses_wide_intermediates() {
+        assert_eq!(checked_mul_div(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(checked_mul_div(7, 3, 2), Ok(10));
+        assert_eq!(checked_mul_div(u64::MAX, 2, 1), Err(error(VaultError::Overflow)));
+        assert_eq!(checked_mul_div(1, 1, 0), Err(error(VaultError::DivideByZero)));
+    }
+
+    #[test]
+    fn mul_div_ceil_rounds_up_only_on_remainder() {
+        assert_eq!(checked_mul_div_ceil(7, 3, 2), Ok(11));
+        assert_eq!(checked_mul_div_ceil(6, 3, 2), Ok(9));
+        assert_eq!(checked_mul_div_ceil(0, 3, 2), Ok(0));
+        assert_eq!(checked_mul_div_ceil(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(checked_mul_div_ceil(u64::MAX, 3, 2), Err(error(VaultError::Overflow)));
+        assert_eq!(checked_mul_div_ceil(1, 1, 0), Err(error(VaultError::DivideByZero)));
+    }
+
+    #[test]
+    fn mul_div_round_rounds_halves_up() {
+        assert_eq!(checked_mul_div_round(1, 5, 2), Ok(3));
+        assert_eq!(checked_mul_div_round(2, 7, 3), Ok(5));
+        assert_eq!(checked_mul_div_round(1, 4, 3), Ok(1));
+        assert_eq!(checked_mul_div_round(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(checked_mul_div_round(u64::MAX, 3, 2), Err(error(VaultError::Overflow)));
+        assert_eq!(checked_mul_div_round(1, 1, 0), Err(error(VaultError::DivideByZero)));
+    }
+}
+
+#[cfg(test)]
+mod token_layout_tests {
+    use super::*;
+    use pinocchio_tkn::state::MINT_SIZE;
+
+    #[test]
+    fn token_layout_reads_the_token_2022_account_type() {
+        let mut data = [0u8; TOKEN_ACCOUNT_SIZE + 5];
+        assert_eq!(check_token_layout(&data[..TOKEN_ACCOUNT_SIZE], TOKEN_ACCOUNT_SIZE, 2), Ok(()));
+        assert_eq!(check_token_layout(&data[..MINT_SIZE], MINT_SIZE, 1), Ok(()));
+        assert_eq!(check_token_layout(&data[..MINT_SIZE + 1], MINT_SIZE, 1), Err(VaultError::InvalidAccountLayout.into()));
+        data[TOKEN_ACCOUNT_SIZE] = 2;
+        assert_eq!(check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2), Ok(()));
+        assert_eq!(check_token_layout(&data, MINT_SIZE, 1), Err(VaultError::InvalidAccountLayout.into()));
+        data[TOKEN_ACCOUNT_SIZE] = 1;
+        assert_eq!(check_token_layout(&data, MINT_SIZE, 1), Ok(()));
+        assert_eq!(check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2), Err(VaultError::InvalidAccountLayout.into()));
+    }
+}
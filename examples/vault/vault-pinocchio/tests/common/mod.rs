@@ -0,0 +1,125 @@
+// Account builders shared by the Mollusk and LiteSVM tests; each test binary uses a subset.
+#![allow(dead_code)]
+
+use mollusk_svm::Mollusk;
+use mollusk_svm_programs_token::token;
+use solana_program_option::COption;
+use solana_program_pack::Pack;
+use solana_sdk::{account::Account, program_error::ProgramError, pubkey::Pubkey};
+use solana_sdk_ids::{bpf_loader_upgradeable, system_program};
+use spl_token_interface::state::{Account as TokenAccount, AccountState, Mint};
+use spl_token_interface::ID as TOKEN_PROGRAM_ID;
+use vault_pinocchio::{VaultError, VaultState};
+
+/// Mollusk with the SPL Token program and the vault program loaded under `program_id`.
+pub fn vault_mollusk(program_id: &Pubkey) -> Mollusk {
+    let mut mollusk = Mollusk::default();
+    token::add_program(&mut mollusk);
+    let program_path = format!(
+        "{}/target/deploy/vault_pinocchio",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    mollusk.add_program(program_id, &program_path);
+    mollusk
+}
+
+pub fn vault_error(error: VaultError) -> ProgramError {
+    ProgramError::Custom(error as u32)
+}
+
+pub fn system_account(lamports: u64) -> Account {
+    Account {
+        lamports,
+        data: vec![],
+        owner: system_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// A data-less account owned by `program_id`, such as a PDA signing authority.
+pub fn program_account(program_id: Pubkey) -> Account {
+    Account {
+        lamports: 1,
+        data: vec![],
+        owner: program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// A stand-in for an executable program account, such as the token program.
+pub fn executable_account() -> Account {
+    Account {
+        lamports: 1,
+        data: vec![],
+        owner: bpf_loader_upgradeable::id(),
+        executable: true,
+        rent_epoch: 0,
+    }
+}
+
+pub fn vault_state_account(program_id: Pubkey, state: &VaultState) -> Account {
+    let mut data = vec![0u8; VaultState::LEN];
+    state.pack(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner: program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn vault_state(account: &Account) -> VaultState {
+    VaultState::unpack(&account.data).unwrap()
+}
+
+pub fn mint_account(mint_authority: Pubkey, supply: u64, decimals: u8) -> Account {
+    let mint = Mint {
+        mint_authority: COption::Some(mint_authority),
+        supply,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    let mut data = vec![0u8; Mint::LEN];
+    Mint::pack(mint, &mut data).unwrap();
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner: TOKEN_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn token_account(owner: Pubkey, mint: Pubkey, amount: u64) -> Account {
+    token_account_in_state(owner, mint, amount, AccountState::Initialized)
+}
+
+pub fn token_account_in_state(owner: Pubkey, mint: Pubkey, amount: u64, state: AccountState) -> Account {
+    let token = TokenAccount {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    let mut data = vec![0u8; TokenAccount::LEN];
+    TokenAccount::pack(token, &mut data).unwrap();
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner: TOKEN_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn token_amount(account: &Account) -> u64 {
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
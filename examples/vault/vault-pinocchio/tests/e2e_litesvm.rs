@@ -1,37 +1,25 @@
+mod common;
+
+use common::vault_state;
 use litesvm::LiteSVM;
 use solana_keypair::Keypair;
 use solana_program_pack::Pack;
 use solana_rent::Rent;
 use solana_signer::Signer;
 use solana_sdk::{
-    instruction::{AccountMeta, Instruction},
+    instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
 };
 use solana_system_interface::instruction::create_account;
 use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
 use solana_sdk_ids::system_program;
 use spl_token_interface::{
   instruction as token_instruction,
   state::{Account as TokenAccount, Mint},
   ID as TOKEN_PROGRAM_ID,
 };
-
-#[derive(Debug)]
-struct VaultState {
-    total_deposits: u64,
-    total_shares: u64,
-}
-
-fn decode_vault_state(data: &[u8]) -> VaultState {
-    let mut total_deposits_bytes = [0u8; 8];
-    total_deposits_bytes.copy_from_slice(&data[96..104]);
-    let mut total_shares_bytes = [0u8; 8];
-    total_shares_bytes.copy_from_slice(&data[104..112]);
-    VaultState {
-        total_deposits: u64::from_le_bytes(total_deposits_bytes),
-        total_shares: u64::from_le_bytes(total_shares_bytes),
-    }
-}
+use vault_pinocchio::VaultError;
 
 fn encode_create_vault(underlying_mint: Pubkey, share_mint: Pubkey) -> Vec<u8> {
     let mut data = vec![0u8];
@@ -237,7 +225,7 @@ fn e2e_create_deposit_withdraw() {
     };
 
     let tx = Transaction::new_signed_with_payer(
-        &[create_vault_ix, deposit_ix, withdraw_ix],
+        &[create_vault_ix.clone(), deposit_ix, withdraw_ix],
         Some(&payer_pubkey),
         &[&payer],
         svm.latest_blockhash(),
@@ -245,7 +233,7 @@ fn e2e_create_deposit_withdraw() {
     svm.send_transaction(tx).unwrap();
 
     let vault_account = svm.get_account(&vault).unwrap();
-    let state = decode_vault_state(&vault_account.data);
+    let state = vault_state(&vault_account);
     assert_eq!(state.total_deposits, 0);
     assert_eq!(state.total_shares, 0);
 
@@ -256,4 +244,19 @@ fn e2e_create_deposit_withdraw() {
 
     assert_eq!(user_underlying_state.amount, 1_000_000);
     assert_eq!(user_shares_state.amount, 0);
+
+    let reinit_tx = Transaction::new_signed_with_payer(
+        &[create_vault_ix],
+        Some(&payer_pubkey),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    let err = svm.send_transaction(reinit_tx).unwrap_err().err;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::AccountAlreadyInitialized as u32),
+        )
+    );
 }
@@ -0,0 +1,79 @@
+mod common;
+
+use common::{system_account, vault_error, vault_mollusk, vault_state, vault_state_account};
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use vault_pinocchio::{VaultError, VaultState};
+
+#[test]
+fn set_admin_replaces_admin() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let new_admin = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    let state = VaultState {
+        admin: admin.to_bytes(),
+        ..VaultState::default()
+    };
+    let accounts = vec![
+        (admin, system_account(1_000_000)),
+        (vault, vault_state_account(program_id, &state)),
+    ];
+
+    let mut data = vec![4u8];
+    data.extend_from_slice(new_admin.as_ref());
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new(vault, false),
+        ],
+        data,
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+
+    assert_eq!(vault_state(result.get_account(&vault).unwrap()).admin, new_admin.to_bytes());
+}
+
+#[test]
+fn set_admin_rejects_other_signer() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let signer = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    let state = VaultState {
+        admin: admin.to_bytes(),
+        ..VaultState::default()
+    };
+    let accounts = vec![
+        (signer, system_account(1_000_000)),
+        (vault, vault_state_account(program_id, &state)),
+    ];
+
+    let mut data = vec![4u8];
+    data.extend_from_slice(Pubkey::new_unique().as_ref());
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(signer, true),
+            AccountMeta::new(vault, false),
+        ],
+        data,
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(vault_error(VaultError::ConstraintHasOne))],
+    );
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use common::{vault_mollusk, vault_state_account};
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use vault_pinocchio::{ShareValue, VaultState};
+
+fn share_value_data(shares: u64) -> Vec<u8> {
+    let mut data = vec![6u8];
+    data.extend_from_slice(&shares.to_le_bytes());
+    data
+}
+
+#[test]
+fn share_value_rounds_an_exact_half_up() {
+    let program_id = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    // 2_500 / 1_000 = 2.5 per share, and 3 shares are worth 7.5.
+    let state = VaultState {
+        total_deposits: 2_500,
+        total_shares: 1_000,
+        ..VaultState::default()
+    };
+    let accounts = vec![(vault, vault_state_account(program_id, &state))];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new_readonly(vault, false)],
+        data: share_value_data(3),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    assert_eq!(
+        ShareValue::decode(&result.return_data),
+        Some(ShareValue {
+            price_per_share_floor: 2,
+            price_per_share_nearest: 3,
+            price_per_share_ceil: 3,
+            value_floor: 7,
+            value_nearest: 8,
+            value_ceil: 8,
+        })
+    );
+}
+
+#[test]
+fn share_value_rounds_just_below_a_half_down() {
+    let program_id = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    // 2_499 / 1_000 = 2.499 per share, and 3 shares are worth 7.497.
+    let state = VaultState {
+        total_deposits: 2_499,
+        total_shares: 1_000,
+        ..VaultState::default()
+    };
+    let accounts = vec![(vault, vault_state_account(program_id, &state))];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new_readonly(vault, false)],
+        data: share_value_data(3),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    assert_eq!(
+        ShareValue::decode(&result.return_data),
+        Some(ShareValue {
+            price_per_share_floor: 2,
+            price_per_share_nearest: 2,
+            price_per_share_ceil: 3,
+            value_floor: 7,
+            value_nearest: 7,
+            value_ceil: 8,
+        })
+    );
+}
+
+#[test]
+fn share_value_rounds_just_above_a_half_up() {
+    let program_id = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    // 2_501 / 1_000 = 2.501 per share, and 3 shares are worth 7.503.
+    let state = VaultState {
+        total_deposits: 2_501,
+        total_shares: 1_000,
+        ..VaultState::default()
+    };
+    let accounts = vec![(vault, vault_state_account(program_id, &state))];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new_readonly(vault, false)],
+        data: share_value_data(3),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    assert_eq!(
+        ShareValue::decode(&result.return_data),
+        Some(ShareValue {
+            price_per_share_floor: 2,
+            price_per_share_nearest: 3,
+            price_per_share_ceil: 3,
+            value_floor: 7,
+            value_nearest: 8,
+            value_ceil: 8,
+        })
+    );
+}
+
+#[test]
+fn share_value_splits_floor_and_ceil_on_a_remainder_of_one() {
+    let program_id = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    // 2_001 = 2 * 1_000 + 1: floor and nearest drop the remainder, ceil rounds it up.
+    let state = VaultState {
+        total_deposits: 2_001,
+        total_shares: 1_000,
+        ..VaultState::default()
+    };
+    let accounts = vec![(vault, vault_state_account(program_id, &state))];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new_readonly(vault, false)],
+        data: share_value_data(1),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+    assert_eq!(
+        ShareValue::decode(&result.return_data),
+        Some(ShareValue {
+            price_per_share_floor: 2,
+            price_per_share_nearest: 2,
+            price_per_share_ceil: 3,
+            value_floor: 2,
+            value_nearest: 2,
+            value_ceil: 3,
+        })
+    );
+}
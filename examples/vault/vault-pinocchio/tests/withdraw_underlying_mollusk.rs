@@ -0,0 +1,193 @@
+mod common;
+
+use common::{
+    executable_account, mint_account, program_account, system_account, token_account, token_amount, vault_mollusk,
+    vault_state, vault_state_account,
+};
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token_interface::error::TokenError;
+use spl_token_interface::ID as TOKEN_PROGRAM_ID;
+use vault_pinocchio::VaultState;
+
+fn withdraw_underlying_data(amount: u64) -> Vec<u8> {
+    let mut data = vec![3u8];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[test]
+fn withdraw_underlying_burns_exact_shares_without_remainder() {
+    let program_id = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let underlying_mint = Pubkey::new_unique();
+    let share_mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let (vault_authority, _vault_bump) =
+        Pubkey::find_program_address(&[b"authority", vault.as_ref()], &program_id);
+    let user_shares = Pubkey::new_unique();
+    let user_underlying = Pubkey::new_unique();
+    let vault_underlying = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    // 3 underlying for 2 shares: 3 * 2 / 3 = 2 shares, no rounding.
+    let state = VaultState {
+        admin: user.to_bytes(),
+        underlying_mint: underlying_mint.to_bytes(),
+        share_mint: share_mint.to_bytes(),
+        total_deposits: 3,
+        total_shares: 2,
+        bump: 0,
+    };
+    let accounts = vec![
+        (user, system_account(1_000_000)),
+        (vault, vault_state_account(program_id, &state)),
+        (vault_authority, program_account(program_id)),
+        (user_shares, token_account(user, share_mint, 2)),
+        (share_mint, mint_account(vault_authority, 2, 6)),
+        (user_underlying, token_account(user, underlying_mint, 0)),
+        (vault_underlying, token_account(vault_authority, underlying_mint, 3)),
+        (TOKEN_PROGRAM_ID, executable_account()),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(user, true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(vault_authority, false),
+            AccountMeta::new(user_shares, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new(user_underlying, false),
+            AccountMeta::new(vault_underlying, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data: withdraw_underlying_data(3),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+
+    let state = vault_state(result.get_account(&vault).unwrap());
+    assert_eq!(state.total_deposits, 0);
+    assert_eq!(state.total_shares, 0);
+    assert_eq!(token_amount(result.get_account(&user_shares).unwrap()), 0);
+    assert_eq!(token_amount(result.get_account(&user_underlying).unwrap()), 3);
+}
+
+#[test]
+fn withdraw_underlying_rounds_shares_up() {
+    let program_id = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let underlying_mint = Pubkey::new_unique();
+    let share_mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let (vault_authority, _vault_bump) =
+        Pubkey::find_program_address(&[b"authority", vault.as_ref()], &program_id);
+    let user_shares = Pubkey::new_unique();
+    let user_underlying = Pubkey::new_unique();
+    let vault_underlying = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    // 1 * 2 / 3 = 0.67 shares: floor would burn nothing, ceil burns 1.
+    let state = VaultState {
+        admin: user.to_bytes(),
+        underlying_mint: underlying_mint.to_bytes(),
+        share_mint: share_mint.to_bytes(),
+        total_deposits: 3,
+        total_shares: 2,
+        bump: 0,
+    };
+    let accounts = vec![
+        (user, system_account(1_000_000)),
+        (vault, vault_state_account(program_id, &state)),
+        (vault_authority, program_account(program_id)),
+        (user_shares, token_account(user, share_mint, 2)),
+        (share_mint, mint_account(vault_authority, 2, 6)),
+        (user_underlying, token_account(user, underlying_mint, 0)),
+        (vault_underlying, token_account(vault_authority, underlying_mint, 3)),
+        (TOKEN_PROGRAM_ID, executable_account()),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(user, true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(vault_authority, false),
+            AccountMeta::new(user_shares, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new(user_underlying, false),
+            AccountMeta::new(vault_underlying, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data: withdraw_underlying_data(1),
+    };
+
+    let result = mollusk.process_and_validate_instruction(&ix, &accounts, &[Check::success()]);
+
+    let state = vault_state(result.get_account(&vault).unwrap());
+    assert_eq!(state.total_deposits, 2);
+    assert_eq!(state.total_shares, 1);
+    assert_eq!(token_amount(result.get_account(&user_shares).unwrap()), 1);
+    assert_eq!(token_amount(result.get_account(&user_underlying).unwrap()), 1);
+}
+
+#[test]
+fn withdraw_underlying_rejects_withdrawal_without_shares() {
+    let program_id = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let underlying_mint = Pubkey::new_unique();
+    let share_mint = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let (vault_authority, _vault_bump) =
+        Pubkey::find_program_address(&[b"authority", vault.as_ref()], &program_id);
+    let user_shares = Pubkey::new_unique();
+    let user_underlying = Pubkey::new_unique();
+    let vault_underlying = Pubkey::new_unique();
+    let mollusk = vault_mollusk(&program_id);
+
+    // A user holding no shares cannot take out a sub-share amount.
+    let state = VaultState {
+        admin: user.to_bytes(),
+        underlying_mint: underlying_mint.to_bytes(),
+        share_mint: share_mint.to_bytes(),
+        total_deposits: 3,
+        total_shares: 2,
+        bump: 0,
+    };
+    let accounts = vec![
+        (user, system_account(1_000_000)),
+        (vault, vault_state_account(program_id, &state)),
+        (vault_authority, program_account(program_id)),
+        (user_shares, token_account(user, share_mint, 0)),
+        (share_mint, mint_account(vault_authority, 2, 6)),
+        (user_underlying, token_account(user, underlying_mint, 0)),
+        (vault_underlying, token_account(vault_authority, underlying_mint, 3)),
+        (TOKEN_PROGRAM_ID, executable_account()),
+    ];
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(user, true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(vault_authority, false),
+            AccountMeta::new(user_shares, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new(user_underlying, false),
+            AccountMeta::new(vault_underlying, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data: withdraw_underlying_data(1),
+    };
+
+    mollusk.process_and_validate_instruction(
+        &ix,
+        &accounts,
+        &[Check::err(ProgramError::Custom(TokenError::InsufficientFunds as u32))],
+    );
+}
@@ -5,6 +5,8 @@ import {
   arg,
   ata,
   bump,
+  compute,
+  errors,
   expr,
   field,
   mint,
@@ -12,6 +14,8 @@ import {
   program,
   programAccount,
   pubkey,
+  ratio,
+  require,
   state,
   token,
   tx,
@@ -25,6 +29,10 @@ export const Vault = program({
   programId: 'GTcXWNZ8Ytmkcgzfr3V1R9X3tHxo7hC49DUh7ggMzvCV',
 });
 
+Vault.errors = errors({
+  ZeroAmount: 'Amount must be greater than zero',
+});
+
 Vault.accounts.vault = account({
   name: 'VaultState',
   schema: {
@@ -58,7 +66,7 @@ Vault.createVault = tx({
     ata('vaultUnderlying', accountRef('vaultAuthority'), arg('underlyingMint'), {
       writable: true,
     }),
-    programAccount('tokenProgram'),
+    programAccount('tokenProgram', { address: 'token' }),
   ],
   ops: [
     state.init('vault', {
@@ -72,14 +80,7 @@ Vault.createVault = tx({
   ],
 });
 
-const sharesToMint = expr.if(
-  expr.eq(field('vault', 'totalShares'), expr.const(0)),
-  expr.arg('amount'),
-  expr.div(
-    expr.mul(expr.arg('amount'), field('vault', 'totalShares')),
-    field('vault', 'totalDeposits'),
-  ),
-);
+const sharesToMint = expr.local('sharesToMint');
 
 Vault.deposit = tx({
   name: 'deposit',
@@ -105,9 +106,22 @@ Vault.deposit = tx({
     ata('userShares', accountRef('user'), field('vault', 'shareMint'), {
       writable: true,
     }),
-    programAccount('tokenProgram'),
+    programAccount('tokenProgram', { address: 'token' }),
   ],
   ops: [
+    require(expr.gt(expr.arg('amount'), expr.const(0)), 'ZeroAmount'),
+    compute(
+      'sharesToMint',
+      expr.if(
+        expr.eq(field('vault', 'totalShares'), expr.const(0)),
+        expr.arg('amount'),
+        expr.div(
+          expr.mul(expr.arg('amount'), field('vault', 'totalShares')),
+          field('vault', 'totalDeposits'),
+          { rounding: 'floor' },
+        ),
+      ),
+    ),
     token.transfer({
       from: 'userUnderlying',
       to: 'vaultUnderlying',
@@ -159,9 +173,10 @@ Vault.withdraw = tx({
       field('vault', 'underlyingMint'),
       { writable: true },
     ),
-    programAccount('tokenProgram'),
+    programAccount('tokenProgram', { address: 'token' }),
   ],
   ops: [
+    require(expr.gt(expr.arg('shares'), expr.const(0)), 'ZeroAmount'),
     token.burn({
       mint: 'shareMint',
       from: 'userShares',
@@ -184,6 +199,85 @@ Vault.withdraw = tx({
   ],
 });
 
+// Burns enough shares to cover `amount`, rounding up so a withdrawal never costs fewer
+// shares than the underlying it takes out.
+const sharesToBurn = expr.local('sharesToBurn');
+
+Vault.withdrawUnderlying = tx({
+  name: 'withdrawUnderlying',
+  args: {
+    amount: u64(),
+  },
+  accounts: [
+    accountMeta('user', { signer: true }),
+    accountMeta('vault', { writable: true }),
+    accountMeta('vaultAuthority', {
+      pda: pda(['authority', accountRef('vault')]),
+    }),
+    ata('userShares', accountRef('user'), field('vault', 'shareMint'), {
+      writable: true,
+    }),
+    mint('shareMint', field('vault', 'shareMint'), { writable: true }),
+    ata('userUnderlying', accountRef('user'), field('vault', 'underlyingMint'), {
+      writable: true,
+    }),
+    ata(
+      'vaultUnderlying',
+      accountRef('vaultAuthority'),
+      field('vault', 'underlyingMint'),
+      { writable: true },
+    ),
+    programAccount('tokenProgram', { address: 'token' }),
+  ],
+  ops: [
+    require(expr.gt(expr.arg('amount'), expr.const(0)), 'ZeroAmount'),
+    compute(
+      'sharesToBurn',
+      expr.mulDiv(
+        expr.arg('amount'),
+        field('vault', 'totalShares'),
+        field('vault', 'totalDeposits'),
+        { rounding: 'ceil' },
+      ),
+    ),
+    token.burn({
+      mint: 'shareMint',
+      from: 'userShares',
+      authority: 'user',
+      amount: sharesToBurn,
+      program: 'tokenProgram',
+    }),
+    token.transfer({
+      from: 'vaultUnderlying',
+      to: 'userUnderlying',
+      authority: 'vaultAuthority',
+      signer: 'vaultAuthority',
+      amount: expr.arg('amount'),
+      program: 'tokenProgram',
+    }),
+    state.update('vault', {
+      totalDeposits: expr.sub(field('vault', 'totalDeposits'), expr.arg('amount')),
+      totalShares: expr.sub(field('vault', 'totalShares'), sharesToBurn),
+    }),
+  ],
+});
+
+Vault.setAdmin = tx({
+  name: 'setAdmin',
+  args: {
+    newAdmin: pubkey(),
+  },
+  accounts: [
+    accountMeta('admin', { signer: true }),
+    accountMeta('vault', { writable: true, hasOne: ['admin'] }),
+  ],
+  ops: [
+    state.update('vault', {
+      admin: expr.arg('newAdmin'),
+    }),
+  ],
+});
+
 Vault.vaultSummary = view({
   name: 'vaultSummary',
   args: {
@@ -194,6 +288,27 @@ Vault.vaultSummary = view({
     shareMint: pubkey(),
     totalDeposits: u64(),
     totalShares: u64(),
-    exchangeRate: 'ratio',
+    exchangeRate: ratio(field('vault', 'totalDeposits'), field('vault', 'totalShares')),
+  },
+});
+
+// What `shares` are worth in underlying under each rounding mode. `withdraw` pays out the
+// floor value; `nearest` rounds halves up.
+const totalDeposits = field('vault', 'totalDeposits');
+const totalShares = field('vault', 'totalShares');
+
+Vault.shareValue = view({
+  name: 'shareValue',
+  args: {
+    vault: pubkey(),
+    shares: u64(),
+  },
+  returns: {
+    pricePerShareFloor: expr.div(totalDeposits, totalShares),
+    pricePerShareNearest: expr.div(totalDeposits, totalShares, { rounding: 'nearest' }),
+    pricePerShareCeil: expr.div(totalDeposits, totalShares, { rounding: 'ceil' }),
+    valueFloor: expr.mulDiv(expr.arg('shares'), totalDeposits, totalShares),
+    valueNearest: expr.mulDiv(expr.arg('shares'), totalDeposits, totalShares, { rounding: 'nearest' }),
+    valueCeil: expr.mulDiv(expr.arg('shares'), totalDeposits, totalShares, { rounding: 'ceil' }),
   },
 });
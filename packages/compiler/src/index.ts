@@ -1,15 +1,87 @@
+import { createHash } from 'node:crypto';
 import { promises as fs } from 'node:fs';
 import path from 'node:path';
 import { pathToFileURL } from 'node:url';
-import type { IxDef, ProgramDef, ViewDef } from '@solana-ts-transpiler/sdk';
+import type { AccountDef, ErrorsDef, EventsDef, IxDef, ProgramDef, TokenProgram, ViewDef } from '@solana-ts-transpiler/sdk';
 
+export type IrAccount = AccountDef & { discriminator: number[] };
 export type IrInstruction = IxDef & { discriminator: number };
+// Views are dispatched like instructions, numbered after them.
+export type IrView = ViewDef & { discriminator: number };
+export type IrError = { name: string; code: number; message: string };
+export type IrEvent = { name: string; fields: EventsDef['events'][string]; discriminator: number[] };
 export type IrProgram = {
   name: string;
   programId: string;
-  accounts: ProgramDef['accounts'];
+  tokenProgram: TokenProgram;
+  accounts: Record<string, IrAccount>;
   instructions: IrInstruction[];
-  views: ViewDef[];
+  views: IrView[];
+  errors: IrError[];
+  events: IrEvent[];
+};
+
+// Raised by generated checks and helpers; every generator renders this same table.
+export const builtinErrors: IrError[] = [
+  { name: 'Overflow', code: 1000, message: 'Arithmetic overflow' },
+  { name: 'DivideByZero', code: 1001, message: 'Division by zero' },
+  { name: 'UnknownInstruction', code: 1002, message: 'Unknown instruction discriminator' },
+  { name: 'AccountNotWritable', code: 1003, message: 'Account must be writable' },
+  { name: 'ConstraintSeeds', code: 1004, message: 'Account does not match its PDA seeds' },
+  { name: 'ConstraintOwner', code: 1005, message: 'Account is not owned by the expected program' },
+  { name: 'ConstraintTokenOwner', code: 1006, message: 'Token account owner does not match' },
+  { name: 'ConstraintMint', code: 1007, message: 'Token account mint does not match' },
+  { name: 'ConstraintAddress', code: 1008, message: 'Account address does not match' },
+  { name: 'ConstraintProgram', code: 1009, message: 'Program account does not match the expected program' },
+  { name: 'ProgramNotExecutable', code: 1010, message: 'Program account is not executable' },
+  { name: 'AccountDiscriminatorMismatch', code: 1011, message: 'Account discriminator does not match' },
+  { name: 'AccountAlreadyInitialized', code: 1012, message: 'Account is already initialized' },
+  { name: 'DuplicateMutableAccount', code: 1013, message: 'The same account was passed twice as mutable state' },
+  { name: 'ConstraintHasOne', code: 1014, message: 'An account does not match the key it must equal' },
+  { name: 'IndexOutOfBounds', code: 1015, message: 'A vec argument index is out of bounds' },
+  { name: 'ConstraintTokenState', code: 1016, message: 'Token account is frozen, or not frozen for a thaw' },
+  { name: 'InvalidArgs', code: 1017, message: 'Instruction data does not decode as the instruction args' },
+  { name: 'InvalidAccountLayout', code: 1018, message: 'Account data does not decode as its layout' },
+  { name: 'ConstraintEquals', code: 1019, message: 'Account does not match its equals constraint' },
+];
+
+const userErrorCodeStart = 6000;
+
+const buildErrors = (defs: ErrorsDef[]): IrError[] => {
+  const seen = new Set(builtinErrors.map((error) => error.name));
+  const userErrors = defs
+    .flatMap((def) => Object.entries(def.errors))
+    .map(([name, message], index) => {
+      if (!/^[A-Z][A-Za-z0-9]*$/.test(name)) {
+        throw new Error(`Error name ${name} must be PascalCase.`);
+      }
+      if (seen.has(name)) {
+        throw new Error(`Duplicate error name ${name}.`);
+      }
+      seen.add(name);
+      return { name, code: userErrorCodeStart + index, message };
+    });
+  return [...builtinErrors, ...userErrors];
+};
+
+export const anchorEventDiscriminator = (name: string): number[] => [
+  ...createHash('sha256').update(`event:${name}`).digest().subarray(0, 8),
+];
+
+const buildEvents = (defs: EventsDef[]): IrEvent[] => {
+  const seen = new Set<string>();
+  return defs
+    .flatMap((def) => Object.entries(def.events))
+    .map(([name, fields]) => {
+      if (!/^[A-Z][A-Za-z0-9]*$/.test(name)) {
+        throw new Error(`Event name ${name} must be PascalCase.`);
+      }
+      if (seen.has(name)) {
+        throw new Error(`Duplicate event name ${name}.`);
+      }
+      seen.add(name);
+      return { name, fields, discriminator: anchorEventDiscriminator(name) };
+    });
 };
 
 export const loadProgramFromModule = async (inputPath: string): Promise<ProgramDef> => {
@@ -28,9 +100,63 @@ export const loadProgramFromModule = async (inputPath: string): Promise<ProgramD
   return program;
 };
 
+export const anchorAccountDiscriminator = (name: string): number[] => [
+  ...createHash('sha256').update(`account:${name}`).digest().subarray(0, 8),
+];
+
+// The default tag comes from the account name, so reordering `accounts` never changes it.
+const resolveAccountDiscriminator = (key: string, account: AccountDef): number[] => {
+  const discriminator = account.discriminator ?? anchorAccountDiscriminator(account.name)[0];
+  if (discriminator === 'anchor') return anchorAccountDiscriminator(account.name);
+  const bytes = typeof discriminator === 'number' ? [discriminator] : discriminator;
+  if (bytes.length === 0 || bytes.length > 8) {
+    throw new Error(`Account ${key} discriminator must be 1 to 8 bytes.`);
+  }
+  if (bytes.some((byte) => !Number.isInteger(byte) || byte < 0 || byte > 255)) {
+    throw new Error(`Account ${key} discriminator bytes must be integers in 0..=255.`);
+  }
+  // All-zero bytes are indistinguishable from a freshly allocated account.
+  if (bytes.every((byte) => byte === 0)) {
+    if (account.discriminator === undefined) {
+      throw new Error(`Account ${key} default discriminator is zero; set discriminator explicitly.`);
+    }
+    throw new Error(`Account ${key} discriminator cannot be all zeros.`);
+  }
+  return bytes;
+};
+
+const tokenPrograms: TokenProgram[] = ['token', 'token2022', 'either'];
+
+const resolveTokenProgram = (program: ProgramDef): TokenProgram => {
+  const tokenProgram = program.tokenProgram ?? 'token';
+  if (!tokenPrograms.includes(tokenProgram)) {
+    throw new Error(`tokenProgram must be one of ${tokenPrograms.join(', ')}, got ${tokenProgram}.`);
+  }
+  return tokenProgram;
+};
+
+const buildAccounts = (accounts: ProgramDef['accounts']): Record<string, IrAccount> => {
+  const resolved = Object.entries(accounts).map(([key, account]) => ({
+    key,
+    account: { ...account, discriminator: resolveAccountDiscriminator(key, account) },
+  }));
+  resolved.forEach(({ key, account }, index) => {
+    resolved.slice(index + 1).forEach((other) => {
+      const shared = Math.min(account.discriminator.length, other.account.discriminator.length);
+      const prefix = account.discriminator.slice(0, shared);
+      if (prefix.every((byte, i) => byte === other.account.discriminator[i])) {
+        throw new Error(`Accounts ${key} and ${other.key} have overlapping discriminators.`);
+      }
+    });
+  });
+  return Object.fromEntries(resolved.map(({ key, account }) => [key, account]));
+};
+
 export const buildIr = (program: ProgramDef): IrProgram => {
   const instructions: IrInstruction[] = [];
-  const views: ViewDef[] = [];
+  const views: IrView[] = [];
+  const errorDefs: ErrorsDef[] = [];
+  const eventDefs: EventsDef[] = [];
 
   const withInitAccounts = (ix: IxDef): IxDef => {
     const needsSystemProgram = ix.ops.some((op) => op.op === 'state.init');
@@ -44,6 +170,7 @@ export const buildIr = (program: ProgramDef): IrProgram => {
         {
           name: 'systemProgram',
           kind: 'program',
+          address: 'system',
         },
       ],
     };
@@ -58,7 +185,13 @@ export const buildIr = (program: ProgramDef): IrProgram => {
       }
       if ((value as ViewDef).__kind === 'view') {
         const v = value as ViewDef;
-        views.push({ ...v, name: v.name ?? key });
+        views.push({ ...v, name: v.name ?? key, discriminator: 0 });
+      }
+      if ((value as ErrorsDef).__kind === 'errors') {
+        errorDefs.push(value as ErrorsDef);
+      }
+      if ((value as EventsDef).__kind === 'events') {
+        eventDefs.push(value as EventsDef);
       }
     }
   }
@@ -66,13 +199,19 @@ export const buildIr = (program: ProgramDef): IrProgram => {
   instructions.forEach((ix, index) => {
     ix.discriminator = index;
   });
+  views.forEach((v, index) => {
+    v.discriminator = instructions.length + index;
+  });
 
   return {
     name: program.name,
     programId: program.programId,
-    accounts: program.accounts,
+    tokenProgram: resolveTokenProgram(program),
+    accounts: buildAccounts(program.accounts),
     instructions,
     views,
+    errors: buildErrors(errorDefs),
+    events: buildEvents(eventDefs),
   };
 };
 
@@ -5,24 +5,38 @@ import {
   accountNode,
   accountValueNode,
   argumentValueNode,
+  arrayTypeNode,
+  booleanTypeNode,
+  bytesTypeNode,
+  bytesValueNode,
   camelCase,
   constantDiscriminatorNode,
   constantPdaSeedNode,
   constantValueNode,
   createFromRoot,
+  enumEmptyVariantTypeNode,
+  enumTypeNode,
+  errorNode,
+  fieldDiscriminatorNode,
+  fixedCountNode,
+  fixedSizeTypeNode,
   instructionAccountNode,
   instructionArgumentNode,
   instructionNode,
   numberTypeNode,
   numberValueNode,
+  optionTypeNode,
   pascalCase,
   pdaLinkNode,
   pdaNode,
   pdaSeedValueNode,
   pdaValueNode,
+  prefixedCountNode,
   programNode,
   publicKeyTypeNode,
+  publicKeyValueNode,
   rootNode,
+  sizePrefixTypeNode,
   snakeCase,
   stringTypeNode,
   stringValueNode,
@@ -30,6 +44,7 @@ import {
   structTypeNode,
   variablePdaSeedNode,
 } from 'codama';
+import type { TypeNode } from 'codama';
 
 const [,, irPath, outDir] = process.argv;
 
@@ -38,11 +53,25 @@ if (!irPath || !outDir) {
 }
 
 type IrType = { kind: string; [key: string]: unknown };
-type IrAccount = { name: string; schema: Record<string, IrType>; pda?: { seeds: Array<any> } };
+type IrAccount = {
+  name: string;
+  schema: Record<string, IrType>;
+  pda?: { seeds: Array<any> };
+  discriminator: number[];
+};
 type IrInstruction = {
   name: string;
   args: Record<string, IrType>;
-  accounts: Array<{ name: string; signer?: boolean; writable?: boolean; pda?: { seeds: Array<any> } }>;
+  accounts: Array<{
+    name: string;
+    signer?: boolean;
+    writable?: boolean;
+    kind?: string;
+    address?: unknown;
+    pda?: { seeds: Array<any> };
+    tokenProgram?: string;
+  }>;
+  ops: Array<{ op: string; program?: string }>;
   discriminator: number;
 };
 
@@ -50,21 +79,48 @@ const irRaw = await fs.readFile(irPath, 'utf8');
 const ir = JSON.parse(irRaw) as {
   name: string;
   programId: string;
+  tokenProgram?: string;
   accounts: Record<string, IrAccount>;
   instructions: IrInstruction[];
+  errors?: Array<{ name: string; code: number; message: string }>;
 };
 
 const programName = ir.name;
 const programId = ir.programId;
 const accounts = ir.accounts ?? {};
 const instructions = ir.instructions ?? [];
+const programErrors = ir.errors ?? [];
 
-const toTypeNode = (type: IrType) => {
+const toTypeNode = (type: IrType): TypeNode => {
   switch (type.kind) {
-    case 'u64':
-      return numberTypeNode('u64');
+    case 'array':
+      return arrayTypeNode(toTypeNode(type.of as IrType), fixedCountNode(type.len as number));
+    case 'vec':
+      return arrayTypeNode(toTypeNode(type.of as IrType), prefixedCountNode(numberTypeNode('u32')));
+    case 'enum':
+      return enumTypeNode((type.variants as string[]).map((variant) => enumEmptyVariantTypeNode(variant)));
+    case 'option':
+      // Matches the program's fixed layout: None still takes the value's bytes.
+      return optionTypeNode(toTypeNode(type.of as IrType), { fixed: true });
+    case 'bytes':
+      return sizePrefixTypeNode(bytesTypeNode(), numberTypeNode('u32'));
+    case 'string':
+      return sizePrefixTypeNode(stringTypeNode('utf8'), numberTypeNode('u32'));
+    case 'struct':
+      return structTypeNode(
+        Object.entries(type.fields as Record<string, IrType>).map(([name, fieldType]) =>
+          structFieldTypeNode({ name, type: toTypeNode(fieldType) }),
+        ),
+      );
     case 'u8':
-      return numberTypeNode('u8');
+    case 'u16':
+    case 'u32':
+    case 'u64':
+    case 'u128':
+    case 'i64':
+      return numberTypeNode(type.kind);
+    case 'bool':
+      return booleanTypeNode();
     case 'pubkey':
       return publicKeyTypeNode();
     default:
@@ -72,12 +128,28 @@ const toTypeNode = (type: IrType) => {
   }
 };
 
-const typeSize = (type: IrType) => {
+const typeSize = (type: IrType): number => {
   switch (type.kind) {
+    case 'array':
+      return (type.len as number) * typeSize(type.of as IrType);
+    case 'struct':
+      return Object.values(type.fields as Record<string, IrType>).reduce((sum, fieldType) => sum + typeSize(fieldType), 0);
+    case 'option':
+      return 1 + typeSize(type.of as IrType);
+    case 'enum':
+      return 1;
     case 'u64':
+    case 'i64':
       return 8;
     case 'u8':
+    case 'bool':
       return 1;
+    case 'u16':
+      return 2;
+    case 'u32':
+      return 4;
+    case 'u128':
+      return 16;
     case 'pubkey':
       return 32;
     default:
@@ -86,6 +158,14 @@ const typeSize = (type: IrType) => {
 };
 
 const discriminatorArgName = 'instructionDiscriminator';
+const accountDiscriminatorFieldName = 'discriminator';
+
+const knownProgramAddresses: Record<string, string> = {
+  token: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA',
+  token2022: 'TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb',
+  system: '11111111111111111111111111111111',
+  associatedToken: 'ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL',
+};
 
 const pdaDefinitions = new Map<string, { seeds: Array<any> }>();
 
@@ -136,22 +216,49 @@ const pdaNodes = [...pdaDefinitions.entries()].map(([name, pda]) =>
 
 const accountNodes = Object.entries(accounts).map(([accountKey, account]) => {
   const accountName = camelCase(account.name ?? accountKey);
+  if (account.schema?.[accountDiscriminatorFieldName]) {
+    throw new Error(`Account ${accountName} already defines ${accountDiscriminatorFieldName}.`);
+  }
+  const discriminatorHex = account.discriminator
+    .map((byte) => byte.toString(16).padStart(2, '0'))
+    .join('');
+  const discriminatorField = structFieldTypeNode({
+    name: accountDiscriminatorFieldName,
+    type: fixedSizeTypeNode(bytesTypeNode(), account.discriminator.length),
+    defaultValue: bytesValueNode('base16', discriminatorHex),
+    defaultValueStrategy: 'omitted',
+  });
   const fields = Object.entries(account.schema ?? {}).map(([name, type]) =>
     structFieldTypeNode({
       name,
       type: toTypeNode(type),
     }),
   );
-  const size = Object.values(account.schema ?? {}).reduce((sum, type) => sum + typeSize(type), 0);
+  const size = Object.values(account.schema ?? {}).reduce(
+    (sum, type) => sum + typeSize(type),
+    account.discriminator.length,
+  );
   return accountNode({
     name: accountName,
-    data: structTypeNode(fields),
+    data: structTypeNode([discriminatorField, ...fields]),
+    discriminators: [fieldDiscriminatorNode(accountDiscriminatorFieldName)],
     size,
     ...(account.pda ? { pda: pdaLinkNode(accountKey) } : {}),
   });
 });
 
-const buildPdaDefaultValue = (meta: IrInstruction['accounts'][number], ix: IrInstruction) => {
+const buildDefaultValue = (meta: IrInstruction['accounts'][number], ix: IrInstruction) => {
+  if (meta.kind === 'program' && typeof meta.address === 'string') {
+    const address = knownProgramAddresses[meta.address];
+    return address ? publicKeyValueNode(address) : undefined;
+  }
+  // A token program account without an address defaults to the program's token program;
+  // with 'either' the caller picks one.
+  const invokedByTokenOp = ix.ops.some((op) => op.op.startsWith('token.') && (op.program ?? 'tokenProgram') === meta.name);
+  if (meta.kind === 'program' && meta.address === undefined && invokedByTokenOp) {
+    const address = knownProgramAddresses[meta.tokenProgram ?? ir.tokenProgram ?? 'token'];
+    return address ? publicKeyValueNode(address) : undefined;
+  }
   if (!meta.pda) return undefined;
   const seeds = [];
   for (const seed of meta.pda.seeds) {
@@ -198,7 +305,7 @@ const instructionNodes = instructions.map((ix) => {
   ];
 
   const accountNodesForIx = ix.accounts.map((meta) => {
-    const defaultValue = buildPdaDefaultValue(meta, ix);
+    const defaultValue = buildDefaultValue(meta, ix);
     return instructionAccountNode({
       name: meta.name,
       isSigner: Boolean(meta.signer),
@@ -223,7 +330,13 @@ const program = programNode({
   instructions: instructionNodes,
   definedTypes: [],
   pdas: pdaNodes,
-  errors: [],
+  errors: programErrors.map((error) =>
+    errorNode({
+      name: camelCase(error.name),
+      code: error.code,
+      message: error.message,
+    }),
+  ),
 });
 
 const codama = createFromRoot(rootNode(program));
@@ -243,12 +356,29 @@ await codama.accept(
   }),
 );
 
-const tsType = (type: IrType) => {
+const tsType = (type: IrType): string => {
   switch (type.kind) {
+    case 'array':
+    case 'vec':
+      return `${tsType(type.of as IrType)}[]`;
+    case 'bytes':
+      return 'Uint8Array';
+    case 'string':
+      return 'string';
+    case 'struct':
+      return `{ ${Object.entries(type.fields as Record<string, IrType>)
+        .map(([name, fieldType]) => `${name}: ${tsType(fieldType)};`)
+        .join(' ')} }`;
     case 'u64':
+    case 'u128':
+    case 'i64':
       return 'bigint';
     case 'u8':
+    case 'u16':
+    case 'u32':
       return 'number';
+    case 'bool':
+      return 'boolean';
     case 'pubkey':
       return 'PublicKey';
     default:
@@ -294,7 +424,13 @@ const renderIxBuilder = (ix: IrInstruction) => {
     ...ix.accounts.map((meta) => `      ${meta.name}: toAddress(${resolveAccount(meta)}),`),
     ...argsList.map(([name, type]) => {
       const key = argInputName(name);
-      const value = type.kind === 'pubkey' ? `toAddress(args.${name})` : `args.${name}`;
+      const isPubkeyVec = type.kind === 'vec' && (type.of as IrType).kind === 'pubkey';
+      const value =
+        type.kind === 'pubkey'
+          ? `toAddress(args.${name})`
+          : isPubkeyVec
+            ? `args.${name}.map(toAddress)`
+            : `args.${name}`;
       return `      ${key}: ${value},`;
     }),
   ];
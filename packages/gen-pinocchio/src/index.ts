@@ -11,44 +11,60 @@ const irRaw = await fs.readFile(irPath, 'utf8');
 const ir = JSON.parse(irRaw);
 
 const programName = ir.name as string;
-const instructions = ir.instructions as Array<any>;
 const accounts = ir.accounts as Record<string, any>;
-
-const resolveStateAccountKey = () => {
-  const stateAccounts = new Set<string>();
-  instructions.forEach((ix) => {
-    (ix.ops as Array<any>).forEach((op) => {
-      if (op.op === 'state.init' || op.op === 'state.update') {
-        if (typeof op.account === 'string') stateAccounts.add(op.account);
-      }
-    });
-  });
-
-  if (stateAccounts.size > 1) {
-    throw new Error(`Multiple state accounts are not supported: ${[...stateAccounts].join(', ')}`);
-  }
-
-  if (stateAccounts.size === 1) {
-    const [key] = [...stateAccounts];
-    if (!accounts[key]) {
-      throw new Error(`State account ${key} referenced in ops but not defined.`);
+const programErrors = (ir.errors ?? []) as Array<{ name: string; code: number; message: string }>;
+const programTokenProgram = (ir.tokenProgram ?? 'token') as string;
+const programEvents = (ir.events ?? []) as Array<{ name: string; fields: Record<string, any>; discriminator: number[] }>;
+
+// A view compiles to a read-only instruction: pubkey args named after a state account
+// pass that account, the other args stay instruction data, and one `view.return` op
+// writes the return fields. A bare type (`u64()`) returns the state field of that name.
+const viewInstruction = (view: any) => {
+  const argEntries = Object.entries(view.args ?? {}) as Array<[string, any]>;
+  const isAccountArg = ([name, type]: [string, any]) => type.kind === 'pubkey' && accounts[name] !== undefined;
+  argEntries.forEach(([name, type]) => {
+    if (accounts[name] && type.kind !== 'pubkey') {
+      throw new Error(`View ${view.name} arg ${name} is named after a state account, so it must be pubkey().`);
     }
-    return key;
-  }
-
-  const accountKeys = Object.keys(accounts);
-  if (accountKeys.length === 1) {
-    return accountKeys[0]!;
+  });
+  const viewAccounts = argEntries.filter(isAccountArg).map(([name]) => ({ name }));
+  if (viewAccounts.length === 0) {
+    throw new Error(`View ${view.name} needs a pubkey arg named after a state account.`);
   }
-
-  throw new Error('Unable to infer state account. Add a state.init/state.update op.');
+  const types: Record<string, string> = {};
+  const fields = Object.fromEntries(
+    Object.entries(view.returns).map(([name, value]: [string, any]) => {
+      if (value === 'ratio') {
+        throw new Error(`View ${view.name} return ${name}: use ratio(numerator, denominator).`);
+      }
+      const isBareType = Object.keys(value).length === 1;
+      if (!isBareType) return [name, value];
+      const owners = viewAccounts.filter((meta) => accounts[meta.name].schema[name]);
+      if (owners.length !== 1) {
+        throw new Error(`View ${view.name} return ${name} must name a field of one state account, or be an expression.`);
+      }
+      types[name] = value.kind;
+      return [name, { kind: 'field', account: owners[0].name, name }];
+    }),
+  );
+  return {
+    name: view.name,
+    args: Object.fromEntries(argEntries.filter((entry) => !isAccountArg(entry))),
+    accounts: viewAccounts,
+    ops: [{ op: 'view.return', fields, types }],
+    discriminator: view.discriminator,
+    view: true,
+  };
 };
 
-const stateAccountKey = resolveStateAccountKey();
-const stateAccount = accounts[stateAccountKey];
-if (!stateAccount) {
-  throw new Error(`Expected state account ${stateAccountKey} definition in IR.`);
-}
+// Instruction returns reuse the view return op, run after every other op.
+const withReturnOp = (ix: any) =>
+  ix.returns ? { ...ix, ops: [...ix.ops, { op: 'view.return', fields: ix.returns, types: {} }] } : ix;
+
+const instructions = [
+  ...(ir.instructions as Array<any>).map(withReturnOp),
+  ...((ir.views ?? []) as Array<any>).map(viewInstruction),
+];
 
 const getInitAccounts = (ix: any) =>
   (ix.ops as Array<any>)
@@ -74,28 +90,263 @@ const toPascal = (input: string) =>
     .map((part) => part[0]?.toUpperCase() + part.slice(1))
     .join('');
 
-const stateStructName = stateAccount.name ?? `${toPascal(stateAccountKey)}State`;
-const stateAccountVar = toSnake(stateAccountKey);
-const stateVarName = `${stateAccountVar}_state`;
+const structNameFor = (accountKey: string) =>
+  accounts[accountKey].name ?? `${toPascal(accountKey)}State`;
+
+const errorEnumName = `${toPascal(programName)}Error`;
+
+const renderErrorEnum = () => {
+  const variants = programErrors
+    .map((error) => `    /// ${error.message}\n    ${error.name} = ${error.code},`)
+    .join('\n');
+  return `#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ${errorEnumName} {
+${variants}
+}
+
+impl From<${errorEnumName}> for ProgramError {
+    fn from(error: ${errorEnumName}) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+`;
+};
+
+const structNames = Object.keys(accounts).map(structNameFor);
+structNames.forEach((name, index) => {
+  if (structNames.indexOf(name) !== index) {
+    throw new Error(`Duplicate account struct name ${name}.`);
+  }
+});
+
+// Instruction accounts map to an entry in `accounts` via `state`, or by sharing its name.
+const stateKeyOf = (meta: any): string | undefined => {
+  if (meta.state !== undefined) {
+    if (!accounts[meta.state]) {
+      throw new Error(`Account ${meta.name} references undefined state ${meta.state}.`);
+    }
+    return meta.state;
+  }
+  return accounts[meta.name] ? meta.name : undefined;
+};
+
+const stateVarOf = (accountName: string) => `${toSnake(accountName)}_state`;
+
+const findStateMeta = (ix: any, accountName: string) => {
+  const meta = (ix.accounts as Array<any>).find((entry) => entry.name === accountName);
+  if (!meta) {
+    throw new Error(`State account ${accountName} not found in ${ix.name} accounts.`);
+  }
+  const key = stateKeyOf(meta);
+  if (!key) {
+    throw new Error(`Account ${accountName} in ${ix.name} is not a state account.`);
+  }
+  return { meta, key, schema: accounts[key].schema as Record<string, any>, structName: structNameFor(key) };
+};
+
+// Walks a field path (`field('pool', 'reserves', 0)`, `field('pool', 'tier', 'feeBps')`)
+// down to the type it addresses; undefined when the field does not exist.
+const fieldTypeAt = (schema: Record<string, any>, ref: any) => {
+  let type = schema[ref.name];
+  let described = `${ref.account}.${ref.name}`;
+  (ref.path ?? []).forEach((step: any) => {
+    if (!type) return;
+    if (type.kind === 'array' && Number.isInteger(step)) {
+      if (step < 0 || step >= type.len) {
+        throw new Error(`Index ${step} is out of bounds for ${described} of length ${type.len}.`);
+      }
+      type = type.of;
+      described = `${described}[${step}]`;
+    } else if (type.kind === 'struct' && typeof step === 'string' && type.fields[step]) {
+      type = type.fields[step];
+      described = `${described}.${step}`;
+    } else {
+      throw new Error(`${described} has no ${typeof step === 'number' ? 'index' : 'field'} ${step}.`);
+    }
+  });
+  return type;
+};
+
+// Name of the nested struct or enum a field path ends in; array indices keep the name.
+const nestedNameAt = (structName: string, ref: any) =>
+  [ref.name, ...(ref.path ?? []).filter((step: any) => typeof step === 'string')].reduce(
+    (parent: string, step: string) => nestedStructName(parent, step),
+    structName,
+  );
+
+// Expression type of a schema type: scalars by kind, enums by their Rust name and
+// options as `Option<T>`.
+const exprTypeOf = (type: any, rustName: string): string => {
+  if (type.kind === 'enum') return rustName;
+  if (type.kind === 'option') return `Option<${exprTypeOf(type.of, rustName)}>`;
+  return type.kind;
+};
+
+const optionInner = (type: string) =>
+  type.startsWith('Option<') ? type.slice('Option<'.length, -1) : undefined;
+
+// `state.update` keys address elements and nested fields with dots: `'reserves.0'`.
+const parseFieldKey = (account: string, key: string) => {
+  const [name, ...steps] = key.split('.');
+  const path = steps.map((step) => (/^\d+$/.test(step) ? Number(step) : step));
+  return { kind: 'field', account, name, ...(path.length > 0 ? { path } : {}) };
+};
+
+const fieldPathToRust = (path: any[] = []) =>
+  path.map((step) => (typeof step === 'number' ? `[${step}]` : `.${toSnake(step)}`)).join('');
+
+const fieldRefToRust = (ref: any) =>
+  `${stateVarOf(ref.account)}.${toSnake(ref.name)}${fieldPathToRust(ref.path)}`;
+
+const collectRefs = (node: any, kind: string, refs: any[] = []): any[] => {
+  if (!node || typeof node !== 'object') return refs;
+  if (Array.isArray(node)) {
+    node.forEach((item) => collectRefs(item, kind, refs));
+    return refs;
+  }
+  if (node.kind === kind) {
+    refs.push(node);
+    return refs;
+  }
+  Object.values(node).forEach((value) => collectRefs(value, kind, refs));
+  return refs;
+};
+
+const collectFieldRefs = (node: any) => collectRefs(node, 'field');
+
+const fieldAccountsOf = (node: any) => new Set(collectFieldRefs(node).map((ref) => ref.account as string));
+
+// `equals` on an account and `hasOne` on a state account (`hasOne: ['admin']` means
+// `vault.admin` must be the `admin` account) both pin an account key to a pubkey ref.
+// Each fails with its own error.
+const equalityConstraints = (ix: any) =>
+  (ix.accounts as Array<any>).flatMap((meta) => [
+    ...(meta.equals ? [{ account: meta.name, ref: meta.equals, error: 'ConstraintEquals' }] : []),
+    ...(meta.hasOne ?? []).map((name: string) => ({
+      account: name,
+      ref: { kind: 'field', account: meta.name, name },
+      error: 'ConstraintHasOne',
+    })),
+  ]);
+
+const validateFieldRefs = (ix: any) => {
+  collectFieldRefs([ix.accounts, ix.ops, equalityConstraints(ix)]).forEach((ref) => {
+    const { schema, key } = findStateMeta(ix, ref.account);
+    if (!fieldTypeAt(schema, ref)) {
+      throw new Error(`Unknown field ${ref.name} on ${key} in ${ix.name}.`);
+    }
+  });
+};
+
+// Little-endian layout of every scalar the DSL supports.
+const scalarTypes: Record<string, { rust: string; size: number }> = {
+  pubkey: { rust: 'Pubkey', size: 32 },
+  u64: { rust: 'u64', size: 8 },
+  u8: { rust: 'u8', size: 1 },
+  u16: { rust: 'u16', size: 2 },
+  u32: { rust: 'u32', size: 4 },
+  u128: { rust: 'u128', size: 16 },
+  i64: { rust: 'i64', size: 8 },
+  bool: { rust: 'bool', size: 1 },
+};
+
+const integerRanges: Record<string, [bigint, bigint]> = {
+  u8: [0n, 2n ** 8n - 1n],
+  u16: [0n, 2n ** 16n - 1n],
+  u32: [0n, 2n ** 32n - 1n],
+  u64: [0n, 2n ** 64n - 1n],
+  u128: [0n, 2n ** 128n - 1n],
+  i64: [-(2n ** 63n), 2n ** 63n - 1n],
+};
+
+const isInteger = (kind: string) => kind in integerRanges;
+
+const scalarOf = (type: any) => {
+  const scalar = scalarTypes[type.kind];
+  if (!scalar) throw new Error(`Unsupported type: ${type.kind}`);
+  return scalar;
+};
+
+const renderRustType = (type: any) => scalarOf(type).rust;
+
+const comparisonOperators: Record<string, string> = {
+  eq: '==',
+  ne: '!=',
+  lt: '<',
+  lte: '<=',
+  gt: '>',
+  gte: '>=',
+};
+
+const negatedComparisons: Record<string, string> = {
+  eq: 'ne',
+  ne: 'eq',
+  lt: 'gte',
+  lte: 'gt',
+  gt: 'lte',
+  gte: 'lt',
+};
+
+// Pushes a negation down to the leaves so the output never contains `!(a < b)` or
+// `!(a && b)`, which clippy's nonminimal_bool rejects.
+const negateExpr = (expr: any): any => {
+  const flipped = negatedComparisons[expr.kind];
+  if (flipped) return { ...expr, kind: flipped };
+  if (expr.kind === 'and') return { kind: 'or', left: negateExpr(expr.left), right: negateExpr(expr.right) };
+  if (expr.kind === 'or') return { kind: 'and', left: negateExpr(expr.left), right: negateExpr(expr.right) };
+  if (expr.kind === 'not') return expr.value;
+  if (expr.kind === 'bool') return { kind: 'bool', value: !expr.value };
+  return { kind: 'not', value: expr };
+};
+
+// `x == true` and `x != false` are just `x`; clippy's bool_comparison rejects the long form.
+const foldBoolComparison = (expr: any): any => {
+  if (expr.kind !== 'eq' && expr.kind !== 'ne') return expr;
+  const literal = [expr.left, expr.right].find((side) => side.kind === 'bool');
+  if (!literal) return expr;
+  const other = literal === expr.left ? expr.right : expr.left;
+  const keep = (expr.kind === 'eq') === literal.value;
+  return foldBoolComparison(keep ? other : negateExpr(other));
+};
+
+const wrapIf = (expr: any, kinds: string[]) => {
+  const rendered = exprToRust(expr);
+  return kinds.includes(foldBoolComparison(expr).kind) ? `(${rendered})` : rendered;
+};
 
-const renderRustType = (type: any) => {
-  if (type.kind === 'u64') return 'u64';
-  if (type.kind === 'u8') return 'u8';
-  if (type.kind === 'pubkey') return 'Pubkey';
-  throw new Error(`Unsupported type: ${type.kind}`);
+const roundingHelpers: Record<string, { div: string; mulDiv: string }> = {
+  floor: { div: 'checked_div', mulDiv: 'checked_mul_div' },
+  ceil: { div: 'checked_div_ceil', mulDiv: 'checked_mul_div_ceil' },
+  nearest: { div: 'checked_div_round', mulDiv: 'checked_mul_div_round' },
 };
 
-const fieldNames = Object.keys(stateAccount.schema);
-const fieldMap = new Map(fieldNames.map((name) => [name, toSnake(name)]));
+const booleanKinds = [...Object.keys(comparisonOperators), 'and', 'or', 'if'];
+
+// Expression keys already bound to a Rust variable in the handler being rendered.
+const exprBindings = new Map<string, { name: string; accounts: Set<string> }>();
 
-const exprToRust = (expr: any): string => {
+const exprKey = (expr: any) => JSON.stringify(expr);
+
+const exprToRust = (rawExpr: any): string => {
+  const binding = exprBindings.get(exprKey(rawExpr));
+  if (binding) return binding.name;
+  const expr = foldBoolComparison(rawExpr);
   switch (expr.kind) {
     case 'const':
-      return `${expr.value}u64`;
+      return `${expr.value}${expr.type ?? 'u64'}`;
+    case 'bool':
+      return `${expr.value}`;
+    case 'pubkey':
+      return `pinocchio_pubkey::pubkey!("${expr.value}")`;
+    case 'account':
+      return `*${toSnake(expr.name)}.key()`;
     case 'arg':
       return `args.${toSnake(expr.name)}`;
+    case 'local':
+      return toSnake(expr.name);
     case 'field':
-      return `${stateVarName}.${fieldMap.get(expr.name)}`;
+      return fieldRefToRust(expr);
     case 'add':
       return `checked_add(${exprToRust(expr.left)}, ${exprToRust(expr.right)})?`;
     case 'sub':
@@ -104,12 +355,78 @@ const exprToRust = (expr: any): string => {
       return `checked_mul(${exprToRust(expr.left)}, ${exprToRust(expr.right)})?`;
     case 'div': {
       if (expr.left?.kind === 'mul') {
-        return `checked_mul_div(${exprToRust(expr.left.left)}, ${exprToRust(expr.left.right)}, ${exprToRust(expr.right)})?`;
+        return `${roundingHelpers[expr.rounding ?? 'floor'].mulDiv}(${exprToRust(expr.left.left)}, ${exprToRust(expr.left.right)}, ${exprToRust(expr.right)})?`;
+      }
+      return `${roundingHelpers[expr.rounding ?? 'floor'].div}(${exprToRust(expr.left)}, ${exprToRust(expr.right)})?`;
+    }
+    case 'min':
+    case 'max':
+      return `${wrapIf(expr.left, ['if'])}.${expr.kind}(${exprToRust(expr.right)})`;
+    case 'sqrt': {
+      if (expr.value.kind === 'mul') {
+        return `sqrt_mul(${exprToRust(expr.value.left)}, ${exprToRust(expr.value.right)})`;
+      }
+      return `sqrt(${exprToRust(expr.value)})`;
+    }
+    case 'mulDiv':
+      return `${roundingHelpers[expr.rounding ?? 'floor'].mulDiv}(${exprToRust(expr.left)}, ${exprToRust(expr.right)}, ${exprToRust(expr.divisor)})?`;
+    case 'isSome':
+      return `${wrapIf(expr.value, ['if'])}.is_some()`;
+    case 'some':
+      return `Some(${exprToRust(expr.value)})`;
+    case 'none':
+      return 'None';
+    case 'variant':
+      return `${expr.type}::${expr.name}`;
+    case 'len':
+      return `arg_len(${exprToRust(expr.value)}.len())`;
+    case 'at':
+      return `${exprToRust(expr.value)}.get(${exprToRust(expr.index)})?`;
+    case 'cast': {
+      const rust = renderRustType({ kind: expr.to });
+      const [fromMin, fromMax] = integerRanges[expr.from];
+      const [toMin, toMax] = integerRanges[expr.to];
+      // Widening is lossless; anything else is checked and fails with Overflow.
+      if (fromMin >= toMin && fromMax <= toMax) {
+        return `${rust}::from(${exprToRust(expr.value)})`;
       }
-      return `checked_div(${exprToRust(expr.left)}, ${exprToRust(expr.right)})?`;
+      return `${rust}::try_from(${exprToRust(expr.value)}).map_err(|_| ${errorEnumName}::Overflow)?`;
     }
+    case 'bps':
+      return `checked_bps(${exprToRust(expr.left)}, ${exprToRust(expr.right)})?`;
+    case 'pow':
+      return `checked_pow(${exprToRust(expr.left)}, ${exprToRust(expr.right)})?`;
     case 'eq':
-      return `${exprToRust(expr.left)} == ${exprToRust(expr.right)}`;
+    case 'ne':
+    case 'lt':
+    case 'lte':
+    case 'gt':
+    case 'gte': {
+      // Unsigned values cannot go below zero; clippy denies `x <= 0` as an absurd comparison.
+      const isZero = (side: any) =>
+        side.kind === 'const' && BigInt(side.value) === 0n && integerRanges[side.type ?? 'u64'][0] === 0n;
+      const { left, right } = expr;
+      if ((expr.kind === 'lte' && isZero(right)) || (expr.kind === 'gte' && isZero(left))) {
+        const [value, zero] = isZero(right) ? [left, right] : [right, left];
+        return `${exprToRust(value)} == ${exprToRust(zero)}`;
+      }
+      if ((expr.kind === 'lt' && isZero(right)) || (expr.kind === 'gt' && isZero(left))) {
+        throw new Error('Comparison below zero is always false for unsigned values.');
+      }
+      if ((expr.kind === 'gte' && isZero(right)) || (expr.kind === 'lte' && isZero(left))) {
+        throw new Error('Comparison against zero is always true for unsigned values.');
+      }
+      return `${wrapIf(left, booleanKinds)} ${comparisonOperators[expr.kind]} ${wrapIf(right, booleanKinds)}`;
+    }
+    case 'and':
+      return `${wrapIf(expr.left, ['or', 'if'])} && ${wrapIf(expr.right, ['or', 'if'])}`;
+    case 'or':
+      return `${wrapIf(expr.left, ['if'])} || ${wrapIf(expr.right, ['if'])}`;
+    case 'not': {
+      const negated = negateExpr(expr.value);
+      if (negated.kind !== 'not') return exprToRust(negated);
+      return `!${wrapIf(expr.value, ['if'])}`;
+    }
     case 'if':
       return `if ${exprToRust(expr.cond)} { ${exprToRust(expr.then)} } else { ${exprToRust(expr.else)} }`;
     default:
@@ -117,67 +434,370 @@ const exprToRust = (expr: any): string => {
   }
 };
 
-const initValueToRust = (value: any, fieldType: any): string => {
+const negatedConditionToRust = (expr: any) => exprToRust(negateExpr(expr));
+
+const arithmeticKinds = ['add', 'sub', 'mul', 'div', 'min', 'max'];
+
+// Literal-only trees have no type of their own until their context gives them one.
+const isLiteralTree = (expr: any): boolean =>
+  expr.kind === 'const' ||
+  (arithmeticKinds.includes(expr.kind) && isLiteralTree(expr.left) && isLiteralTree(expr.right));
+
+// Trees whose type comes from their context: literals, `none` and enum variants.
+const isContextTyped = (expr: any): boolean =>
+  isLiteralTree(expr) ||
+  expr.kind === 'none' ||
+  expr.kind === 'variant' ||
+  (expr.kind === 'some' && isContextTyped(expr.value));
+
+// Type inference: every node gets a scalar type from the schema and arg types, and
+// literals take the type their context expects (u64 when nothing does). Returns the
+// expression with typed literals and casts, ready for `exprToRust`. Errors are
+// prefixed with the DSL location (`deposit.ops[2]`) instead of surfacing in rustc.
+const lowerExpr = (
+  expr: any,
+  ix: any,
+  localTypes: Map<string, string>,
+  at: string,
+  expected?: string,
+  role = expr.kind,
+): { type: string; expr: any } => {
+  const fail = (message: string): never => {
+    throw new Error(`${at}: ${message}`);
+  };
+  const lower = (value: any, want?: string, valueRole?: string) =>
+    lowerExpr(value, ix, localTypes, at, want, valueRole);
+  const operandType = (left: any, right: any, want?: string) =>
+    want ?? [left, right].filter((side) => !isContextTyped(side)).map((side) => lower(side).type)[0] ?? 'u64';
+  const integerOperands = (kind: string, left: any, right: any, want?: string) => {
+    const type = operandType(left, right, want);
+    if (!isInteger(type)) fail(`${kind} expects integers, got ${type}.`);
+    return {
+      type,
+      left: lower(left, type, `${kind} operand`).expr,
+      right: lower(right, type, `${kind} operand`).expr,
+    };
+  };
+  const expectRounding = () => {
+    if (!roundingHelpers[expr.rounding ?? 'floor']) {
+      fail(`${expr.kind} has unknown rounding ${expr.rounding}.`);
+    }
+  };
+
+  const infer = (): { type: string; expr: any } => {
+    switch (expr.kind) {
+      case 'const': {
+        const type = expected ?? 'u64';
+        if (!isInteger(type)) fail(`${role} expects ${type}, got integer literal ${expr.value}.`);
+        const [min, max] = integerRanges[type];
+        const value = BigInt(expr.value);
+        if (value < min || value > max) fail(`${role}: ${expr.value} does not fit in ${type}.`);
+        return { type, expr: { ...expr, type } };
+      }
+      case 'bool':
+        return { type: 'bool', expr };
+      case 'pubkey':
+        if (!/^[1-9A-HJ-NP-Za-km-z]{32,44}$/.test(expr.value)) fail(`${expr.value} is not a base58 address.`);
+        return { type: 'pubkey', expr };
+      case 'account':
+        if (!(ix.accounts as Array<any>).some((meta) => meta.name === expr.name)) {
+          fail(`unknown account ${expr.name}.`);
+        }
+        return { type: 'pubkey', expr };
+      case 'arg': {
+        const type = ix.args?.[expr.name];
+        if (!type) fail(`unknown arg ${expr.name}.`);
+        return { type: type.kind, expr };
+      }
+      case 'field': {
+        const { schema, structName } = findStateMeta(ix, expr.account);
+        const type = fieldTypeAt(schema, expr);
+        if (!type) fail(`unknown field ${expr.name} on ${expr.account}.`);
+        if (type.kind === 'array' || type.kind === 'struct') {
+          fail(`${expr.account}.${expr.name} is an ${type.kind}; address one element or field.`);
+        }
+        return { type: exprTypeOf(type, nestedNameAt(structName, expr)), expr };
+      }
+      case 'local': {
+        const type = localTypes.get(expr.name);
+        if (!type) fail(`reads local ${expr.name} before its compute.`);
+        return { type: type!, expr };
+      }
+      case 'add':
+      case 'sub':
+      case 'mul':
+      case 'min':
+      case 'max': {
+        const { type, left, right } = integerOperands(expr.kind, expr.left, expr.right, expected);
+        return { type, expr: { ...expr, left, right } };
+      }
+      case 'div': {
+        expectRounding();
+        // Mul-div and rounded division use u128 intermediates, so they are u64-only.
+        if (expr.left.kind === 'mul' || (expr.rounding ?? 'floor') !== 'floor') {
+          const { left, right } = integerOperands(expr.kind, expr.left, expr.right, 'u64');
+          return { type: 'u64', expr: { ...expr, left, right } };
+        }
+        const { type, left, right } = integerOperands(expr.kind, expr.left, expr.right, expected);
+        return { type, expr: { ...expr, left, right } };
+      }
+      case 'bps':
+      case 'pow': {
+        const { left, right } = integerOperands(expr.kind, expr.left, expr.right, 'u64');
+        return { type: 'u64', expr: { ...expr, left, right } };
+      }
+      case 'mulDiv': {
+        expectRounding();
+        const { left, right } = integerOperands(expr.kind, expr.left, expr.right, 'u64');
+        const divisor = lower(expr.divisor, 'u64', 'mulDiv operand').expr;
+        return { type: 'u64', expr: { ...expr, left, right, divisor } };
+      }
+      case 'sqrt':
+        return { type: 'u64', expr: { ...expr, value: lower(expr.value, 'u64', 'sqrt operand').expr } };
+      case 'eq':
+      case 'ne': {
+        const type = operandType(expr.left, expr.right);
+        if (type === 'vec') fail(`${expr.kind} cannot compare vec args.`);
+        const left = lower(expr.left, type, `${expr.kind} operand`).expr;
+        const right = lower(expr.right, type, `${expr.kind} operand`).expr;
+        return { type: 'bool', expr: { ...expr, left, right } };
+      }
+      case 'lt':
+      case 'lte':
+      case 'gt':
+      case 'gte': {
+        const { left, right } = integerOperands(expr.kind, expr.left, expr.right);
+        return { type: 'bool', expr: { ...expr, left, right } };
+      }
+      case 'and':
+      case 'or': {
+        const left = lower(expr.left, 'bool', `${expr.kind} operand`).expr;
+        const right = lower(expr.right, 'bool', `${expr.kind} operand`).expr;
+        return { type: 'bool', expr: { ...expr, left, right } };
+      }
+      case 'not':
+        return { type: 'bool', expr: { ...expr, value: lower(expr.value, 'bool', 'not operand').expr } };
+      case 'if': {
+        const cond = lower(expr.cond, 'bool', 'if condition').expr;
+        const type = operandType(expr.then, expr.else, expected);
+        const then = lower(expr.then, type, 'if branch').expr;
+        const otherwise = lower(expr.else, type, 'if branch').expr;
+        return { type, expr: { ...expr, cond, then, else: otherwise } };
+      }
+      case 'isSome': {
+        const inner = lower(expr.value);
+        if (!optionInner(inner.type)) fail(`isSome expects an option, got ${inner.type}.`);
+        return { type: 'bool', expr: { ...expr, value: inner.expr } };
+      }
+      case 'some': {
+        const wanted = expected && optionInner(expected);
+        const inner = wanted ? lower(expr.value, wanted, 'some value') : lower(expr.value);
+        return { type: `Option<${inner.type}>`, expr: { ...expr, value: inner.expr } };
+      }
+      case 'none':
+        if (!expected || !optionInner(expected)) fail('none needs an option to compare with or assign to.');
+        return { type: expected!, expr };
+      case 'variant': {
+        const variants = expected ? enumVariants.get(expected) : undefined;
+        if (!variants) fail(`variant ${expr.name} needs an enum to compare with or assign to.`);
+        if (!variants!.includes(expr.name)) fail(`${expected} has no variant ${expr.name}.`);
+        return { type: expected!, expr: { ...expr, type: expected } };
+      }
+      case 'len': {
+        const inner = lower(expr.value);
+        if (!variableArgKinds.includes(inner.type)) fail(`len expects a bytes, string or vec arg, got ${inner.type}.`);
+        return { type: 'u64', expr: { ...expr, value: inner.expr } };
+      }
+      case 'at': {
+        const argType = expr.value.kind === 'arg' ? ix.args?.[expr.value.name] : undefined;
+        if (argType?.kind !== 'vec') fail('at expects a vec arg.');
+        const index = lower(expr.index, 'u64', 'at index').expr;
+        return { type: argType.of.kind, expr: { ...expr, index } };
+      }
+      case 'cast': {
+        const to = expr.type?.kind;
+        if (!isInteger(to)) fail(`cast target must be an integer type, got ${to}.`);
+        if (isLiteralTree(expr.value)) return lower(expr.value, to, 'cast');
+        const inner = lower(expr.value);
+        if (!isInteger(inner.type)) fail(`cast expects an integer, got ${inner.type}.`);
+        if (inner.type === to) return inner;
+        return { type: to, expr: { kind: 'cast', value: inner.expr, from: inner.type, to } };
+      }
+      default:
+        return fail(`unsupported expr ${expr.kind}.`);
+    }
+  };
+
+  const result = infer();
+  if (expected && result.type !== expected) {
+    const hint = isInteger(expected) && isInteger(result.type) ? '; use expr.cast to convert' : '';
+    fail(`${role} expects ${expected}, got ${result.type}${hint}.`);
+  }
+  return result;
+};
+
+const initValueToRust = (value: any): string => {
   if (value.kind === 'arg') {
     return `args.${toSnake(value.name)}`;
   }
-  if (value.kind === 'account') {
-    return `*${toSnake(value.name)}.key()`;
-  }
   if (value.kind === 'field') {
-    return `${stateVarName}.${fieldMap.get(value.name)}`;
+    return fieldRefToRust(value);
   }
   if (value.kind === 'bump') {
     return `${toSnake(value.account)}_bump`;
   }
-  if (value.kind === 'const') {
-    if (fieldType.kind === 'u8') {
-      return `${value.value}u8`;
-    }
-    return `${value.value}u64`;
-  }
   return exprToRust(value);
 };
 
+const variableArgKinds = ['bytes', 'string', 'vec'];
+
+const hasVariableArgs = (ix: any) =>
+  Object.values(ix.args).some((type: any) => variableArgKinds.includes(type.kind));
+
+// Variable-length args borrow from the instruction data instead of allocating.
+const renderArgType = (type: any) => {
+  if (type.kind === 'bytes') return `&'a [u8]`;
+  if (type.kind === 'string') return `&'a str`;
+  if (type.kind === 'vec') return `ArgVec<'a, ${renderRustType(type.of)}>`;
+  return renderRustType(type);
+};
+
+const validateArgType = (ix: any, name: string, type: any) => {
+  if (!variableArgKinds.includes(type.kind)) {
+    scalarOf(type);
+    return;
+  }
+  if (!Number.isInteger(type.maxLen) || type.maxLen < 1 || type.maxLen > 2 ** 32 - 1) {
+    throw new Error(`Arg ${name} in ${ix.name} needs a maxLen between 1 and 2^32 - 1.`);
+  }
+  if (type.kind === 'vec') scalarOf(type.of);
+};
+
+const hasArgs = (ix: any) => Object.keys(ix.args).length > 0;
+
+// Only instructions with args get an args struct and decoder.
 const renderArgsStruct = (ix: any) => {
   const structName = `${toPascal(ix.name)}Args`;
   const fields = Object.entries(ix.args)
-    .map(([name, type]) => `    pub ${toSnake(name)}: ${renderRustType(type)},`)
+    .map(([name, type]) => `    pub ${toSnake(name)}: ${renderArgType(type)},`)
     .join('\n');
-  if (!fields) return `struct ${structName} {}\n`;
-  return `struct ${structName} {\n${fields}\n}\n`;
+  const lifetime = hasVariableArgs(ix) ? `<'a>` : '';
+  return `struct ${structName}${lifetime} {\n${fields}\n}\n`;
 };
 
 const renderArgsDecoder = (ix: any) => {
   const structName = `${toPascal(ix.name)}Args`;
   const argEntries = Object.entries(ix.args);
-  if (argEntries.length === 0) {
-    return `fn decode_${toSnake(ix.name)}_args(_data: &[u8]) -> Result<${structName}, ProgramError> {\n    Ok(${structName} {})\n}\n`;
-  }
+  // Offsets after a variable-length arg add up the lengths decoded so far.
   let offset = 0;
-  const reads = argEntries.map(([name, type]) => {
+  const lengths: string[] = [];
+  const reads = argEntries.map(([name, type]: [string, any]) => {
+    validateArgType(ix, name, type);
     const rustName = toSnake(name);
-    if (type.kind === 'u64') {
-      const code = `let ${rustName} = read_u64(data, ${offset})?;`;
-      offset += 8;
-      return code;
-    }
-    if (type.kind === 'u8') {
-      const code = `let ${rustName} = read_u8(data, ${offset})?;`;
-      offset += 1;
-      return code;
-    }
-    if (type.kind === 'pubkey') {
-      const code = `let ${rustName} = read_pubkey(data, ${offset})?;`;
-      offset += 32;
-      return code;
-    }
-    throw new Error(`Unsupported arg type: ${type.kind}`);
+    const at = [`${offset}`, ...lengths].join(' + ');
+    if (type.kind === 'bytes' || type.kind === 'string') {
+      offset += 4;
+      lengths.push(`${rustName}.len()`);
+      return `let ${rustName} = read_${type.kind}(data, ${at}, ${type.maxLen})?;`;
+    }
+    if (type.kind === 'vec') {
+      const size = scalarOf(type.of).size;
+      offset += 4;
+      lengths.push(size === 1 ? `${rustName}.len()` : `${rustName}.len() * ${size}`);
+      return `let ${rustName} = read_vec(data, ${at}, ${type.maxLen}, ${size}, read_${type.of.kind})?;`;
+    }
+    offset += scalarOf(type).size;
+    return `let ${rustName} = read_${type.kind}(data, ${at}).ok_or(${errorEnumName}::InvalidArgs)?;`;
   });
   const assigns = argEntries
     .map(([name]) => `        ${toSnake(name)},`)
     .join('\n');
-  return `fn decode_${toSnake(ix.name)}_args(data: &[u8]) -> Result<${structName}, ProgramError> {\n    ${reads.join('\n    ')}\n    Ok(${structName} {\n${assigns}\n    })\n}\n`;
+  const returned = hasVariableArgs(ix) ? `${structName}<'_>` : structName;
+  return `fn decode_${toSnake(ix.name)}_args(data: &[u8]) -> Result<${returned}, ProgramError> {\n    ${reads.join('\n    ')}\n    Ok(${structName} {\n${assigns}\n    })\n}\n`;
+};
+
+const renderVariableArgHelpers = () => {
+  const argKinds = new Set(
+    instructions.flatMap((ix) => Object.values(ix.args).map((type: any) => type.kind)),
+  );
+  if (!variableArgKinds.some((kind) => argKinds.has(kind))) return '';
+  // `expr.len` and `expr.at` on vec args go through ArgVec's `len` and `get`.
+  const vecArgExprs = (kind: string) =>
+    instructions.flatMap((ix) =>
+      collectRefs(ix.ops, kind).filter((expr) => expr.value?.kind === 'arg' && ix.args[expr.value.name]?.kind === 'vec'),
+    );
+  const usesLen = instructions.some((ix) => collectRefs(ix.ops, 'len').length > 0);
+  const usesVecGet = vecArgExprs('at').length > 0;
+  const usesVecLen = usesVecGet || vecArgExprs('len').length > 0;
+  const helpers = [`${usesLen ? `// Instruction data lengths fit in usize on every Solana target.
+fn arg_len(len: usize) -> u64 {
+    len as u64
+}
+
+` : ''}// Borsh-style length prefix: a little-endian u32 count, capped at \`max_len\`.
+fn read_len(data: &[u8], offset: usize, max_len: usize) -> Result<usize, ProgramError> {
+    let len = read_u32(data, offset).ok_or(${errorEnumName}::InvalidArgs)? as usize;
+    if len > max_len {
+        return Err(${errorEnumName}::InvalidArgs.into());
+    }
+    Ok(len)
+}
+`];
+  if (argKinds.has('bytes') || argKinds.has('string')) {
+    helpers.push(`fn read_bytes(data: &[u8], offset: usize, max_len: usize) -> Result<&[u8], ProgramError> {
+    let len = read_len(data, offset, max_len)?;
+    data.get(offset + 4..offset + 4 + len)
+        .ok_or(${errorEnumName}::InvalidArgs.into())
+}
+`);
+  }
+  if (argKinds.has('string')) {
+    helpers.push(`fn read_string(data: &[u8], offset: usize, max_len: usize) -> Result<&str, ProgramError> {
+    core::str::from_utf8(read_bytes(data, offset, max_len)?)
+        .map_err(|_| ${errorEnumName}::InvalidArgs.into())
+}
+`);
+  }
+  if (argKinds.has('vec')) {
+    helpers.push(`// A vec arg stays in the instruction data. That data has no alignment guarantee, so a
+// \`&[u64]\` cannot borrow it; elements are decoded on access instead.
+#[derive(Clone, Copy)]
+struct ArgVec<'a, T> {
+    data: &'a [u8],
+    size: usize,
+    read: fn(&[u8], usize) -> Option<T>,
+}
+
+${usesVecLen ? `impl<T> ArgVec<'_, T> {
+    fn len(&self) -> usize {
+        self.data.len() / self.size
+    }
+${usesVecGet ? `
+    fn get(&self, index: u64) -> Result<T, ProgramError> {
+        let index = usize::try_from(index).map_err(|_| ${errorEnumName}::IndexOutOfBounds)?;
+        if index >= self.len() {
+            return Err(${errorEnumName}::IndexOutOfBounds.into());
+        }
+        (self.read)(self.data, index * self.size).ok_or(${errorEnumName}::InvalidArgs.into())
+    }
+` : ''}}
+
+` : ''}fn read_vec<T>(
+    data: &[u8],
+    offset: usize,
+    max_len: usize,
+    size: usize,
+    read: fn(&[u8], usize) -> Option<T>,
+) -> Result<ArgVec<'_, T>, ProgramError> {
+    let len = read_len(data, offset, max_len)?;
+    let data = data
+        .get(offset + 4..offset + 4 + len * size)
+        .ok_or(${errorEnumName}::InvalidArgs)?;
+    Ok(ArgVec { data, size, read })
+}
+`);
+  }
+  return `${helpers.join('\n')}\n`;
 };
 
 const seedExpr = (seed: any) => {
@@ -191,11 +811,264 @@ const seedExpr = (seed: any) => {
     return `${toSnake(seed.name)}.key().as_ref()`;
   }
   if (seed.kind === 'field') {
-    return `${stateVarName}.${fieldMap.get(seed.name)}.as_ref()`;
+    return `${fieldRefToRust(seed)}.as_ref()`;
   }
   throw new Error(`Unsupported seed kind: ${seed.kind}`);
 };
 
+const knownProgramIds: Record<string, string> = {
+  token: 'TOKEN_PROGRAM_ID',
+  token2022: 'TOKEN_2022_PROGRAM_ID',
+  system: 'SYSTEM_PROGRAM_ID',
+  associatedToken: 'ASSOCIATED_TOKEN_PROGRAM_ID',
+};
+
+const pubkeyRefToRust = (ref: any) => {
+  if (typeof ref === 'string') {
+    const constName = knownProgramIds[ref];
+    if (!constName) {
+      throw new Error(`Unknown program address: ${ref}`);
+    }
+    return `&${constName}`;
+  }
+  if (ref.kind === 'arg') {
+    return `&args.${toSnake(ref.name)}`;
+  }
+  if (ref.kind === 'account') {
+    return `${toSnake(ref.name)}.key()`;
+  }
+  if (ref.kind === 'field') {
+    return `&${fieldRefToRust(ref)}`;
+  }
+  throw new Error(`Unsupported pubkey ref kind: ${ref.kind}`);
+};
+
+const usesAccountKind = (kind: string) =>
+  instructions.some((ix) => (ix.accounts as Array<any>).some((meta) => meta.kind === kind));
+
+// The pinocchio_tkn CPI struct behind each token op, with its account fields in struct
+// order as [Rust field, op key naming the account, role]. Roles say what kind of account
+// the op needs: `token` a writable ata, `mint`/`mintMut` a read-only/writable mint,
+// `target` the mint or ata whose authority changes, `lamports` any writable account.
+type TokenAccountRole = 'token' | 'mint' | 'mintMut' | 'target' | 'lamports' | 'any';
+
+const tokenOpSpecs: Record<string, { struct: string; accounts: Array<[string, string, TokenAccountRole]> }> = {
+  'token.transfer': {
+    struct: 'Transfer',
+    accounts: [['source', 'from', 'token'], ['destination', 'to', 'token'], ['authority', 'authority', 'any']],
+  },
+  'token.transferChecked': {
+    struct: 'TransferChecked',
+    accounts: [
+      ['source', 'from', 'token'],
+      ['mint', 'mint', 'mint'],
+      ['destination', 'to', 'token'],
+      ['authority', 'authority', 'any'],
+    ],
+  },
+  'token.mintTo': {
+    struct: 'MintTo',
+    accounts: [['mint', 'mint', 'mintMut'], ['destination', 'to', 'token'], ['authority', 'authority', 'any']],
+  },
+  'token.burn': {
+    struct: 'Burn',
+    accounts: [['source', 'from', 'token'], ['mint', 'mint', 'mintMut'], ['authority', 'authority', 'any']],
+  },
+  'token.approve': {
+    struct: 'Approve',
+    accounts: [['source', 'account', 'token'], ['delegate', 'delegate', 'any'], ['owner', 'authority', 'any']],
+  },
+  'token.revoke': { struct: 'Revoke', accounts: [['source', 'account', 'token'], ['owner', 'authority', 'any']] },
+  'token.setAuthority': {
+    struct: 'SetAuthority',
+    accounts: [['account', 'account', 'target'], ['current_authority', 'authority', 'any']],
+  },
+  'token.freezeAccount': {
+    struct: 'FreezeAccount',
+    accounts: [['account', 'account', 'token'], ['mint', 'mint', 'mint'], ['freeze_authority', 'authority', 'any']],
+  },
+  'token.thawAccount': {
+    struct: 'ThawAccount',
+    accounts: [['account', 'account', 'token'], ['mint', 'mint', 'mint'], ['freeze_authority', 'authority', 'any']],
+  },
+  'token.closeAccount': {
+    struct: 'CloseAccount',
+    accounts: [['account', 'account', 'token'], ['destination', 'to', 'lamports'], ['owner', 'authority', 'any']],
+  },
+  'token.syncNative': { struct: 'SyncNative', accounts: [['account', 'account', 'token']] },
+};
+
+const tokenOpKinds = Object.keys(tokenOpSpecs);
+
+const tokenAuthorityTypes = ['mintTokens', 'freezeAccount', 'accountOwner', 'closeAccount'];
+
+const tokenPrograms = ['token', 'token2022', 'either'];
+
+// The token program(s) an ata, mint or token program account may belong to. Undefined
+// for other accounts and for program accounts with a non-token address.
+const tokenProgramOf = (meta: any): string | undefined => {
+  if (meta.tokenProgram !== undefined) {
+    if (!tokenPrograms.includes(meta.tokenProgram)) {
+      throw new Error(`Account ${meta.name} tokenProgram must be one of ${tokenPrograms.join(', ')}.`);
+    }
+    if (!['ata', 'mint', 'program'].includes(meta.kind) || (meta.kind === 'program' && meta.address)) {
+      throw new Error(`Account ${meta.name}: tokenProgram only applies to ata, mint and token program accounts without an address.`);
+    }
+  }
+  if (meta.kind === 'ata' || meta.kind === 'mint') return meta.tokenProgram ?? programTokenProgram;
+  if (meta.kind !== 'program') return undefined;
+  if (meta.address === 'token' || meta.address === 'token2022') return meta.address;
+  return meta.address ? undefined : meta.tokenProgram ?? programTokenProgram;
+};
+
+const tokenOpProgram = (ix: any, op: any) =>
+  (ix.accounts as Array<any>).find((meta) => meta.name === (op.program ?? 'tokenProgram'));
+
+// Mint authorities live on the mint; owner and close authorities on the token account.
+const setAuthorityTargetKind = (authorityType: string) =>
+  authorityType === 'mintTokens' || authorityType === 'freezeAccount' ? 'mint' : 'ata';
+
+const tokenRoleKinds: Partial<Record<TokenAccountRole, string>> = { token: 'ata', mint: 'mint', mintMut: 'mint' };
+
+const checkTokenOpAccounts = (ix: any, op: any) => {
+  const refs: Array<[string, any, TokenAccountRole]> = [
+    ...tokenOpSpecs[op.op].accounts.map(([, key, role]): [string, any, TokenAccountRole] => [key, op[key], role]),
+    ['program', op.program ?? 'tokenProgram', 'any'],
+  ];
+  const metas = refs.map(([key, name]) => {
+    if (typeof name !== 'string') {
+      throw new Error(`${op.op} in ${ix.name} requires ${key}.`);
+    }
+    const meta = (ix.accounts as Array<any>).find((entry) => entry.name === name);
+    if (!meta) {
+      throw new Error(`${op.op} in ${ix.name}: ${key} references unknown account ${name}.`);
+    }
+    return meta;
+  });
+  refs.forEach(([key, name, role], index) => {
+    const meta = metas[index];
+    const kind = role === 'target' ? setAuthorityTargetKind(op.authorityType) : tokenRoleKinds[role];
+    if (kind && meta.kind !== kind) {
+      throw new Error(`${op.op} in ${ix.name}: ${key} ${name} must be ${kind === 'ata' ? 'an ata' : 'a mint'} account.`);
+    }
+    if (role !== 'mint' && role !== 'any' && !meta.writable) {
+      throw new Error(`${op.op} in ${ix.name}: ${key} ${name} must be writable.`);
+    }
+  });
+  const programMeta = tokenOpProgram(ix, op);
+  const opTokenProgram = tokenProgramOf(programMeta);
+  // Token-2022 mints with transfer fees or hooks reject a plain Transfer.
+  if (op.op === 'token.transfer' && (opTokenProgram === 'token2022' || opTokenProgram === 'either')) {
+    throw new Error(`token.transfer in ${ix.name} may run on Token-2022; use token.transferChecked instead.`);
+  }
+  metas.forEach((meta) => {
+    if (meta.kind !== 'ata' && meta.kind !== 'mint') return;
+    const accountTokenProgram = tokenProgramOf(meta);
+    if (!opTokenProgram || accountTokenProgram === opTokenProgram) return;
+    if (accountTokenProgram === 'either' || opTokenProgram === 'either') return;
+    throw new Error(
+      `${op.op} in ${ix.name}: ${meta.name} belongs to ${accountTokenProgram} but ${programMeta.name} is ${opTokenProgram}.`,
+    );
+  });
+};
+
+// A thaw needs its token account frozen; every other ata must be initialized and unfrozen.
+const expectedTokenState = (ix: any, meta: any) => {
+  const uses = (opName: string) =>
+    (ix.ops as Array<any>).some((op) => op.op === opName && op.account === meta.name);
+  if (!uses('token.thawAccount')) return 'Initialized';
+  if (uses('token.freezeAccount')) {
+    throw new Error(`${meta.name} in ${ix.name} cannot be both frozen and thawed.`);
+  }
+  return 'Frozen';
+};
+
+// Program accounts without an explicit address default to the program's token program
+// when a token op invokes them, so a caller can never swap in their own "token program".
+// The result may be 'either', which accepts both SPL Token and Token-2022.
+const resolveProgramAddress = (ix: any, meta: any) => {
+  if (meta.address) return meta.address;
+  const invokedByTokenOp = (ix.ops as Array<any>).some(
+    (op) => tokenOpKinds.includes(op.op) && (op.program ?? 'tokenProgram') === meta.name,
+  );
+  return invokedByTokenOp ? tokenProgramOf(meta) : undefined;
+};
+
+// The `&[Pubkey]` a check helper accepts for an address or token program choice.
+const programIdsToRust = (address: any) => {
+  if (address === 'either') return '&[TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID]';
+  if (typeof address === 'string') return `&[${pubkeyRefToRust(address).slice(1)}]`;
+  return `core::slice::from_ref(${pubkeyRefToRust(address)})`;
+};
+
+// Whether generated code names the SPL Token or Token-2022 program ID.
+const usesTokenProgramId = (id: 'token' | 'token2022') =>
+  instructions.some((ix) =>
+    (ix.accounts as Array<any>).some((meta) => {
+      const tokenProgram = meta.kind === 'program' ? resolveProgramAddress(ix, meta) : tokenProgramOf(meta);
+      return tokenProgram === id || tokenProgram === 'either';
+    }),
+  );
+
+const usesKnownProgram = (name: string) =>
+  instructions.some((ix) =>
+    (ix.accounts as Array<any>).some(
+      (meta) => meta.kind === 'program' && resolveProgramAddress(ix, meta) === name,
+    ),
+  );
+
+// Token account and mint constraints that only reference args/accounts can run right
+// after the accounts are bound; refs to state fields have to wait for the state load.
+const renderConstraintChecks = (ix: any, deferred: boolean) => {
+  const constraints: Array<{ name: string; kind: string; refs: any[]; check: string }> = [];
+  (ix.accounts as Array<any>).forEach((meta) => {
+    let refs: any[];
+    let check: string;
+    if (meta.kind === 'ata') {
+      if (!meta.owner || !meta.mint) {
+        throw new Error(`ata account ${meta.name} in ${ix.name} requires both owner and mint.`);
+      }
+      refs = [meta.owner, meta.mint];
+      check = `check_token_account(${toSnake(meta.name)}, ${pubkeyRefToRust(meta.owner)}, ${pubkeyRefToRust(meta.mint)}, ${programIdsToRust(tokenProgramOf(meta))}, AccountState::${expectedTokenState(ix, meta)})?;`;
+    } else if (meta.kind === 'mint') {
+      if (!meta.address) {
+        throw new Error(`mint account ${meta.name} in ${ix.name} requires an address.`);
+      }
+      refs = [meta.address];
+      check = `check_mint_account(${toSnake(meta.name)}, ${pubkeyRefToRust(meta.address)}, ${programIdsToRust(tokenProgramOf(meta))})?;`;
+    } else if (meta.kind === 'program') {
+      const address = resolveProgramAddress(ix, meta);
+      if (!address) return;
+      refs = [address];
+      check = `check_program_account(${toSnake(meta.name)}, ${programIdsToRust(address)})?;`;
+    } else {
+      return;
+    }
+    constraints.push({ name: meta.name, kind: meta.kind, refs, check });
+  });
+  equalityConstraints(ix).forEach(({ account, ref, error }) => {
+    if (!(ix.accounts as Array<any>).some((meta) => meta.name === account)) {
+      throw new Error(`Unknown account ${account} in hasOne constraint in ${ix.name}.`);
+    }
+    const { expr } = lowerExpr(ref, ix, new Map(), `${ix.name}.accounts.${account}`, 'pubkey', 'equals');
+    constraints.push({
+      name: account,
+      kind: 'equals',
+      refs: [ref],
+      check: `if *${toSnake(account)}.key() != ${exprToRust(expr)} {\n        return Err(${errorEnumName}::${error}.into());\n    }`,
+    });
+  });
+  const initAccounts = getInitAccounts(ix);
+  return constraints
+    .filter(({ name, kind, refs }) => {
+      if (refs.some((ref) => ref?.kind === 'field' && initAccounts.includes(ref.account))) {
+        throw new Error(`${kind} constraints cannot reference fields before state.init: ${name} in ${ix.name}.`);
+      }
+      return refs.some((ref) => ref?.kind === 'field') === deferred;
+    })
+    .map(({ check }) => check);
+};
+
 const renderAccountChecks = (ix: any) => {
   const list = ix.accounts as Array<any>;
   const checks: string[] = [];
@@ -214,7 +1087,7 @@ const renderAccountChecks = (ix: any) => {
       });
     }
     if (op.signer) neededBumps.add(op.signer);
-    ['from', 'to', 'authority', 'mint', 'program'].forEach((key) => {
+    ['from', 'to', 'authority', 'mint', 'account', 'delegate', 'program'].forEach((key) => {
       const value = op[key];
       if (typeof value === 'string') usedAccounts.add(value);
     });
@@ -227,20 +1100,26 @@ const renderAccountChecks = (ix: any) => {
       checks.push(`if !${name}.is_signer() { return Err(ProgramError::MissingRequiredSignature); }`);
     }
     if (meta.writable) {
-      checks.push(`if !${name}.is_writable() { return Err(ProgramError::InvalidAccountData); }`);
+      checks.push(`if !${name}.is_writable() { return Err(${errorEnumName}::AccountNotWritable.into()); }`);
     }
     if (meta.pda) {
       const seeds = meta.pda.seeds as Array<any>;
       const seedExprs = seeds.map(seedExpr).join(', ');
       const bumpVar = neededBumps.has(meta.name) ? `${name}_bump` : `_${name}_bump`;
       checks.push(`let (${name}_key, ${bumpVar}) = pubkey::find_program_address(&[${seedExprs}], program_id);`);
-      checks.push(`if ${name}.key() != &${name}_key { return Err(ProgramError::InvalidSeeds); }`);
+      checks.push(`if ${name}.key() != &${name}_key { return Err(${errorEnumName}::ConstraintSeeds.into()); }`);
     }
 
-    if (!meta.signer && !meta.writable && !meta.pda && !usedAccounts.has(meta.name)) {
+    const constrained =
+      meta.kind === 'ata' ||
+      meta.kind === 'mint' ||
+      (meta.kind === 'program' && resolveProgramAddress(ix, meta) !== undefined);
+    const isState = stateKeyOf(meta) !== undefined;
+    if (!meta.signer && !meta.writable && !meta.pda && !constrained && !isState && !usedAccounts.has(meta.name)) {
       checks.push(`let _ = ${name};`);
     }
   });
+  checks.push(...renderConstraintChecks(ix, false));
   return checks.join('\n    ');
 };
 
@@ -270,199 +1149,1111 @@ const renderSignerInit = (ix: any) => {
   return lines.join('\n    ');
 };
 
-const renderOps = (ix: any) => {
-  const ops = ix.ops as Array<any>;
-  const lines: string[] = [];
-  let stateLoaded = false;
-
-  const ensureStateLoaded = () => {
-    if (!stateLoaded) {
-      lines.push(`let mut ${stateVarName} = ${stateStructName}::load(${stateAccountVar})?;`);
-      stateLoaded = true;
-    }
-  };
-
-  const initAccounts = getInitAccounts(ix);
-  if (initAccounts.length > 1) {
-    throw new Error(`Multiple state.init ops are not supported in ${ix.name}.`);
-  }
-  const initAccountName = initAccounts[0];
-  const initMeta = initAccountName
-    ? (ix.accounts as Array<any>).find((meta) => meta.name === initAccountName)
-    : null;
-  const payerMeta = initAccountName ? findPayerAccount(ix) : null;
-
-  ops.forEach((op) => {
+// Lowers every expression slot of the handler's ops; locals get the type of their
+// compute value, in op order.
+const lowerOps = (ix: any) => {
+  const localTypes = new Map<string, string>();
+  return (ix.ops as Array<any>).map((op, opIndex) => {
+    const at = `${ix.name}.ops[${opIndex}]`;
+    const lower = (value: any, expected: string | undefined, role: string) =>
+      lowerExpr(value, ix, localTypes, at, expected, role);
     switch (op.op) {
-      case 'state.init': {
-        if (!initMeta) {
-          throw new Error(`state.init account ${initAccountName} not found in ${ix.name} accounts.`);
-        }
-        if (!payerMeta) {
-          throw new Error(`state.init in ${ix.name} requires a signer+writable payer account.`);
-        }
-        const initVar = toSnake(initMeta.name);
-        const payerVar = toSnake(payerMeta.name);
-        if (!initMeta.pda && !initMeta.signer) {
-          throw new Error(`state.init account ${initMeta.name} must be a PDA or signer in ${ix.name}.`);
-        }
-        if (initMeta.pda?.seeds?.some((seed: any) => seed?.kind === 'field')) {
-          throw new Error(`state.init PDA seeds cannot reference fields: ${initMeta.name} in ${ix.name}.`);
+      case 'require':
+        return { ...op, cond: lower(op.cond, 'bool', 'require condition').expr };
+      case 'compute': {
+        const { type, expr } = lower(op.value, undefined, `compute ${op.name}`);
+        localTypes.set(op.name, type);
+        return { ...op, value: expr };
+      }
+      case 'token.transfer':
+      case 'token.mintTo':
+      case 'token.burn':
+      case 'token.approve':
+        return { ...op, amount: lower(op.amount, 'u64', `${op.op} amount`).expr };
+      case 'token.transferChecked':
+        return {
+          ...op,
+          amount: lower(op.amount, 'u64', `${op.op} amount`).expr,
+          decimals: op.decimals && lower(op.decimals, 'u8', `${op.op} decimals`).expr,
+        };
+      case 'token.setAuthority': {
+        if (!tokenAuthorityTypes.includes(op.authorityType)) {
+          throw new Error(`${at}: authorityType must be one of ${tokenAuthorityTypes.join(', ')}.`);
         }
-        lines.push(`if !${initVar}.is_owned_by(program_id) {`);
-        if (initMeta.pda) {
-          const seedExprs = (initMeta.pda.seeds as Array<any>).map(seedExpr).join(', ');
-          lines.push(`    let ${initVar}_bump_ref = [${initVar}_bump];`);
-          lines.push(`    let ${initVar}_seeds = seeds!(${seedExprs}, &${initVar}_bump_ref);`);
-          lines.push(`    let ${initVar}_signer = Signer::from(&${initVar}_seeds);`);
-          lines.push(`    create_program_account(${payerVar}, ${initVar}, program_id, ${stateStructName}::LEN, Some(&${initVar}_signer))?;`);
-        } else {
-          lines.push(`    create_program_account(${payerVar}, ${initVar}, program_id, ${stateStructName}::LEN, None)?;`);
+        if (op.newAuthority === undefined) {
+          throw new Error(`${at}: token.setAuthority requires newAuthority; pass null to remove the authority.`);
         }
-        lines.push('}');
-        lines.push(`if !${initVar}.is_owned_by(program_id) { return Err(ProgramError::IncorrectProgramId); }`);
-        ensureStateLoaded();
-        Object.entries(op.fields).forEach(([fieldName, value]) => {
-          const fieldType = stateAccount.schema[fieldName];
-          const rustField = fieldMap.get(fieldName);
-          lines.push(`${stateVarName}.${rustField} = ${initValueToRust(value, fieldType)};`);
-        });
-        lines.push(`${stateStructName}::store(${stateAccountVar}, &${stateVarName})?;`);
-        break;
-      }
-      case 'state.update': {
-        ensureStateLoaded();
-        const updates = Object.entries(op.fields);
-        updates.forEach(([fieldName, expr]) => {
-          const rustField = fieldMap.get(fieldName);
-          lines.push(`let next_${rustField} = ${exprToRust(expr)};`);
-        });
-        updates.forEach(([fieldName]) => {
-          const rustField = fieldMap.get(fieldName);
-          lines.push(`${stateVarName}.${rustField} = next_${rustField};`);
-        });
-        lines.push(`${stateStructName}::store(${stateAccountVar}, &${stateVarName})?;`);
-        break;
+        const newAuthority =
+          op.newAuthority === null ? null : lower(op.newAuthority, 'pubkey', `${op.op} newAuthority`).expr;
+        return { ...op, newAuthority };
       }
-      case 'token.transfer': {
-        ensureStateLoaded();
-        if (op.signer) {
-          const signerVar = `${toSnake(op.signer)}_signer`;
-          lines.push(`Transfer {\n        source: ${toSnake(op.from)},\n        destination: ${toSnake(op.to)},\n        authority: ${toSnake(op.authority)},\n        amount: ${exprToRust(op.amount)},\n        program_id: Some(${toSnake(op.program ?? 'tokenProgram')}.key()),\n    }.invoke_signed(&[${signerVar}.clone()])?;`);
-        } else {
-          lines.push(`Transfer {\n        source: ${toSnake(op.from)},\n        destination: ${toSnake(op.to)},\n        authority: ${toSnake(op.authority)},\n        amount: ${exprToRust(op.amount)},\n        program_id: Some(${toSnake(op.program ?? 'tokenProgram')}.key()),\n    }.invoke()?;`);
-        }
-        break;
+      case 'view.return': {
+        const types: Record<string, string> = {};
+        const fields = Object.fromEntries(
+          Object.entries(op.fields).map(([name, value]: [string, any]) => {
+            const role = `${ix.name} return ${name}`;
+            if (value.kind === 'ratio') {
+              types[name] = 'ratio';
+              const numerator = lower(value.numerator, 'u64', `${role} numerator`).expr;
+              const denominator = lower(value.denominator, 'u64', `${role} denominator`).expr;
+              return [name, { ...value, numerator, denominator }];
+            }
+            if (!ix.view && scalarTypes[value.kind] && Object.keys(value).length === 1) {
+              throw new Error(`${at}: ${role} must be an expression or ratio(numerator, denominator).`);
+            }
+            const { type, expr } = lower(value, op.types[name], role);
+            if (!scalarTypes[type]) throw new Error(`${at}: ${role} must be a scalar, got ${type}.`);
+            types[name] = type;
+            return [name, expr];
+          }),
+        );
+        return { ...op, fields, types };
       }
-      case 'token.mintTo': {
-        ensureStateLoaded();
-        if (op.signer) {
-          const signerVar = `${toSnake(op.signer)}_signer`;
-          lines.push(`MintTo {\n        mint: ${toSnake(op.mint)},\n        destination: ${toSnake(op.to)},\n        authority: ${toSnake(op.authority)},\n        amount: ${exprToRust(op.amount)},\n        program_id: Some(${toSnake(op.program ?? 'tokenProgram')}.key()),\n    }.invoke_signed(&[${signerVar}.clone()])?;`);
-        } else {
-          lines.push(`MintTo {\n        mint: ${toSnake(op.mint)},\n        destination: ${toSnake(op.to)},\n        authority: ${toSnake(op.authority)},\n        amount: ${exprToRust(op.amount)},\n        program_id: Some(${toSnake(op.program ?? 'tokenProgram')}.key()),\n    }.invoke()?;`);
-        }
-        break;
+      case 'event': {
+        const event = programEvents.find((entry) => entry.name === op.name);
+        if (!event) throw new Error(`${at}: unknown event ${op.name}.`);
+        Object.keys(op.data).forEach((name) => {
+          if (!event.fields[name]) throw new Error(`${at}: event ${op.name} has no field ${name}.`);
+        });
+        const data = Object.fromEntries(
+          Object.entries(event.fields).map(([name, type]) => {
+            if (!op.data[name]) throw new Error(`${at}: event ${op.name} is missing ${name}.`);
+            return [name, lower(op.data[name], type.kind, `${op.name}.${name}`).expr];
+          }),
+        );
+        return { ...op, data };
       }
-      case 'token.burn': {
-        ensureStateLoaded();
-        lines.push(`Burn {\n        source: ${toSnake(op.from)},\n        mint: ${toSnake(op.mint)},\n        authority: ${toSnake(op.authority)},\n        amount: ${exprToRust(op.amount)},\n        program_id: Some(${toSnake(op.program ?? 'tokenProgram')}.key()),\n    }.invoke()?;`);
-        break;
+      case 'state.update':
+      case 'state.init': {
+        const { schema, structName } = findStateMeta(ix, op.account);
+        const fields = Object.fromEntries(
+          Object.entries(op.fields).map(([fieldName, value]: [string, any]) => {
+            const ref = parseFieldKey(op.account, fieldName);
+            const schemaType = fieldTypeAt(schema, ref);
+            if (schemaType?.kind === 'array' || schemaType?.kind === 'struct') {
+              throw new Error(`${at}: ${op.account}.${fieldName} is an ${schemaType.kind}; set one element or field.`);
+            }
+            const role = `${op.account}.${fieldName}`;
+            if (!schemaType) return [fieldName, value];
+            const fieldType = exprTypeOf(schemaType, nestedNameAt(structName, ref));
+            if (value.kind === 'bump') {
+              if (fieldType !== 'u8') throw new Error(`${at}: ${role} expects ${fieldType}, got u8.`);
+              return [fieldName, value];
+            }
+            return [fieldName, lower(value, fieldType, role).expr];
+          }),
+        );
+        return { ...op, fields };
       }
       default:
-        break;
+        return op;
     }
   });
+};
 
-  return lines.join('\n    ');
+// State accounts that the handler loads up front; accounts created by state.init are
+// bound by the init op itself.
+const loadedStateAccounts = (ix: any) => {
+  const initAccounts = new Set(getInitAccounts(ix));
+  return (ix.accounts as Array<any>).filter(
+    (meta) => stateKeyOf(meta) !== undefined && !initAccounts.has(meta.name),
+  );
 };
 
-const renderInstructionHandler = (ix: any) => {
-  const argsStruct = renderArgsStruct(ix);
-  const argsDecoder = renderArgsDecoder(ix);
-  const accountChecks = renderAccountChecks(ix);
+const updatedStateAccounts = (ix: any) =>
+  new Set(
+    (ix.ops as Array<any>)
+      .filter((op) => op.op === 'state.update')
+      .map((op) => op.account as string),
+  );
+
+// Two metas of the same account type must not alias when either is written, otherwise
+// the second store would overwrite the first with a stale copy.
+const renderStateAliasChecks = (ix: any) => {
+  const written = new Set([...updatedStateAccounts(ix), ...getInitAccounts(ix)]);
+  const stateMetas = (ix.accounts as Array<any>).filter((meta) => stateKeyOf(meta) !== undefined);
+  const checks: string[] = [];
+  stateMetas.forEach((meta, index) => {
+    stateMetas.slice(index + 1).forEach((other) => {
+      if (stateKeyOf(meta) !== stateKeyOf(other)) return;
+      if (!written.has(meta.name) && !written.has(other.name)) return;
+      checks.push(`    if ${toSnake(meta.name)}.key() == ${toSnake(other.name)}.key() {\n        return Err(${errorEnumName}::DuplicateMutableAccount.into());\n    }\n`);
+    });
+  });
+  return checks.join('');
+};
+
+const cseKinds = ['add', 'sub', 'mul', 'div', 'min', 'max', 'sqrt', 'mulDiv', 'bps', 'pow', 'cast', 'at', 'if'];
+
+const opExprSlots = (op: any): any[] => {
+  switch (op.op) {
+    case 'require':
+      return [op.cond];
+    case 'compute':
+      return [op.value];
+    case 'token.transfer':
+    case 'token.mintTo':
+    case 'token.burn':
+    case 'token.approve':
+      return [op.amount];
+    case 'token.transferChecked':
+      return op.decimals ? [op.amount, op.decimals] : [op.amount];
+    case 'token.setAuthority':
+      return op.newAuthority ? [op.newAuthority] : [];
+    case 'state.update':
+      return Object.values(op.fields);
+    case 'state.init':
+      return Object.values(op.fields).filter((value: any) => value.kind !== 'bump');
+    case 'event':
+      return Object.values(op.data);
+    case 'view.return':
+      return Object.values(op.fields).flatMap((value: any) =>
+        value.kind === 'ratio' ? [value.numerator, value.denominator] : [value],
+      );
+    default:
+      return [];
+  }
+};
+
+// Subtrees that always evaluate when their op runs. `if` branches and the right side of
+// `and`/`or` may be skipped, so hoisting them could fail a path that never evaluated them
+// (e.g. a division guarded by a zero check).
+const unconditionalSubtrees = (
+  expr: any,
+  ancestors: any[] = [],
+  out: Array<{ expr: any; ancestors: any[] }> = [],
+) => {
+  if (!expr || typeof expr !== 'object') return out;
+  let inner = ancestors;
+  if (cseKinds.includes(expr.kind)) {
+    out.push({ expr, ancestors });
+    inner = [...ancestors, expr];
+  }
+  let children: any[];
+  if (expr.kind === 'if') {
+    children = [expr.cond];
+  } else if (expr.kind === 'and' || expr.kind === 'or') {
+    children = [expr.left];
+  } else if (['not', 'cast', 'len', 'isSome', 'some'].includes(expr.kind)) {
+    children = [expr.value];
+  } else if (expr.kind === 'at') {
+    children = [expr.index];
+  } else if (expr.kind === 'div' && expr.left?.kind === 'mul') {
+    // Rendered as one checked_mul_div, so the product is never a value of its own.
+    children = [expr.left.left, expr.left.right, expr.right];
+  } else if (expr.kind === 'sqrt') {
+    children = expr.value.kind === 'mul' ? [expr.value.left, expr.value.right] : [expr.value];
+  } else {
+    children = [expr.left, expr.right, expr.divisor];
+  }
+  children.forEach((child) => unconditionalSubtrees(child, inner, out));
+  return out;
+};
+
+// Common subexpression elimination: picks the arithmetic trees a handler would evaluate
+// more than once and returns them keyed by the index of the first op that needs them.
+// A state.update starts a new version of its account, so trees reading it before and
+// after the write are different values.
+const planSharedExprs = (ix: any) => {
+  const versions = new Map<string, number>();
+  const versionedKey = (expr: any) =>
+    `${exprKey(expr)}@${[...fieldAccountsOf(expr)].map((name) => `${name}:${versions.get(name) ?? 0}`).join(',')}`;
+  const localKeys = new Set<string>();
+  const occurrences = new Map<string, Array<{ opIndex: number; expr: any; ancestors: string[] }>>();
+
+  (ix.ops as Array<any>).forEach((op, opIndex) => {
+    opExprSlots(op).forEach((slot) => {
+      unconditionalSubtrees(slot).forEach(({ expr, ancestors }) => {
+        // The compute op binds its own value.
+        if (op.op === 'compute' && expr === op.value) return;
+        const key = versionedKey(expr);
+        const ancestorKeys = ancestors.map(versionedKey);
+        if ([key, ...ancestorKeys].some((entry) => localKeys.has(entry))) return;
+        const entries = occurrences.get(key) ?? [];
+        entries.push({ opIndex, expr, ancestors: ancestorKeys });
+        occurrences.set(key, entries);
+      });
+    });
+    if (op.op === 'compute' && cseKinds.includes(op.value.kind)) {
+      localKeys.add(versionedKey(op.value));
+    }
+    if (op.op === 'state.update') {
+      versions.set(op.account, (versions.get(op.account) ?? 0) + 1);
+    }
+  });
+
+  // Largest trees first: an occurrence inside a shared ancestor is evaluated once, by the
+  // ancestor's binding.
+  const selected = new Set<string>();
+  [...occurrences.keys()]
+    .sort((a, b) => b.length - a.length)
+    .forEach((key) => {
+      const ancestorUses = new Set<string>();
+      let directUses = 0;
+      occurrences.get(key)!.forEach(({ ancestors }) => {
+        const shared = [...ancestors].reverse().find((ancestor) => selected.has(ancestor));
+        if (shared) {
+          ancestorUses.add(shared);
+        } else {
+          directUses += 1;
+        }
+      });
+      if (directUses + ancestorUses.size >= 2) selected.add(key);
+    });
+
+  const plan = new Map<number, any[]>();
+  [...selected]
+    .sort((a, b) => a.length - b.length)
+    .forEach((key) => {
+      const [{ opIndex, expr }] = occurrences.get(key)!;
+      plan.set(opIndex, [...(plan.get(opIndex) ?? []), expr]);
+    });
+  return plan;
+};
+
+const reservedLocalNames = (ix: any) =>
+  new Set([
+    'args',
+    'accounts',
+    'data',
+    'program_id',
+    ...(ix.accounts as Array<any>).flatMap((meta) => {
+      const name = toSnake(meta.name);
+      return [name, `${name}_state`, `${name}_bump`, `${name}_bump_ref`, `${name}_seeds`, `${name}_signer`, `${name}_initialized`];
+    }),
+  ]);
+
+// A token CPI through its pinocchio_tkn struct, signed with the PDA seeds from
+// renderSignerInit when the op names a signer.
+const renderTokenOp = (ix: any, op: any) => {
+  checkTokenOpAccounts(ix, op);
+  const { struct, accounts: accountFields } = tokenOpSpecs[op.op];
+  const newAuthority = (value: any) => {
+    if (value === null) return 'None';
+    return value.kind === 'account' ? `Some(${toSnake(value.name)}.key())` : `Some(&${exprToRust(value)})`;
+  };
+  const fields = [
+    ...accountFields.map(([field, key]) => (toSnake(op[key]) === field ? field : `${field}: ${toSnake(op[key])}`)),
+    ...(op.amount ? [`amount: ${exprToRust(op.amount)}`] : []),
+    ...(op.op === 'token.transferChecked'
+      ? [`decimals: ${op.decimals ? exprToRust(op.decimals) : `mint_decimals(${toSnake(op.mint)})?`}`]
+      : []),
+    ...(op.op === 'token.setAuthority'
+      ? [`authority_type: AuthorityType::${toPascal(op.authorityType)}`, `new_authority: ${newAuthority(op.newAuthority)}`]
+      : []),
+    `program_id: Some(${toSnake(op.program ?? 'tokenProgram')}.key())`,
+  ];
+  const invoke = op.signer ? `invoke_signed(core::slice::from_ref(&${toSnake(op.signer)}_signer))` : 'invoke()';
+  return `${struct} {\n${fields.map((field) => `        ${field},`).join('\n')}\n    }.${invoke}?;`;
+};
+
+const renderOps = (ix: any) => {
+  const lines: string[] = [];
+  const bound = new Set<string>();
+  const updated = updatedStateAccounts(ix);
+  const referenced = fieldAccountsOf([ix.accounts, ix.ops, equalityConstraints(ix)]);
+
+  validateFieldRefs(ix);
+
+  const ops = lowerOps(ix);
+  const reservedNames = reservedLocalNames(ix);
+  const locals = new Set<string>();
+  const sharedExprs = planSharedExprs({ ...ix, ops });
+  let sharedCount = 0;
+  exprBindings.clear();
+
+  const initAccounts = getInitAccounts(ix);
+  const duplicateInit = initAccounts.find((name, index) => initAccounts.indexOf(name) !== index);
+  if (duplicateInit) {
+    throw new Error(`Multiple state.init ops target ${duplicateInit} in ${ix.name}.`);
+  }
+  const payerMeta = initAccounts.length > 0 ? findPayerAccount(ix) : null;
+
+  loadedStateAccounts(ix).forEach((meta) => {
+    const structName = structNameFor(stateKeyOf(meta)!);
+    const accountVar = toSnake(meta.name);
+    if (updated.has(meta.name)) {
+      lines.push(`let mut ${stateVarOf(meta.name)} = ${structName}::load(${accountVar})?;`);
+    } else if (referenced.has(meta.name)) {
+      lines.push(`let ${stateVarOf(meta.name)} = ${structName}::load(${accountVar})?;`);
+    } else {
+      lines.push(`${structName}::load(${accountVar})?;`);
+    }
+    bound.add(meta.name);
+  });
+
+  lines.push(...renderConstraintChecks(ix, true));
+
+  ops.forEach((op, opIndex) => {
+    fieldAccountsOf(op.op === 'state.init' ? op.fields : op).forEach((name) => {
+      if (!bound.has(name)) {
+        throw new Error(`${op.op} in ${ix.name} reads ${name} before its state.init.`);
+      }
+    });
+    (sharedExprs.get(opIndex) ?? []).forEach((expr) => {
+      const name = `cse_${sharedCount++}`;
+      lines.push(`let ${name} = ${exprToRust(expr)};`);
+      exprBindings.set(exprKey(expr), { name, accounts: fieldAccountsOf(expr) });
+    });
+    switch (op.op) {
+      case 'state.init': {
+        const { meta: initMeta, key, schema, structName } = findStateMeta(ix, op.account);
+        if (!payerMeta) {
+          throw new Error(`state.init in ${ix.name} requires a signer+writable payer account.`);
+        }
+        const initVar = toSnake(initMeta.name);
+        const payerVar = toSnake(payerMeta.name);
+        const stateVar = stateVarOf(initMeta.name);
+        if (!initMeta.pda && !initMeta.signer) {
+          throw new Error(`state.init account ${initMeta.name} must be a PDA or signer in ${ix.name}.`);
+        }
+        if (initMeta.pda?.seeds?.some((seed: any) => seed?.kind === 'field')) {
+          throw new Error(`state.init PDA seeds cannot reference fields: ${initMeta.name} in ${ix.name}.`);
+        }
+        lines.push(`if !${initVar}.is_owned_by(program_id) {`);
+        if (initMeta.pda) {
+          const seedExprs = (initMeta.pda.seeds as Array<any>).map(seedExpr).join(', ');
+          lines.push(`    let ${initVar}_bump_ref = [${initVar}_bump];`);
+          lines.push(`    let ${initVar}_seeds = seeds!(${seedExprs}, &${initVar}_bump_ref);`);
+          lines.push(`    let ${initVar}_signer = Signer::from(&${initVar}_seeds);`);
+          lines.push(`    create_program_account(${payerVar}, ${initVar}, program_id, ${structName}::LEN, Some(&${initVar}_signer))?;`);
+        } else {
+          lines.push(`    create_program_account(${payerVar}, ${initVar}, program_id, ${structName}::LEN, None)?;`);
+        }
+        lines.push('}');
+        lines.push(`if !${initVar}.is_owned_by(program_id) { return Err(${errorEnumName}::ConstraintOwner.into()); }`);
+        const initFields = Object.entries(op.fields).map(([fieldName, value]) => {
+          if (!schema[fieldName]) {
+            throw new Error(`Unknown field ${fieldName} on ${key} in ${ix.name}.`);
+          }
+          return `${toSnake(fieldName)}: ${initValueToRust(value)},`;
+        });
+        const coversAllFields = Object.keys(schema).every((name) => name in op.fields);
+        const initLiteral = [
+          `${structName} {`,
+          ...initFields.map((line) => `    ${line}`),
+          ...(coversAllFields ? [] : [`    ..${structName}::default()`]),
+          '}',
+        ];
+        const laterUpdate = ops
+          .slice(opIndex + 1)
+          .some((next) => next.op === 'state.update' && next.account === op.account);
+        const binding = laterUpdate ? `let mut ${stateVar}` : `let ${stateVar}`;
+        const store = `${structName}::store(${initVar}, &${stateVar})?;`;
+        const mode = op.mode ?? 'init';
+        if (mode === 'init') {
+          lines.push(`if ${structName}::is_initialized(${initVar})? {`);
+          lines.push(`    return Err(${errorEnumName}::AccountAlreadyInitialized.into());`);
+          lines.push('}');
+          lines.push(`${binding} = ${initLiteral[0]}`, ...initLiteral.slice(1, -1), '};');
+          lines.push(store);
+        } else if (mode === 'initIfNeeded') {
+          const initializedVar = `${initVar}_initialized`;
+          lines.push(`let ${initializedVar} = ${structName}::is_initialized(${initVar})?;`);
+          lines.push(`${binding} = if ${initializedVar} {`);
+          lines.push(`    ${structName}::load(${initVar})?`);
+          lines.push('} else {');
+          initLiteral.forEach((line) => lines.push(`    ${line}`));
+          lines.push('};');
+          lines.push(`if !${initializedVar} {`);
+          lines.push(`    ${store}`);
+          lines.push('}');
+        } else {
+          throw new Error(`Unsupported state.init mode ${mode} in ${ix.name}.`);
+        }
+        bound.add(initMeta.name);
+        break;
+      }
+      case 'state.update': {
+        const { meta, key, schema, structName } = findStateMeta(ix, op.account);
+        if (!bound.has(meta.name)) {
+          throw new Error(`state.update in ${ix.name} targets ${meta.name} before its state.init.`);
+        }
+        const stateVar = stateVarOf(meta.name);
+        const updates = Object.entries(op.fields).map(([fieldName, expr]) => {
+          const ref = parseFieldKey(op.account, fieldName);
+          if (!fieldTypeAt(schema, ref)) {
+            throw new Error(`Unknown field ${fieldName} on ${key} in ${ix.name}.`);
+          }
+          const next = `next_${fieldName.split('.').map(toSnake).join('_')}`;
+          return { ref, expr, next };
+        });
+        updates.forEach(({ expr, next }) => {
+          lines.push(`let ${next} = ${exprToRust(expr)};`);
+        });
+        updates.forEach(({ ref, next }) => {
+          lines.push(`${fieldRefToRust(ref)} = ${next};`);
+        });
+        lines.push(`${structName}::store(${toSnake(meta.name)}, &${stateVar})?;`);
+        exprBindings.forEach((binding, key) => {
+          if (binding.accounts.has(meta.name)) exprBindings.delete(key);
+        });
+        break;
+      }
+      case 'compute': {
+        const local = toSnake(op.name);
+        if (!/^[a-z][A-Za-z0-9]*$/.test(op.name)) {
+          throw new Error(`Local ${op.name} in ${ix.name} must be camelCase.`);
+        }
+        if (locals.has(op.name)) {
+          throw new Error(`Duplicate local ${op.name} in ${ix.name}.`);
+        }
+        if (reservedNames.has(local) || local.startsWith('next_')) {
+          throw new Error(`Local ${op.name} in ${ix.name} clashes with a generated name.`);
+        }
+        lines.push(`let ${local} = ${exprToRust(op.value)};`);
+        if (cseKinds.includes(op.value.kind)) {
+          exprBindings.set(exprKey(op.value), { name: local, accounts: fieldAccountsOf(op.value) });
+        }
+        locals.add(op.name);
+        break;
+      }
+      case 'require': {
+        if (!programErrors.some((error) => error.name === op.error)) {
+          throw new Error(`require in ${ix.name} references undeclared error ${op.error}.`);
+        }
+        lines.push(`if ${negatedConditionToRust(op.cond)} {`);
+        lines.push(`    return Err(${errorEnumName}::${op.error}.into());`);
+        lines.push('}');
+        break;
+      }
+      case 'token.transfer':
+      case 'token.transferChecked':
+      case 'token.mintTo':
+      case 'token.burn':
+      case 'token.approve':
+      case 'token.revoke':
+      case 'token.setAuthority':
+      case 'token.freezeAccount':
+      case 'token.thawAccount':
+      case 'token.closeAccount':
+      case 'token.syncNative':
+        lines.push(renderTokenOp(ix, op));
+        break;
+      case 'view.return': {
+        const fields = Object.entries(op.fields).map(([name, value]: [string, any]) => {
+          const field = toSnake(name);
+          if (value.kind === 'ratio') {
+            return `${field}: Ratio {\n            numerator: ${exprToRust(value.numerator)},\n            denominator: ${exprToRust(value.denominator)},\n        }`;
+          }
+          const rust = exprToRust(value);
+          return rust === field ? field : `${field}: ${rust}`;
+        });
+        lines.push(`${returnStructName(ix)} {\n${fields.map((field) => `        ${field},`).join('\n')}\n    }.set_return_data()?;`);
+        break;
+      }
+      case 'event': {
+        const fields = Object.entries(op.data).map(([name, value]) => {
+          const field = toSnake(name);
+          const rust = exprToRust(value);
+          return rust === field ? field : `${field}: ${rust}`;
+        });
+        lines.push(`${op.name} {\n${fields.map((field) => `        ${field},`).join('\n')}\n    }.emit()?;`);
+        break;
+      }
+      default:
+        break;
+    }
+  });
+  exprBindings.clear();
+
+  const read = new Set(collectRefs(ops, 'local').map((ref) => ref.name as string));
+  locals.forEach((name) => {
+    if (!read.has(name)) {
+      throw new Error(`Local ${name} in ${ix.name} is never read.`);
+    }
+  });
+  // A view data arg only matters through its returns; one they never read is a typo.
+  if (ix.view) {
+    const readArgs = new Set(collectRefs(ops, 'arg').map((ref) => ref.name as string));
+    const unread = Object.keys(ix.args).find((name) => !readArgs.has(name));
+    if (unread) {
+      throw new Error(`View ${ix.name} arg ${unread} is not a state account and no return reads it.`);
+    }
+  }
+  return lines.join('\n    ');
+};
+
+const renderInstructionHandler = (ix: any) => {
+  const argsItems = hasArgs(ix) ? `${renderArgsStruct(ix)}\n${renderArgsDecoder(ix)}\n` : '';
+  const argsDecode = hasArgs(ix) ? `let args = decode_${toSnake(ix.name)}_args(data)?;\n    ` : '';
+  const accountChecks = renderAccountChecks(ix);
   const signerInit = renderSignerInit(ix);
   const ops = renderOps(ix);
-  const ownerCheck = hasStateInit(ix)
-    ? ''
-    : `    // State account must be owned by this program\n    if !${stateAccountVar}.is_owned_by(program_id) {\n        return Err(ProgramError::IncorrectProgramId);\n    }\n`;
+  const ownerCheck = loadedStateAccounts(ix)
+    .map((meta) => `    // State account must be owned by this program\n    if !${toSnake(meta.name)}.is_owned_by(program_id) {\n        return Err(${errorEnumName}::ConstraintOwner.into());\n    }\n`)
+    .join('');
+  const aliasCheck = renderStateAliasChecks(ix);
+  const accountCountCheck =
+    ix.accounts.length === 1 ? 'accounts.is_empty()' : `accounts.len() < ${ix.accounts.length}`;
 
-  return `\n${argsStruct}
-${argsDecoder}
-fn handle_${toSnake(ix.name)}(\n    program_id: &Pubkey,\n    accounts: &[AccountInfo],\n    data: &[u8],\n) -> ProgramResult {\n    if accounts.len() < ${ix.accounts.length} {\n        return Err(ProgramError::NotEnoughAccountKeys);\n    }\n    let args = decode_${toSnake(ix.name)}_args(data)?;\n    ${accountChecks}\n    ${signerInit}\n${ownerCheck}    ${ops}\n    Ok(())\n}\n`;
+  return `\n${argsItems}fn handle_${toSnake(ix.name)}(\n    program_id: &Pubkey,\n    accounts: &[AccountInfo],\n    ${hasArgs(ix) ? 'data' : '_data'}: &[u8],\n) -> ProgramResult {\n    if ${accountCountCheck} {\n        return Err(ProgramError::NotEnoughAccountKeys);\n    }\n    ${argsDecode}${accountChecks}\n    ${signerInit}\n${ownerCheck}${aliasCheck}    ${ops}\n    Ok(())\n}\n`;
 };
 
-const renderStateStruct = () => {
-  const fields = Object.entries(stateAccount.schema)
-    .map(([name, type]) => `    pub ${fieldMap.get(name)}: ${renderRustType(type)},`)
-    .join('\n');
+// Nested structs are named after their parent and field: `PoolState.tier` is `PoolStateTier`.
+const nestedStructName = (parent: string, fieldName: string) => `${parent}${toPascal(fieldName)}`;
 
-  const readFields: string[] = [];
-  let offset = 0;
-  Object.entries(stateAccount.schema).forEach(([name, type]) => {
-    const rustField = fieldMap.get(name);
-    if (type.kind === 'pubkey') {
-      readFields.push(`let ${rustField} = read_pubkey(&data, ${offset})?;`);
-      offset += 32;
-    } else if (type.kind === 'u64') {
-      readFields.push(`let ${rustField} = read_u64(&data, ${offset})?;`);
-      offset += 8;
-    } else if (type.kind === 'u8') {
-      readFields.push(`let ${rustField} = read_u8(&data, ${offset})?;`);
-      offset += 1;
+const elementOf = (type: any): any => (type.kind === 'array' ? elementOf(type.of) : type);
+
+const sizeOf = (type: any): number => {
+  if (type.kind === 'array') return type.len * sizeOf(type.of);
+  if (type.kind === 'enum') return 1;
+  if (type.kind === 'option') return 1 + sizeOf(type.of);
+  if (type.kind === 'struct') {
+    return Object.values(type.fields).reduce((sum: number, fieldType) => sum + sizeOf(fieldType), 0);
+  }
+  return scalarOf(type).size;
+};
+
+const scalarKindsOf = (type: any): string[] => {
+  if (type.kind === 'array') return scalarKindsOf(type.of);
+  if (type.kind === 'struct') return Object.values(type.fields).flatMap(scalarKindsOf);
+  if (type.kind === 'option') return scalarKindsOf(type.of);
+  if (type.kind === 'enum') return [];
+  return [type.kind];
+};
+
+// Arrays stop at 32 elements, the largest length `Default` is implemented for.
+const validateSchemaType = (type: any, described: string) => {
+  if (type.kind === 'array') {
+    if (!Number.isInteger(type.len) || type.len < 1 || type.len > 32) {
+      throw new Error(`${described} array length must be 1 to 32, got ${type.len}.`);
     }
-  });
+    validateSchemaType(type.of, `${described}[]`);
+  } else if (type.kind === 'struct') {
+    if (Object.keys(type.fields).length === 0) throw new Error(`${described} struct has no fields.`);
+    Object.entries(type.fields).forEach(([name, fieldType]) => {
+      if (name === 'data' || name === 'offset') {
+        throw new Error(`${described}.${name}: nested struct fields cannot be named data or offset.`);
+      }
+      validateSchemaType(fieldType, `${described}.${name}`);
+    });
+  } else if (type.kind === 'enum') {
+    const variants = type.variants ?? [];
+    if (variants.length < 1 || variants.length > 256) {
+      throw new Error(`${described} enum needs 1 to 256 variants, got ${variants.length}.`);
+    }
+    variants.forEach((variant: string, index: number) => {
+      if (!/^[A-Z][A-Za-z0-9]*$/.test(variant)) {
+        throw new Error(`${described} variant ${variant} must be PascalCase.`);
+      }
+      if (variants.indexOf(variant) !== index) throw new Error(`${described} repeats variant ${variant}.`);
+    });
+  } else if (type.kind === 'option') {
+    if (type.of.kind !== 'enum') scalarOf(type.of);
+    validateSchemaType(type.of, described);
+  } else {
+    scalarOf(type);
+  }
+};
+
+const renderFieldType = (type: any, structName: string): string => {
+  if (type.kind === 'array') return `[${renderFieldType(type.of, structName)}; ${type.len}]`;
+  if (type.kind === 'struct' || type.kind === 'enum') return structName;
+  if (type.kind === 'option') return `Option<${renderFieldType(type.of, structName)}>`;
+  return renderRustType(type);
+};
+
+const offsetAt = (base: number | string, delta: number) => {
+  if (typeof base === 'number') return `${base + delta}`;
+  return delta === 0 ? base : `${base} + ${delta}`;
+};
+
+const loopNames = (depth: number) =>
+  depth === 0 ? ['index', 'item'] : [`index_${depth}`, `item_${depth}`];
+
+// Decodes `type` at `offset` into `place`; array elements are filled in place through
+// `iter_mut`, so `isRef` marks a `&mut` element that has to be written through `*`.
+const renderRead = (
+  type: any,
+  place: string,
+  isRef: boolean,
+  offset: string,
+  structName: string,
+  data: string,
+  depth = 0,
+): string[] => {
+  if (type.kind === 'array') {
+    const [index, item] = loopNames(depth);
+    const size = sizeOf(type.of);
+    const elementOffset = `${offset} + ${size === 1 ? index : `${index} * ${size}`}`;
+    return [
+      `for (${index}, ${item}) in ${place}.iter_mut().enumerate() {`,
+      ...renderRead(type.of, item, true, elementOffset, structName, data, depth + 1).map((line) => `    ${line}`),
+      '}',
+    ];
+  }
+  const reader = (scalar: any) =>
+    scalar.kind === 'struct' || scalar.kind === 'enum' ? `${structName}::read` : `read_${scalar.kind}`;
+  const value =
+    type.kind === 'option'
+      ? `read_option(${data}, ${offset}, ${reader(type.of)})?`
+      : `${reader(type)}(${data}, ${offset})?`;
+  return [`${isRef ? '*' : ''}${place} = ${value};`];
+};
 
-  const writeFields: string[] = [];
-  let writeOffset = 0;
-  Object.entries(stateAccount.schema).forEach(([name, type]) => {
-    const rustField = fieldMap.get(name);
-    if (type.kind === 'pubkey') {
-      writeFields.push(`write_pubkey(&mut data, ${writeOffset}, &state.${rustField})?;`);
-      writeOffset += 32;
-    } else if (type.kind === 'u64') {
-      writeFields.push(`write_u64(&mut data, ${writeOffset}, state.${rustField})?;`);
-      writeOffset += 8;
-    } else if (type.kind === 'u8') {
-      writeFields.push(`write_u8(&mut data, ${writeOffset}, state.${rustField})?;`);
-      writeOffset += 1;
+const renderWrite = (
+  type: any,
+  place: string,
+  isRef: boolean,
+  offset: string,
+  structName: string,
+  data: string,
+  depth = 0,
+): string[] => {
+  if (type.kind === 'array') {
+    const [index, item] = loopNames(depth);
+    const size = sizeOf(type.of);
+    const elementOffset = `${offset} + ${size === 1 ? index : `${index} * ${size}`}`;
+    return [
+      `for (${index}, ${item}) in ${place}.iter().enumerate() {`,
+      ...renderWrite(type.of, item, true, elementOffset, structName, data, depth + 1).map((line) => `    ${line}`),
+      '}',
+    ];
+  }
+  if (type.kind === 'struct' || type.kind === 'enum') return [`${place}.write(${data}, ${offset})?;`];
+  if (type.kind === 'option') {
+    const writer =
+      type.of.kind === 'enum'
+        ? '|data, offset, value| value.write(data, offset)'
+        : type.of.kind === 'pubkey'
+          ? '|data, offset, value| write_pubkey(data, offset, &value)'
+          : `write_${type.of.kind}`;
+    return [`write_option(${data}, ${offset}, ${sizeOf(type.of)}, ${isRef ? `*${place}` : place}, ${writer})?;`];
+  }
+  let value = isRef ? `*${place}` : place;
+  if (type.kind === 'pubkey') value = isRef ? place : `&${place}`;
+  return [`write_${type.kind}(${data}, ${offset}, ${value})?;`];
+};
+
+// Binds one local per field, read from `base` onwards, for a `Self { .. }` literal.
+const renderFieldReads = (schema: Record<string, any>, parent: string, base: number | string, data: string) => {
+  const lines: string[] = [];
+  let delta = 0;
+  Object.entries(schema).forEach(([name, type]) => {
+    const rustField = toSnake(name);
+    const structName = nestedStructName(parent, name);
+    const offset = offsetAt(base, delta);
+    if (type.kind === 'array') {
+      lines.push(`let mut ${rustField} = <${renderFieldType(type, structName)}>::default();`);
+      lines.push(...renderRead(type, rustField, false, offset, structName, data));
+    } else {
+      const [read] = renderRead(type, rustField, false, offset, structName, data);
+      lines.push(`let ${read}`);
     }
+    delta += sizeOf(type);
   });
+  return { lines, size: delta };
+};
 
-  return `#[derive(Clone, Copy)]
-struct ${stateStructName} {
-${fields}
+const renderFieldWrites = (schema: Record<string, any>, parent: string, base: number | string, data: string, owner: string) => {
+  const lines: string[] = [];
+  let delta = 0;
+  Object.entries(schema).forEach(([name, type]) => {
+    const structName = nestedStructName(parent, name);
+    lines.push(...renderWrite(type, `${owner}.${toSnake(name)}`, false, offsetAt(base, delta), structName, data));
+    delta += sizeOf(type);
+  });
+  return lines;
+};
+
+const renderStructFields = (schema: Record<string, any>, parent: string) =>
+  Object.entries(schema)
+    .map(([name, type]) => `    pub ${toSnake(name)}: ${renderFieldType(type, nestedStructName(parent, name))},`)
+    .join('\n');
+
+const collectNestedStructs = (
+  parent: string,
+  schema: Record<string, any>,
+  out: Array<{ name: string; fields: Record<string, any> }> = [],
+) => {
+  Object.entries(schema).forEach(([name, type]) => {
+    const element = elementOf(type);
+    if (element.kind !== 'struct') return;
+    const structName = nestedStructName(parent, name);
+    out.push({ name: structName, fields: element.fields });
+    collectNestedStructs(structName, element.fields, out);
+  });
+  return out;
+};
+
+const collectEnums = (parent: string, schema: Record<string, any>, out: Array<{ name: string; variants: string[] }> = []) => {
+  Object.entries(schema).forEach(([name, type]) => {
+    let element = elementOf(type);
+    if (element.kind === 'option') element = element.of;
+    const nestedName = nestedStructName(parent, name);
+    if (element.kind === 'enum') out.push({ name: nestedName, variants: element.variants });
+    if (element.kind === 'struct') collectEnums(nestedName, element.fields, out);
+  });
+  return out;
+};
+
+// Enum types by Rust name, for typing `expr.variant` against the field it meets.
+const enumVariants = new Map(
+  Object.keys(accounts).flatMap((accountKey) =>
+    collectEnums(structNameFor(accountKey), accounts[accountKey].schema).map(({ name, variants }) => [name, variants]),
+  ),
+);
+
+// Tags follow declaration order; load rejects any other tag byte.
+const renderEnum = ({ name, variants }: { name: string; variants: string[] }) => `#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ${name} {
+${variants.map((variant, index) => `${index === 0 ? '    #[default]\n' : ''}    ${variant},`).join('\n')}
 }
 
-impl ${stateStructName} {
-    const LEN: usize = ${writeOffset};
+impl ${name} {
+    fn read(data: &[u8], offset: usize) -> Option<Self> {
+        match data.get(offset) {
+${variants.map((variant, index) => `            Some(${index}) => Some(Self::${variant}),`).join('\n')}
+            _ => None,
+        }
+    }
 
-    fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+    fn write(&self, data: &mut [u8], offset: usize) -> Result<(), ProgramError> {
+        let Some(tag) = data.get_mut(offset) else {
+            return Err(${errorEnumName}::InvalidAccountLayout.into());
+        };
+        *tag = *self as u8;
+        Ok(())
+    }
+}
+`;
+
+const renderNestedStruct = ({ name, fields }: { name: string; fields: Record<string, any> }) => {
+  const reads = renderFieldReads(fields, name, 'offset', 'data');
+  const writes = renderFieldWrites(fields, name, 'offset', 'data', 'self');
+  return `#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ${name} {
+${renderStructFields(fields, name)}
+}
+
+impl ${name} {
+    fn read(data: &[u8], offset: usize) -> Option<Self> {
+        ${reads.lines.join('\n        ')}
+        Some(Self {
+${Object.keys(fields).map((field) => `            ${toSnake(field)},`).join('\n')}
+        })
+    }
+
+    fn write(&self, data: &mut [u8], offset: usize) -> Result<(), ProgramError> {
+        ${writes.join('\n        ')}
+        Ok(())
+    }
+}
+`;
+};
+
+// `is_initialized`, `load` and `store` are emitted only when a handler calls them, so
+// clippy still flags any other helper that goes unused.
+const renderStateStruct = (accountKey: string, handlers: string) => {
+  const account = accounts[accountKey];
+  const structName = structNameFor(accountKey);
+  Object.entries(account.schema).forEach(([name, type]) => validateSchemaType(type, `${structName}.${name}`));
+  const fields = renderStructFields(account.schema, structName);
+
+  const discriminator = account.discriminator as number[];
+  const { lines: readFields, size } = renderFieldReads(account.schema, structName, discriminator.length, 'data');
+  const writeFields = renderFieldWrites(account.schema, structName, discriminator.length, 'data', 'self');
+  const nestedStructs = [
+    ...collectEnums(structName, account.schema).map(renderEnum),
+    ...collectNestedStructs(structName, account.schema).map(renderNestedStruct),
+  ].join('\n');
+  const calls = (method: string) => handlers.includes(`${structName}::${method}(`);
+  const accessors = [
+    calls('is_initialized')
+      ? `    // Freshly allocated accounts are zeroed, so any non-zero discriminator byte
+    // means the account was initialized, possibly as another account type.
+    fn is_initialized(account: &AccountInfo) -> Result<bool, ProgramError> {
         let data = account.try_borrow_data()?;
+        Ok(data.iter().take(Self::DISCRIMINATOR.len()).any(|byte| *byte != 0))
+    }
+`
+      : '',
+    calls('load')
+      ? `    fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = account.try_borrow_data()?;
+        Self::unpack(&data)
+    }
+`
+      : '',
+    calls('store')
+      ? `    fn store(account: &AccountInfo, state: &Self) -> Result<(), ProgramError> {
+        let mut data = account.try_borrow_mut_data()?;
+        state.pack(&mut data)
+    }
+`
+      : '',
+  ]
+    .filter(Boolean)
+    .join('\n');
+
+  return `${nestedStructs ? `${nestedStructs}\n` : ''}#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ${structName} {
+${fields}
+}
+
+impl ${structName} {
+    pub const LEN: usize = ${discriminator.length + size};
+    pub const DISCRIMINATOR: [u8; ${discriminator.length}] = [${discriminator.join(', ')}];
+
+${accessors ? `${accessors}
+` : ''}    /// Decodes account data as \`load\` does, so host code can read raw account bytes.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
         if data.len() < Self::LEN {
             return Err(ProgramError::AccountDataTooSmall);
         }
-        ${readFields.join('\n        ')}
-        Ok(Self {
-${Object.keys(stateAccount.schema).map((name) => `            ${fieldMap.get(name)},`).join('\n')}
-        })
+        if data[..Self::DISCRIMINATOR.len()] != Self::DISCRIMINATOR {
+            return Err(${errorEnumName}::AccountDiscriminatorMismatch.into());
+        }
+        Self::read(data).ok_or(${errorEnumName}::InvalidAccountLayout.into())
     }
 
-    fn store(account: &AccountInfo, state: &Self) -> Result<(), ProgramError> {
-        let mut data = account.try_borrow_mut_data()?;
+    /// Writes the discriminator and fields to the first \`LEN\` bytes, as \`store\` does.
+    pub fn pack(&self, data: &mut [u8]) -> Result<(), ProgramError> {
         if data.len() < Self::LEN {
             return Err(ProgramError::AccountDataTooSmall);
         }
+        data[..Self::DISCRIMINATOR.len()].copy_from_slice(&Self::DISCRIMINATOR);
         ${writeFields.join('\n        ')}
         Ok(())
     }
+
+    fn read(data: &[u8]) -> Option<Self> {
+        ${readFields.join('\n        ')}
+        Some(Self {
+${Object.keys(account.schema).map((name) => `            ${toSnake(name)},`).join('\n')}
+        })
+    }
+}
+`;
+};
+
+// Type names the crate defines besides events and return structs.
+const generatedTypeNames = () => [
+  errorEnumName,
+  'ArgVec',
+  'Ratio',
+  ...Object.keys(accounts).flatMap((key) => [
+    structNameFor(key),
+    ...collectNestedStructs(structNameFor(key), accounts[key].schema).map((nested) => nested.name),
+    ...collectEnums(structNameFor(key), accounts[key].schema).map((nested) => nested.name),
+  ]),
+  ...instructions.filter(hasArgs).map((ix) => `${toPascal(ix.name)}Args`),
+];
+
+// Names of the events some `event` op logs; only those get an `emit`.
+const emittedEvents = () =>
+  new Set(
+    instructions.flatMap((ix) =>
+      (ix.ops as Array<any>).filter((op) => op.op === 'event').map((op) => op.name as string),
+    ),
+  );
+
+// Events log `DISCRIMINATOR ++ fields` with `sol_log_data`, which runtimes print as
+// `Program data: <base64>`. `decode` is the host-side inverse for tests and clients.
+const renderEvent = (event: { name: string; fields: Record<string, any>; discriminator: number[] }) => {
+  if (generatedTypeNames().includes(event.name)) {
+    throw new Error(`Event ${event.name} clashes with a generated type name.`);
+  }
+  const fields = Object.entries(event.fields);
+  fields.forEach(([name, type]) => {
+    if (!scalarTypes[type.kind]) throw new Error(`Event ${event.name}.${name} has unsupported type ${type.kind}.`);
+  });
+  let offset = event.discriminator.length;
+  const layout = fields.map(([name, type]) => {
+    const at = offset;
+    offset += scalarOf(type).size;
+    return { field: toSnake(name), kind: type.kind, at };
+  });
+  return `#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ${event.name} {
+${layout.map(({ field }, index) => `    pub ${field}: ${renderRustType(fields[index][1])},`).join('\n')}
+}
+
+impl ${event.name} {
+    pub const DISCRIMINATOR: [u8; ${event.discriminator.length}] = [${event.discriminator.join(', ')}];
+    pub const LEN: usize = ${offset};
+
+${
+  emittedEvents().has(event.name)
+    ? `    fn emit(&self) -> ProgramResult {
+        let mut data = [0u8; Self::LEN];
+        data[..Self::DISCRIMINATOR.len()].copy_from_slice(&Self::DISCRIMINATOR);
+        ${layout.map(({ field, kind, at }) => `write_${kind}(&mut data, ${at}, ${kind === 'pubkey' ? '&' : ''}self.${field})?;`).join('\n        ')}
+        pinocchio::log::sol_log_data(&[&data]);
+        Ok(())
+    }
+
+`
+    : ''
+}    /// Decodes one \`sol_log_data\` payload; \`None\` if it is another event.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::LEN || data[..Self::DISCRIMINATOR.len()] != Self::DISCRIMINATOR {
+            return None;
+        }
+        Some(Self {
+${layout.map(({ field, kind, at }) => `            ${field}: read_${kind}(data, ${at})?,`).join('\n')}
+        })
+    }
 }
 `;
 };
 
-const renderInitHelpers = () => `const SYSTEM_PROGRAM_ID: Pubkey = [0u8; 32];
+const returningInstructions = () => instructions.filter((ix) => ix.view || ix.returns);
+
+// Views are named for what they return; a mutating instruction's return struct gets a suffix.
+const returnStructName = (ix: any) => (ix.view ? toPascal(ix.name) : `${toPascal(ix.name)}Return`);
+
+const returnTypes = (ix: any): Record<string, string> =>
+  lowerOps(ix).find((op: any) => op.op === 'view.return').types;
+
+const renderRatio = () => `#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ratio {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+`;
 
-fn minimum_balance(space: usize) -> u64 {
+// Return data is packed like event fields, without a discriminator; a ratio is its
+// numerator then its denominator.
+const renderReturnStruct = (ix: any, types: Record<string, string>) => {
+  const structName = returnStructName(ix);
+  const taken = [
+    ...generatedTypeNames(),
+    ...programEvents.map((event) => event.name),
+    ...returningInstructions().filter((other) => other !== ix).map(returnStructName),
+  ];
+  if (taken.includes(structName)) {
+    throw new Error(`${ix.name} return type ${structName} clashes with another generated type name.`);
+  }
+  let offset = 0;
+  const layout = Object.entries(types as Record<string, string>).map(([name, type]) => {
+    const at = offset;
+    offset += type === 'ratio' ? 16 : scalarTypes[type].size;
+    return { field: toSnake(name), type, at };
+  });
+  if (offset > 1024) {
+    throw new Error(`${ix.name} returns ${offset} bytes; return data is capped at 1024.`);
+  }
+  const writes = layout.flatMap(({ field, type, at }) =>
+    type === 'ratio'
+      ? [
+          `write_u64(&mut data, ${at}, self.${field}.numerator)?;`,
+          `write_u64(&mut data, ${at + 8}, self.${field}.denominator)?;`,
+        ]
+      : [`write_${type}(&mut data, ${at}, ${type === 'pubkey' ? '&' : ''}self.${field})?;`],
+  );
+  const reads = layout.map(({ field, type, at }) =>
+    type === 'ratio'
+      ? `${field}: Ratio {\n                numerator: read_u64(&data, ${at})?,\n                denominator: read_u64(&data, ${at + 8})?,\n            },`
+      : `${field}: read_${type}(&data, ${at})?,`,
+  );
+  return `#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ${structName} {
+${layout.map(({ field, type }) => `    pub ${field}: ${type === 'ratio' ? 'Ratio' : renderRustType({ kind: type })},`).join('\n')}
+}
+
+impl ${structName} {
+    pub const LEN: usize = ${offset};
+
+    fn set_return_data(&self) -> ProgramResult {
+        let mut data = [0u8; Self::LEN];
+        ${writes.join('\n        ')}
+        pinocchio::cpi::set_return_data(&data);
+        Ok(())
+    }
+
+    /// Decodes the return data of \`${ix.name}\`. RPC nodes strip trailing zero bytes,
+    /// so shorter input is zero-padded.
+    pub fn decode(return_data: &[u8]) -> Option<Self> {
+        if return_data.len() > Self::LEN {
+            return None;
+        }
+        let mut data = [0u8; Self::LEN];
+        data[..return_data.len()].copy_from_slice(return_data);
+        Some(Self {
+            ${reads.join('\n            ')}
+        })
+    }
+}
+`;
+};
+
+// Legacy mints and token accounts have a fixed size. Token-2022 appends extensions after
+// an account type byte (1 = mint, 2 = token account) at the token account size.
+const renderTokenLayoutHelper = () => `fn check_token_layout(data: &[u8], base_len: usize, account_type: u8) -> ProgramResult {
+    if data.len() == base_len || (data.len() > TOKEN_ACCOUNT_SIZE && data[TOKEN_ACCOUNT_SIZE] == account_type) {
+        return Ok(());
+    }
+    Err(${errorEnumName}::InvalidAccountLayout.into())
+}
+`;
+
+const renderTokenAccountHelper = () => `fn check_token_account(account: &AccountInfo, owner: &Pubkey, mint: &Pubkey, token_programs: &[Pubkey], state: AccountState) -> ProgramResult {
+    if !token_programs.iter().any(|token_program| account.is_owned_by(token_program)) {
+        return Err(${errorEnumName}::ConstraintOwner.into());
+    }
+    let data = account.try_borrow_data()?;
+    check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2)?;
+    let token_account = TokenAccount::from_bytes(&data)?;
+    if token_account.mint() != mint {
+        return Err(${errorEnumName}::ConstraintMint.into());
+    }
+    if token_account.owner() != owner {
+        return Err(${errorEnumName}::ConstraintTokenOwner.into());
+    }
+    // \`from_bytes\` already rejects uninitialized accounts.
+    if token_account.state() != state {
+        return Err(${errorEnumName}::ConstraintTokenState.into());
+    }
+    Ok(())
+}
+`;
+
+const renderMintAccountHelper = () => `fn check_mint_account(account: &AccountInfo, address: &Pubkey, token_programs: &[Pubkey]) -> ProgramResult {
+    if account.key() != address {
+        return Err(${errorEnumName}::ConstraintAddress.into());
+    }
+    if !token_programs.iter().any(|token_program| account.is_owned_by(token_program)) {
+        return Err(${errorEnumName}::ConstraintOwner.into());
+    }
+    let data = account.try_borrow_data()?;
+    check_token_layout(&data, MINT_SIZE, 1)?;
+    Mint::from_bytes(&data)?;
+    Ok(())
+}
+`;
+
+const renderProgramAccountHelper = () => `fn check_program_account(account: &AccountInfo, program_ids: &[Pubkey]) -> ProgramResult {
+    if !program_ids.contains(account.key()) {
+        return Err(${errorEnumName}::ConstraintProgram.into());
+    }
+    if !account.executable() {
+        return Err(${errorEnumName}::ProgramNotExecutable.into());
+    }
+    Ok(())
+}
+`;
+
+const renderMintDecimalsHelper = () => `fn mint_decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    let data = mint.try_borrow_data()?;
+    Ok(Mint::from_bytes(&data)?.decimals())
+}
+`;
+
+const renderProgramIds = (usesSystemProgram: boolean, usesAssociatedTokenProgram: boolean) => {
+  const ids: string[] = [];
+  if (usesSystemProgram) {
+    ids.push('const SYSTEM_PROGRAM_ID: Pubkey = [0u8; 32];');
+  }
+  if (usesAssociatedTokenProgram) {
+    ids.push('const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pinocchio_pubkey::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");');
+  }
+  return ids.length > 0 ? `${ids.join('\n')}\n` : '';
+};
+
+const renderInitHelpers = () => `fn minimum_balance(space: usize) -> u64 {
     if let Ok(rent) = Rent::get() {
         return rent.minimum_balance(space);
     }
@@ -502,177 +2293,679 @@ fn create_program_account(
 }
 `;
 
-const renderLib = () => {
-  const handlers = instructions.map(renderInstructionHandler).join('\n');
-  const dispatchArms = instructions
-    .map((ix) => `        ${ix.discriminator} => handle_${toSnake(ix.name)}(program_id, accounts, &data[1..]),`)
-    .join('\n');
-  const usesInit = instructions.some((ix) => hasStateInit(ix));
-  const pinocchioImports = usesInit
-    ? `use pinocchio::{
-    account_info::AccountInfo,
-    cpi::invoke_signed,
-    instruction::{AccountMeta, Instruction, Signer},
-    program_entrypoint,
-    program_error::ProgramError,
-    pubkey,
-    pubkey::Pubkey,
-    seeds,
-    sysvars::{
-        rent::{Rent, ACCOUNT_STORAGE_OVERHEAD, DEFAULT_EXEMPTION_THRESHOLD, DEFAULT_LAMPORTS_PER_BYTE_YEAR},
-        Sysvar,
-    },
-    ProgramResult,
-};`
-    : `use pinocchio::{
-    account_info::AccountInfo,
-    instruction::Signer,
-    program_entrypoint,
-    program_error::ProgramError,
-    pubkey,
-    pubkey::Pubkey,
-    seeds,
-    ProgramResult,
-};`;
-  const initHelpers = usesInit ? renderInitHelpers() : '';
-
-  return `// AUTO-GENERATED - DO NOT EDIT
-#![no_std]
-
-${pinocchioImports}
-use pinocchio_tkn::common::{Burn, MintTo, Transfer};
-
-program_entrypoint!(process_instruction);
-
-#[cfg(target_arch = "bpf")]
-use pinocchio::default_allocator;
-
-#[cfg(target_arch = "bpf")]
-default_allocator!();
-
-#[cfg(target_arch = "bpf")]
-#[panic_handler]
-fn panic_handler(_info: &core::panic::PanicInfo<'_>) -> ! {
-    unsafe {
-        pinocchio::syscalls::abort();
-    }
-}
-
-#[cfg(not(target_arch = "bpf"))]
-extern crate std;
-
-${renderStateStruct()}
-
-${initHelpers}
-
-fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey, ProgramError> {
+// Readers return `None` for short or malformed data and leave the error to the caller:
+// `InvalidArgs` for instruction data, `InvalidAccountLayout` for account data.
+const renderReadHelper = (kind: string) => {
+  const { rust, size } = scalarTypes[kind];
+  if (kind === 'pubkey') {
+    return `fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
     if data.len() < offset + 32 {
-        return Err(ProgramError::InvalidInstructionData);
+        return None;
     }
     let mut out = [0u8; 32];
     out.copy_from_slice(&data[offset..offset + 32]);
-    Ok(out)
+    Some(out)
 }
-
-fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
-    if data.len() < offset + 8 {
-        return Err(ProgramError::InvalidInstructionData);
+`;
+  }
+  if (kind === 'u8') {
+    return `fn read_u8(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).copied()
+}
+`;
+  }
+  if (kind === 'bool') {
+    return `fn read_bool(data: &[u8], offset: usize) -> Option<bool> {
+    match data.get(offset) {
+        Some(0) => Some(false),
+        Some(1) => Some(true),
+        _ => None,
     }
-    let mut buf = [0u8; 8];
-    buf.copy_from_slice(&data[offset..offset + 8]);
-    Ok(u64::from_le_bytes(buf))
 }
-
-fn read_u8(data: &[u8], offset: usize) -> Result<u8, ProgramError> {
-    if data.len() <= offset {
-        return Err(ProgramError::InvalidInstructionData);
+`;
+  }
+  return `fn read_${kind}(data: &[u8], offset: usize) -> Option<${rust}> {
+    if data.len() < offset + ${size} {
+        return None;
     }
-    Ok(data[offset])
+    let mut buf = [0u8; ${size}];
+    buf.copy_from_slice(&data[offset..offset + ${size}]);
+    Some(${rust}::from_le_bytes(buf))
 }
+`;
+};
 
-fn write_pubkey(data: &mut [u8], offset: usize, value: &Pubkey) -> Result<(), ProgramError> {
+const renderWriteHelper = (kind: string) => {
+  const { rust, size } = scalarTypes[kind];
+  if (kind === 'pubkey') {
+    return `fn write_pubkey(data: &mut [u8], offset: usize, value: &Pubkey) -> Result<(), ProgramError> {
     if data.len() < offset + 32 {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(${errorEnumName}::InvalidAccountLayout.into());
     }
     data[offset..offset + 32].copy_from_slice(value);
     Ok(())
 }
-
-fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Result<(), ProgramError> {
-    if data.len() < offset + 8 {
-        return Err(ProgramError::InvalidAccountData);
+`;
+  }
+  if (kind === 'u8' || kind === 'bool') {
+    return `fn write_${kind}(data: &mut [u8], offset: usize, value: ${rust}) -> Result<(), ProgramError> {
+    if data.len() <= offset {
+        return Err(${errorEnumName}::InvalidAccountLayout.into());
     }
-    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    data[offset] = ${kind === 'bool' ? 'u8::from(value)' : 'value'};
     Ok(())
 }
-
-fn write_u8(data: &mut [u8], offset: usize, value: u8) -> Result<(), ProgramError> {
-    if data.len() <= offset {
-        return Err(ProgramError::InvalidAccountData);
+`;
+  }
+  return `fn write_${kind}(data: &mut [u8], offset: usize, value: ${rust}) -> Result<(), ProgramError> {
+    if data.len() < offset + ${size} {
+        return Err(${errorEnumName}::InvalidAccountLayout.into());
     }
-    data[offset] = value;
+    data[offset..offset + ${size}].copy_from_slice(&value.to_le_bytes());
     Ok(())
 }
+`;
+};
+
+// Only the layouts in use get helpers; the rest would be dead code.
+const renderLayoutHelpers = (returns: Array<Record<string, string>>) => {
+  const eventKinds = (events: typeof programEvents) =>
+    events.flatMap((event) => Object.values(event.fields).map((type: any) => type.kind));
+  const packedKinds = [
+    ...Object.values(accounts).flatMap((account: any) => Object.values(account.schema).flatMap(scalarKindsOf)),
+    ...returns.flatMap((types) => Object.values(types).map((type) => (type === 'ratio' ? 'u64' : type))),
+  ];
+  // Every event has a `decode`, but only emitted events write their fields.
+  const emitted = emittedEvents();
+  const stateKinds = new Set([...packedKinds, ...eventKinds(programEvents)]);
+  const writeKinds = new Set([
+    ...packedKinds,
+    ...eventKinds(programEvents.filter((event) => emitted.has(event.name))),
+  ]);
+  const argKinds = new Set(
+    instructions.flatMap((ix) =>
+      Object.values(ix.args).flatMap((type: any) => {
+        if (type.kind === 'vec') return ['u32', type.of.kind];
+        return variableArgKinds.includes(type.kind) ? ['u32'] : [type.kind];
+      }),
+    ),
+  );
+  const kinds = Object.keys(scalarTypes);
+  return [
+    ...kinds.filter((kind) => stateKinds.has(kind) || argKinds.has(kind)).map(renderReadHelper),
+    ...kinds.filter((kind) => writeKinds.has(kind)).map(renderWriteHelper),
+    ...(usesOptionFields() ? [renderOptionHelpers()] : []),
+  ].join('\n');
+};
+
+const usesOptionFields = () => {
+  const hasOption = (type: any): boolean => {
+    if (type.kind === 'option') return true;
+    if (type.kind === 'array') return hasOption(type.of);
+    if (type.kind === 'struct') return Object.values(type.fields).some(hasOption);
+    return false;
+  };
+  return Object.values(accounts).some((account: any) => Object.values(account.schema).some(hasOption));
+};
 
-#[allow(dead_code)]
-fn checked_add(a: u64, b: u64) -> Result<u64, ProgramError> {
-    a.checked_add(b).ok_or(ProgramError::InvalidInstructionData)
+// Borsh option tag (0 = None, 1 = Some) followed by the value. None zeroes the value's
+// bytes, which stay reserved so every field keeps a fixed offset.
+const renderOptionHelpers = () => `fn read_option<T>(
+    data: &[u8],
+    offset: usize,
+    read: fn(&[u8], usize) -> Option<T>,
+) -> Option<Option<T>> {
+    match data.get(offset) {
+        Some(0) => Some(None),
+        Some(1) => read(data, offset + 1).map(Some),
+        _ => None,
+    }
 }
 
-#[allow(dead_code)]
-fn checked_sub(a: u64, b: u64) -> Result<u64, ProgramError> {
-    a.checked_sub(b).ok_or(ProgramError::InvalidInstructionData)
+fn write_option<T>(
+    data: &mut [u8],
+    offset: usize,
+    size: usize,
+    value: Option<T>,
+    write: fn(&mut [u8], usize, T) -> Result<(), ProgramError>,
+) -> Result<(), ProgramError> {
+    let Some(bytes) = data.get_mut(offset..offset + 1 + size) else {
+        return Err(${errorEnumName}::InvalidAccountLayout.into());
+    };
+    bytes.fill(0);
+    match value {
+        Some(value) => {
+            bytes[0] = 1;
+            write(data, offset + 1, value)
+        }
+        None => Ok(()),
+    }
 }
+`;
 
-#[allow(dead_code)]
-fn checked_mul(a: u64, b: u64) -> Result<u64, ProgramError> {
-    let value = (a as u128).checked_mul(b as u128).ok_or(ProgramError::InvalidInstructionData)?;
-    if value > u64::MAX as u128 {
-        return Err(ProgramError::InvalidInstructionData);
+const renderTokenLayoutTests = () => `
+#[cfg(test)]
+mod token_layout_tests {
+    use super::*;
+    use pinocchio_tkn::state::MINT_SIZE;
+
+    #[test]
+    fn token_layout_reads_the_token_2022_account_type() {
+        let mut data = [0u8; TOKEN_ACCOUNT_SIZE + 5];
+        assert_eq!(check_token_layout(&data[..TOKEN_ACCOUNT_SIZE], TOKEN_ACCOUNT_SIZE, 2), Ok(()));
+        assert_eq!(check_token_layout(&data[..MINT_SIZE], MINT_SIZE, 1), Ok(()));
+        assert_eq!(check_token_layout(&data[..MINT_SIZE + 1], MINT_SIZE, 1), Err(${errorEnumName}::InvalidAccountLayout.into()));
+        data[TOKEN_ACCOUNT_SIZE] = 2;
+        assert_eq!(check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2), Ok(()));
+        assert_eq!(check_token_layout(&data, MINT_SIZE, 1), Err(${errorEnumName}::InvalidAccountLayout.into()));
+        data[TOKEN_ACCOUNT_SIZE] = 1;
+        assert_eq!(check_token_layout(&data, MINT_SIZE, 1), Ok(()));
+        assert_eq!(check_token_layout(&data, TOKEN_ACCOUNT_SIZE, 2), Err(${errorEnumName}::InvalidAccountLayout.into()));
     }
-    Ok(value as u64)
 }
+`;
 
-#[allow(dead_code)]
-fn checked_div(a: u64, b: u64) -> Result<u64, ProgramError> {
+// The trait behind checked_add/sub/mul/div, with a method for each of them in use.
+const renderCheckedInt = (used: Set<string>) => {
+  const ops = ['add', 'sub', 'mul', 'div'].filter((op) => used.has(`checked_${op}`));
+  // Rust division truncates toward zero; floor steps down when a signed quotient is
+  // inexact and negative. Unsigned remainders are never negative, so they never step.
+  const method = (op: string) =>
+    op === 'div'
+      ? `            fn div_checked(self, other: Self) -> Option<Self> {
+                let quotient = self.checked_div(other)?;
+                let remainder = self % other;
+                if remainder != Self::ZERO && (remainder < Self::ZERO) != (other < Self::ZERO) {
+                    Some(quotient - 1)
+                } else {
+                    Some(quotient)
+                }
+            }`
+      : `            fn ${op}_checked(self, other: Self) -> Option<Self> {\n                self.checked_${op}(other)\n            }`;
+  return `// Lets the checked helpers below take any integer type the DSL supports.
+trait CheckedInt: Copy + ${used.has('checked_div') ? 'PartialOrd' : 'PartialEq'} {
+${used.has('checked_div') ? '    const ZERO: Self;\n' : ''}${ops.map((op) => `    fn ${op}_checked(self, other: Self) -> Option<Self>;`).join('\n')}
+}
+
+macro_rules! impl_checked_int {
+    ($($ty:ty),*) => {$(
+        impl CheckedInt for $ty {
+${used.has('checked_div') ? '            const ZERO: Self = 0;\n' : ''}${ops.map(method).join('\n')}
+        }
+    )*};
+}
+
+impl_checked_int!(u8, u16, u32, u64, u128, i64);
+`;
+};
+
+// Checked math helpers in emission order. Each lists the helpers its body calls and the
+// host tests that pin it down; renderLib emits only what the handlers reach.
+const mathHelpers: Array<{
+  name: string;
+  uses: string[];
+  source: string | ((used: Set<string>) => string);
+  test?: string;
+}> = [
+  {
+    name: 'CheckedInt',
+    uses: [],
+    source: renderCheckedInt,
+  },
+  {
+    name: 'checked_add',
+    uses: ['CheckedInt'],
+    source: `fn checked_add<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.add_checked(b).ok_or(${errorEnumName}::Overflow)?)
+}
+`,
+    test: `    #[test]
+    fn add_checks_every_integer_width() {
+        assert_eq!(checked_add(u8::MAX, 1), Err(error(${errorEnumName}::Overflow)));
+        assert_eq!(checked_add(u128::MAX - 1, 1), Ok(u128::MAX));
+        assert_eq!(checked_add(u128::MAX, 1), Err(error(${errorEnumName}::Overflow)));
+    }
+`,
+  },
+  {
+    name: 'checked_sub',
+    uses: ['CheckedInt'],
+    source: `fn checked_sub<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.sub_checked(b).ok_or(${errorEnumName}::Overflow)?)
+}
+`,
+    test: `    #[test]
+    fn sub_checks_every_integer_width() {
+        assert_eq!(checked_sub(0u32, 1), Err(error(${errorEnumName}::Overflow)));
+        assert_eq!(checked_sub(0i64, 5), Ok(-5));
+    }
+`,
+  },
+  {
+    name: 'checked_mul',
+    uses: ['CheckedInt'],
+    source: `fn checked_mul<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    Ok(a.mul_checked(b).ok_or(${errorEnumName}::Overflow)?)
+}
+`,
+    test: `    #[test]
+    fn mul_checks_every_integer_width() {
+        assert_eq!(checked_mul(u16::MAX, 2), Err(error(${errorEnumName}::Overflow)));
+    }
+`,
+  },
+  {
+    name: 'checked_div',
+    uses: ['CheckedInt'],
+    source: `// Floors, on signed types too. Signed division overflows on MIN / -1, so the
+// quotient is checked as well.
+fn checked_div<T: CheckedInt>(a: T, b: T) -> Result<T, ProgramError> {
+    if b == T::ZERO {
+        return Err(${errorEnumName}::DivideByZero.into());
+    }
+    Ok(a.div_checked(b).ok_or(${errorEnumName}::Overflow)?)
+}
+`,
+    test: `    #[test]
+    fn div_checks_every_integer_width() {
+        assert_eq!(checked_div(7u64, 2), Ok(3));
+        assert_eq!(checked_div(-7i64, 2), Ok(-4));
+        assert_eq!(checked_div(7i64, -2), Ok(-4));
+        assert_eq!(checked_div(-7i64, -2), Ok(3));
+        assert_eq!(checked_div(-6i64, 2), Ok(-3));
+        assert_eq!(checked_div(i64::MIN, -1), Err(error(${errorEnumName}::Overflow)));
+        assert_eq!(checked_div(1u16, 0), Err(error(${errorEnumName}::DivideByZero)));
+    }
+`,
+  },
+  {
+    name: 'checked_div_ceil',
+    uses: [],
+    source: `fn checked_div_ceil(a: u64, b: u64) -> Result<u64, ProgramError> {
     if b == 0 {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(${errorEnumName}::DivideByZero.into());
     }
-    Ok(a / b)
+    Ok(a.div_ceil(b))
 }
-
-#[allow(dead_code)]
-fn checked_mul_div(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
+`,
+    test: `    #[test]
+    fn div_ceil_rounds_up_only_on_remainder() {
+        assert_eq!(checked_div_ceil(7, 2), Ok(4));
+        assert_eq!(checked_div_ceil(6, 2), Ok(3));
+        assert_eq!(checked_div_ceil(u64::MAX, 1), Ok(u64::MAX));
+        assert_eq!(checked_div_ceil(1, 0), Err(error(${errorEnumName}::DivideByZero)));
+    }
+`,
+  },
+  {
+    name: 'checked_div_round',
+    uses: [],
+    source: `// Rounds halves up. \`remainder >= b - remainder\` avoids overflowing \`2 * remainder\`.
+fn checked_div_round(a: u64, b: u64) -> Result<u64, ProgramError> {
+    if b == 0 {
+        return Err(${errorEnumName}::DivideByZero.into());
+    }
+    let (quotient, remainder) = (a / b, a % b);
+    Ok(if remainder >= b - remainder { quotient + 1 } else { quotient })
+}
+`,
+    test: `    #[test]
+    fn div_round_rounds_halves_up() {
+        assert_eq!(checked_div_round(5, 2), Ok(3));
+        assert_eq!(checked_div_round(7, 3), Ok(2));
+        assert_eq!(checked_div_round(8, 3), Ok(3));
+        assert_eq!(checked_div_round(u64::MAX, 2), Ok(1 << 63));
+        assert_eq!(checked_div_round(1, 0), Err(error(${errorEnumName}::DivideByZero)));
+    }
+`,
+  },
+  {
+    name: 'checked_mul_div',
+    uses: [],
+    source: `fn checked_mul_div(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
     if c == 0 {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(${errorEnumName}::DivideByZero.into());
     }
-    let value = (a as u128)
-        .checked_mul(b as u128)
-        .ok_or(ProgramError::InvalidInstructionData)?
-        / (c as u128);
-    if value > u64::MAX as u128 {
-        return Err(ProgramError::InvalidInstructionData);
+    let value = (a as u128) * (b as u128) / (c as u128);
+    Ok(u64::try_from(value).map_err(|_| ${errorEnumName}::Overflow)?)
+}
+`,
+    test: `    #[test]
+    fn mul_div_uses_wide_intermediates() {
+        assert_eq!(checked_mul_div(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(checked_mul_div(7, 3, 2), Ok(10));
+        assert_eq!(checked_mul_div(u64::MAX, 2, 1), Err(error(${errorEnumName}::Overflow)));
+        assert_eq!(checked_mul_div(1, 1, 0), Err(error(${errorEnumName}::DivideByZero)));
+    }
+`,
+  },
+  {
+    name: 'checked_mul_div_ceil',
+    uses: [],
+    source: `fn checked_mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
+    if c == 0 {
+        return Err(${errorEnumName}::DivideByZero.into());
+    }
+    let value = ((a as u128) * (b as u128)).div_ceil(c as u128);
+    Ok(u64::try_from(value).map_err(|_| ${errorEnumName}::Overflow)?)
+}
+`,
+    test: `    #[test]
+    fn mul_div_ceil_rounds_up_only_on_remainder() {
+        assert_eq!(checked_mul_div_ceil(7, 3, 2), Ok(11));
+        assert_eq!(checked_mul_div_ceil(6, 3, 2), Ok(9));
+        assert_eq!(checked_mul_div_ceil(0, 3, 2), Ok(0));
+        assert_eq!(checked_mul_div_ceil(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(checked_mul_div_ceil(u64::MAX, 3, 2), Err(error(${errorEnumName}::Overflow)));
+        assert_eq!(checked_mul_div_ceil(1, 1, 0), Err(error(${errorEnumName}::DivideByZero)));
+    }
+`,
+  },
+  {
+    name: 'checked_mul_div_round',
+    uses: [],
+    source: `fn checked_mul_div_round(a: u64, b: u64, c: u64) -> Result<u64, ProgramError> {
+    if c == 0 {
+        return Err(${errorEnumName}::DivideByZero.into());
+    }
+    let (product, divisor) = ((a as u128) * (b as u128), c as u128);
+    let (quotient, remainder) = (product / divisor, product % divisor);
+    let value = if remainder >= divisor - remainder { quotient + 1 } else { quotient };
+    Ok(u64::try_from(value).map_err(|_| ${errorEnumName}::Overflow)?)
+}
+`,
+    test: `    #[test]
+    fn mul_div_round_rounds_halves_up() {
+        assert_eq!(checked_mul_div_round(1, 5, 2), Ok(3));
+        assert_eq!(checked_mul_div_round(2, 7, 3), Ok(5));
+        assert_eq!(checked_mul_div_round(1, 4, 3), Ok(1));
+        assert_eq!(checked_mul_div_round(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(checked_mul_div_round(u64::MAX, 3, 2), Err(error(${errorEnumName}::Overflow)));
+        assert_eq!(checked_mul_div_round(1, 1, 0), Err(error(${errorEnumName}::DivideByZero)));
+    }
+`,
+  },
+  {
+    name: 'checked_bps',
+    uses: ['checked_mul_div'],
+    source: `const BPS_DENOMINATOR: u64 = 10_000;
+
+fn checked_bps(value: u64, bps: u64) -> Result<u64, ProgramError> {
+    checked_mul_div(value, bps, BPS_DENOMINATOR)
+}
+`,
+    test: `    #[test]
+    fn bps_takes_a_fraction_of_ten_thousand() {
+        assert_eq!(checked_bps(1_000_000, 30), Ok(3_000));
+        assert_eq!(checked_bps(333, 10_000), Ok(333));
+        assert_eq!(checked_bps(9_999, 1), Ok(0));
+        assert_eq!(checked_bps(u64::MAX, 20_000), Err(error(${errorEnumName}::Overflow)));
+    }
+`,
+  },
+  {
+    name: 'checked_pow',
+    uses: [],
+    source: `// 0 and 1 keep their value under any positive exponent, even one past u32::MAX.
+fn checked_pow(base: u64, exponent: u64) -> Result<u64, ProgramError> {
+    if base <= 1 {
+        return Ok(if exponent == 0 { 1 } else { base });
+    }
+    let exponent = u32::try_from(exponent).map_err(|_| ${errorEnumName}::Overflow)?;
+    Ok(base.checked_pow(exponent).ok_or(${errorEnumName}::Overflow)?)
+}
+`,
+    test: `    #[test]
+    fn pow_checks_overflow() {
+        assert_eq!(checked_pow(10, 0), Ok(1));
+        assert_eq!(checked_pow(2, 63), Ok(1 << 63));
+        assert_eq!(checked_pow(2, 64), Err(error(${errorEnumName}::Overflow)));
+        assert_eq!(checked_pow(2, u64::MAX), Err(error(${errorEnumName}::Overflow)));
+        assert_eq!(checked_pow(1, u64::MAX), Ok(1));
+        assert_eq!(checked_pow(0, 1 << 32), Ok(0));
+        assert_eq!(checked_pow(0, 0), Ok(1));
+    }
+`,
+  },
+  {
+    name: 'isqrt',
+    uses: [],
+    source: `// Floor square root, bit by bit. The root of any u128 fits in a u64.
+fn isqrt(value: u128) -> u64 {
+    let mut remainder = value;
+    let mut root: u128 = 0;
+    let mut bit: u128 = 1 << 126;
+    while bit > remainder {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root as u64
+}
+`,
+    test: `    #[test]
+    fn isqrt_floors() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(u128::MAX), u64::MAX);
     }
-    Ok(value as u64)
+`,
+  },
+  {
+    name: 'sqrt',
+    uses: ['isqrt'],
+    source: `fn sqrt(a: u64) -> u64 {
+    isqrt(a as u128)
 }
+`,
+    test: `    #[test]
+    fn sqrt_floors() {
+        assert_eq!(sqrt(0), 0);
+        assert_eq!(sqrt(1), 1);
+        assert_eq!(sqrt(15), 3);
+        assert_eq!(sqrt(16), 4);
+        assert_eq!(sqrt(u64::MAX), u32::MAX as u64);
+    }
+`,
+  },
+  {
+    name: 'sqrt_mul',
+    uses: ['isqrt'],
+    source: `fn sqrt_mul(a: u64, b: u64) -> u64 {
+    isqrt((a as u128) * (b as u128))
+}
+`,
+    test: `    #[test]
+    fn sqrt_mul_uses_a_wide_product() {
+        assert_eq!(sqrt_mul(1_000_000, 2_000_000), 1_414_213);
+        assert_eq!(sqrt_mul(u64::MAX, u64::MAX), u64::MAX);
+    }
+`,
+  },
+];
+
+// The math helpers the rendered handlers call, plus the helpers those call.
+const usedMathHelpers = (handlers: string) => {
+  const used = new Set(
+    mathHelpers.filter(({ name }) => new RegExp(`\\b${name}\\(`).test(handlers)).map(({ name }) => name),
+  );
+  [...mathHelpers].reverse().forEach(({ name, uses }) => {
+    if (used.has(name)) uses.forEach((dependency) => used.add(dependency));
+  });
+  return used;
+};
+
+const renderMathHelpers = (handlers: string) => {
+  const used = usedMathHelpers(handlers);
+  return mathHelpers
+    .filter(({ name }) => used.has(name))
+    .map(({ source }) => `${typeof source === 'string' ? source : source(used)}\n`)
+    .join('');
+};
+
+const renderMathTests = (handlers: string) => {
+  const used = usedMathHelpers(handlers);
+  const tests = mathHelpers.filter(({ name }) => used.has(name)).flatMap(({ test }) => (test ? [test] : []));
+  if (tests.length === 0) return '';
+  return `
+#[cfg(test)]
+mod math_tests {
+    use super::*;
+${tests.some((test) => test.includes('error(')) ? `
+    fn error(error: ${errorEnumName}) -> ProgramError {
+        error.into()
+    }
+` : ''}
+${tests.join('\n')}}
+`;
+};
+
+const renderLib = () => {
+  const handlers = instructions.map(renderInstructionHandler).join('\n');
+  const dispatchArms = instructions
+    .map((ix) => `        ${ix.discriminator} => handle_${toSnake(ix.name)}(program_id, accounts, &data[1..]),`)
+    .join('\n');
+  const usesInit = instructions.some((ix) => hasStateInit(ix));
+  const usesPdas = instructions.some((ix) => (ix.accounts as Array<any>).some((meta) => meta.pda));
+  const usesSigners =
+    usesInit || instructions.some((ix) => (ix.ops as Array<any>).some((op) => op.signer));
+  const instructionImports = usesInit ? ['AccountMeta', 'Instruction', 'Signer'] : usesSigners ? ['Signer'] : [];
+  const pinocchioImportLines = [
+    'account_info::AccountInfo,',
+    usesInit ? 'cpi::invoke_signed,' : null,
+    instructionImports.length > 1
+      ? `instruction::{${instructionImports.join(', ')}},`
+      : instructionImports.length === 1
+        ? `instruction::${instructionImports[0]},`
+        : null,
+    'program_entrypoint,',
+    'program_error::ProgramError,',
+    usesPdas ? 'pubkey,' : null,
+    'pubkey::Pubkey,',
+    usesSigners ? 'seeds,' : null,
+    usesInit
+      ? `sysvars::{
+        rent::{Rent, ACCOUNT_STORAGE_OVERHEAD, DEFAULT_EXEMPTION_THRESHOLD, DEFAULT_LAMPORTS_PER_BYTE_YEAR},
+        Sysvar,
+    },`
+      : null,
+    'ProgramResult,',
+  ].filter(Boolean);
+  const pinocchioImports = `use pinocchio::{\n${pinocchioImportLines.map((line) => `    ${line}`).join('\n')}\n};`;
+  const initHelpers = usesInit ? renderInitHelpers() : '';
+  const variableArgHelpers = renderVariableArgHelpers();
+  const usesTokenAccounts = usesAccountKind('ata');
+  const usesMints = usesAccountKind('mint');
+  const tokenOps = instructions.flatMap((ix) => (ix.ops as Array<any>).filter((op) => tokenOpKinds.includes(op.op)));
+  const usesMintDecimals = tokenOps.some((op) => op.op === 'token.transferChecked' && !op.decimals);
+  const tokenStateTypes = [
+    usesMints || usesMintDecimals ? 'Mint' : null,
+    usesTokenAccounts ? 'AccountState' : null,
+    usesTokenAccounts ? 'TokenAccount' : null,
+    usesMints ? 'MINT_SIZE' : null,
+    usesTokenAccounts || usesMints ? 'TOKEN_ACCOUNT_SIZE' : null,
+  ].filter(Boolean);
+  const usesProgramChecks = [...Object.keys(knownProgramIds), 'either'].some(usesKnownProgram);
+  const tokenImports: string[] = [];
+  if (tokenStateTypes.length > 0) {
+    const tokenStateImport =
+      tokenStateTypes.length === 1 ? tokenStateTypes[0] : `{${tokenStateTypes.join(', ')}}`;
+    tokenImports.push(`state::${tokenStateImport}`);
+  }
+  if (usesTokenProgramId('token')) {
+    tokenImports.push('TOKEN_PROGRAM_ID');
+  }
+  if (usesTokenProgramId('token2022')) {
+    tokenImports.push('TOKEN_2022_PROGRAM_ID');
+  }
+  const usesTokenOp = (opName: string) => tokenOps.some((op) => op.op === opName);
+  const usedTokenOps = [
+    ...(usesTokenOp('token.setAuthority') ? ['AuthorityType'] : []),
+    ...tokenOpKinds.filter(usesTokenOp).map((opName) => tokenOpSpecs[opName].struct),
+  ].sort();
+  const tokenOpImports = usedTokenOps.length > 0
+    ? `\nuse pinocchio_tkn::common::{${usedTokenOps.join(', ')}};`
+    : '';
+  const tokenAccountImports = tokenImports.length > 0
+    ? `\nuse pinocchio_tkn::{${tokenImports.join(', ')}};`
+    : '';
+  // Lowering type-checks every op, so each instruction's return types are worked out once.
+  const returns = returningInstructions().map((ix) => ({ ix, types: returnTypes(ix) }));
+  const usesRatio = returns.some(({ types }) => Object.values(types).includes('ratio'));
+  const programIds = renderProgramIds(
+    usesInit || usesKnownProgram('system'),
+    usesKnownProgram('associatedToken'),
+  );
+  const tokenAccountHelpers = [
+    usesTokenAccounts || usesMints ? renderTokenLayoutHelper() : null,
+    usesTokenAccounts ? renderTokenAccountHelper() : null,
+    usesMints ? renderMintAccountHelper() : null,
+    usesProgramChecks ? renderProgramAccountHelper() : null,
+    usesMintDecimals ? renderMintDecimalsHelper() : null,
+  ]
+    .filter(Boolean)
+    .join('\n');
 
-pub fn process_instruction(
+  return `// AUTO-GENERATED - DO NOT EDIT
+#![no_std]
+
+${pinocchioImports}${tokenOpImports}${tokenAccountImports}
+
+program_entrypoint!(process_instruction);
+
+#[cfg(target_arch = "bpf")]
+use pinocchio::default_allocator;
+
+#[cfg(target_arch = "bpf")]
+default_allocator!();
+
+#[cfg(target_arch = "bpf")]
+#[panic_handler]
+fn panic_handler(_info: &core::panic::PanicInfo<'_>) -> ! {
+    unsafe {
+        pinocchio::syscalls::abort();
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+extern crate std;
+
+${renderErrorEnum()}
+${[
+  ...Object.keys(accounts).map((key) => renderStateStruct(key, handlers)),
+  ...programEvents.map(renderEvent),
+  ...(usesRatio ? [renderRatio()] : []),
+  ...returns.map(({ ix, types }) => renderReturnStruct(ix, types)),
+].join('\n')}
+
+${programIds}
+${initHelpers}
+${tokenAccountHelpers}
+${renderLayoutHelpers(returns.map(({ types }) => types))}
+${variableArgHelpers}${renderMathHelpers(handlers)}pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
     if data.is_empty() {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(${errorEnumName}::UnknownInstruction.into());
     }
 
     match data[0] {
 ${dispatchArms}
-        _ => Err(ProgramError::InvalidInstructionData),
+        _ => Err(${errorEnumName}::UnknownInstruction.into()),
     }
 }
 
 ${handlers}
-`;
+${renderMathTests(handlers)}${usesTokenAccounts || usesMints ? renderTokenLayoutTests() : ''}`;
 };
 
 const crateName = `${toSnake(programName)}_pinocchio`;
@@ -689,6 +2982,7 @@ crate-type = ["cdylib", "lib"]
 
 [dependencies]
 pinocchio = { version = "0.9", default-features = false }
+pinocchio-pubkey = "0.3"
 pinocchio-tkn = { version = "0.2.2" }
 
 [dev-dependencies]
@@ -704,8 +2998,10 @@ solana-keypair = "3.0.0"
 solana-message = "3.0.1"
 solana-signer = "3.0.0"
 solana-transaction = "3.0.0"
+solana-transaction-error = "3.0.0"
 solana-system-interface = "3.0.0"
 spl-token-interface = "2.0.0"
+base64 = "0.22"
 `; 
 
 await fs.mkdir(path.join(outDir, 'src'), { recursive: true });
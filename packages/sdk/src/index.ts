@@ -1,43 +1,116 @@
 export type ScalarType =
   | { kind: 'u64' }
   | { kind: 'u8' }
+  | { kind: 'u16' }
+  | { kind: 'u32' }
+  | { kind: 'u128' }
+  | { kind: 'i64' }
+  | { kind: 'bool' }
   | { kind: 'pubkey' };
 
-export type Schema = Record<string, ScalarType>;
+// A one-byte tag naming one of `variants`, in declaration order.
+export type EnumType = { kind: 'enum'; variants: string[] };
+
+// Account fields can also hold fixed-size arrays, nested structs, enums and options.
+// An option is a tag byte followed by room for its value, so None keeps the layout fixed.
+export type SchemaType =
+  | ScalarType
+  | EnumType
+  | { kind: 'option'; of: ScalarType | EnumType }
+  | { kind: 'array'; of: SchemaType; len: number }
+  | { kind: 'struct'; fields: Record<string, SchemaType> };
+
+export type Schema = Record<string, SchemaType>;
+
+// Instruction args can also be length-prefixed (Borsh u32) and capped at `maxLen`.
+export type ArgType =
+  | ScalarType
+  | { kind: 'bytes'; maxLen: number }
+  | { kind: 'string'; maxLen: number }
+  | { kind: 'vec'; of: ScalarType; maxLen: number };
+
+// Array index or nested struct field name, e.g. `field('pool', 'reserves', 0)`.
+export type FieldPathStep = number | string;
 
 export type ArgRef = { kind: 'arg'; name: string };
-export type FieldRef = { kind: 'field'; account: string; name: string };
+export type FieldRef = { kind: 'field'; account: string; name: string; path?: FieldPathStep[] };
 export type AccountRef = { kind: 'account'; name: string };
 export type BumpRef = { kind: 'bump'; account: string };
+export type LocalRef = { kind: 'local'; name: string };
 
 export type Seed = string | ArgRef | FieldRef | AccountRef;
 export type Pda = { kind: 'pda'; seeds: Seed[] };
 
+// How a division rounds its quotient; `nearest` rounds halves up. Defaults to `floor`.
+export type Rounding = 'floor' | 'ceil' | 'nearest';
+
 export type Expr =
   | { kind: 'const'; value: bigint }
+  | { kind: 'bool'; value: boolean }
+  // Base58 address literal.
+  | { kind: 'pubkey'; value: string }
+  | AccountRef
   | ArgRef
   | FieldRef
-  | { kind: 'add' | 'sub' | 'mul' | 'div' | 'eq'; left: Expr; right: Expr }
+  | LocalRef
+  | { kind: 'add' | 'sub' | 'mul' | 'min' | 'max'; left: Expr; right: Expr }
+  | { kind: 'div'; left: Expr; right: Expr; rounding?: Rounding }
+  // `bps`: left * right / 10_000. `pow`: left ** right.
+  | { kind: 'bps' | 'pow'; left: Expr; right: Expr }
+  | { kind: 'mulDiv'; left: Expr; right: Expr; divisor: Expr; rounding?: Rounding }
+  | { kind: 'sqrt'; value: Expr }
+  // Length of a bytes, string or vec arg (as u64), and one element of a vec arg.
+  | { kind: 'len'; value: Expr }
+  | { kind: 'at'; value: Expr; index: Expr }
+  // Options and enum variants; `none` and `variant` take their type from the other side.
+  | { kind: 'isSome'; value: Expr }
+  | { kind: 'some'; value: Expr }
+  | { kind: 'none' }
+  | { kind: 'variant'; name: string }
+  // Converts between integer types; narrowing is checked and fails with Overflow.
+  | { kind: 'cast'; value: Expr; type: ScalarType }
+  | { kind: 'eq' | 'ne' | 'lt' | 'lte' | 'gt' | 'gte'; left: Expr; right: Expr }
+  | { kind: 'and' | 'or'; left: Expr; right: Expr }
+  | { kind: 'not'; value: Expr }
   | { kind: 'if'; cond: Expr; then: Expr; else: Expr };
 
+// 1-byte tag, explicit bytes, or Anchor's sha256("account:<Name>")[..8].
+// Defaults to the first byte of the Anchor discriminator.
+export type AccountDiscriminator = number | number[] | 'anchor';
+
 export type AccountDef = {
   __kind: 'account';
   name: string;
   schema: Schema;
   pda?: Pda;
+  discriminator?: AccountDiscriminator;
 };
 
+export type KnownProgram = 'token' | 'token2022' | 'system' | 'associatedToken';
+
+// Which token program(s) an account may belong to; 'either' accepts SPL Token and Token-2022.
+export type TokenProgram = 'token' | 'token2022' | 'either';
+
 export type AccountMeta = {
   name: string;
   signer?: boolean;
   writable?: boolean;
   kind?: 'account' | 'mint' | 'ata' | 'program';
-  address?: ArgRef | FieldRef | AccountRef;
+  address?: ArgRef | FieldRef | AccountRef | KnownProgram;
   pda?: Pda;
   owner?: ArgRef | FieldRef | AccountRef;
   mint?: ArgRef | FieldRef | AccountRef;
+  // Key in ProgramDef.accounts; defaults to the meta name when that key exists.
+  state?: string;
+  // The account key must equal this pubkey.
+  equals?: ArgRef | FieldRef | AccountRef;
+  // On a state account: each named field must equal the key of the account with that name.
+  hasOne?: string[];
+  // On ata, mint and token program accounts; defaults to the program's tokenProgram.
+  tokenProgram?: TokenProgram;
 };
 
+// SPL Token only: Token-2022 mints with transfer fees or hooks need `token.transferChecked`.
 export type TokenTransferOp = {
   op: 'token.transfer';
   from: string;
@@ -65,66 +138,203 @@ export type TokenBurnOp = {
   authority: string;
   amount: Expr;
   program?: string;
+  signer?: string;
+};
+
+// Like `token.transfer`, but the token program checks `decimals` against the mint.
+export type TokenTransferCheckedOp = {
+  op: 'token.transferChecked';
+  from: string;
+  mint: string;
+  to: string;
+  authority: string;
+  amount: Expr;
+  // Read from the mint account when omitted.
+  decimals?: Expr;
+  program?: string;
+  signer?: string;
+};
+
+export type TokenApproveOp = {
+  op: 'token.approve';
+  account: string;
+  delegate: string;
+  authority: string;
+  amount: Expr;
+  program?: string;
+  signer?: string;
 };
 
+export type TokenRevokeOp = {
+  op: 'token.revoke';
+  account: string;
+  authority: string;
+  program?: string;
+  signer?: string;
+};
+
+export type TokenAuthorityType = 'mintTokens' | 'freezeAccount' | 'accountOwner' | 'closeAccount';
+
+export type TokenSetAuthorityOp = {
+  op: 'token.setAuthority';
+  // The mint or token account whose authority changes.
+  account: string;
+  authority: string;
+  authorityType: TokenAuthorityType;
+  // `null` removes the authority for good.
+  newAuthority: Expr | null;
+  program?: string;
+  signer?: string;
+};
+
+export type TokenFreezeAccountOp = {
+  op: 'token.freezeAccount' | 'token.thawAccount';
+  account: string;
+  mint: string;
+  authority: string;
+  program?: string;
+  signer?: string;
+};
+
+// Sends the token account's lamports to `to`; the account must hold no tokens.
+export type TokenCloseAccountOp = {
+  op: 'token.closeAccount';
+  account: string;
+  to: string;
+  authority: string;
+  program?: string;
+  signer?: string;
+};
+
+export type TokenSyncNativeOp = {
+  op: 'token.syncNative';
+  account: string;
+  program?: string;
+};
+
+export type StateInitMode = 'init' | 'initIfNeeded';
+
 export type StateInitOp = {
   op: 'state.init';
   account: string;
   fields: Record<string, Expr | ArgRef | FieldRef | AccountRef | BumpRef>;
+  mode?: StateInitMode;
 };
 
 export type StateUpdateOp = {
   op: 'state.update';
   account: string;
+  // Dotted keys update one element or nested field: `'reserves.0'`, `'tier.feeBps'`.
   fields: Record<string, Expr>;
 };
 
+// Logs the named event (declared with `events`) through `sol_log_data`.
 export type EventOp = {
   op: 'event';
   name: string;
   data: Record<string, Expr | ArgRef | FieldRef | AccountRef>;
 };
 
+// Fails the instruction with the named program error unless `cond` holds.
+export type RequireOp = {
+  op: 'require';
+  cond: Expr;
+  error: string;
+};
+
+// Binds `value` once under `name`; later ops read it with `expr.local(name)`.
+export type ComputeOp = {
+  op: 'compute';
+  name: string;
+  value: Expr;
+};
+
 export type Op =
   | TokenTransferOp
   | TokenMintToOp
   | TokenBurnOp
+  | TokenTransferCheckedOp
+  | TokenApproveOp
+  | TokenRevokeOp
+  | TokenSetAuthorityOp
+  | TokenFreezeAccountOp
+  | TokenCloseAccountOp
+  | TokenSyncNativeOp
   | StateInitOp
   | StateUpdateOp
-  | EventOp;
+  | EventOp
+  | RequireOp
+  | ComputeOp;
 
 export type IxDef = {
   __kind: 'tx';
   name: string;
-  args: Record<string, ScalarType>;
+  args: Record<string, ArgType>;
   accounts: AccountMeta[];
   ops: Op[];
+  // Written with `set_return_data` after the last op, so CPI callers can read them.
+  returns?: Record<string, ViewRatio | Expr>;
 };
 
+// Returned as two u64s, numerator then denominator.
+export type ViewRatio = { kind: 'ratio'; numerator: Expr; denominator: Expr };
+
+// A bare type returns the state field of the same name; an expression returns its value.
+export type ViewReturn = ScalarType | ViewRatio | Expr;
+
+// A read-only instruction. Pubkey args named after a state account pass that account;
+// the return fields are written with `set_return_data`.
 export type ViewDef = {
   __kind: 'view';
   name: string;
   args: Record<string, ScalarType>;
-  returns: Record<string, ScalarType | 'ratio'>;
+  returns: Record<string, ViewReturn>;
+};
+
+// Event name -> field types. Each event is logged as an 8-byte discriminator
+// (sha256("event:<Name>"), as in Anchor) followed by its fields in declaration order.
+export type EventsDef = {
+  __kind: 'events';
+  events: Record<string, Record<string, ScalarType>>;
+};
+
+// Error name -> message. Codes are assigned from 6000 in declaration order.
+export type ErrorsDef = {
+  __kind: 'errors';
+  errors: Record<string, string>;
 };
 
 export type ProgramDef = {
   __kind: 'program';
   name: string;
   programId: string;
+  tokenProgram?: TokenProgram;
   accounts: Record<string, AccountDef>;
   [key: string]: unknown;
 };
 
 export const u64 = (): ScalarType => ({ kind: 'u64' });
 export const u8 = (): ScalarType => ({ kind: 'u8' });
+export const u16 = (): ScalarType => ({ kind: 'u16' });
+export const u32 = (): ScalarType => ({ kind: 'u32' });
+export const u128 = (): ScalarType => ({ kind: 'u128' });
+export const i64 = (): ScalarType => ({ kind: 'i64' });
+export const bool = (): ScalarType => ({ kind: 'bool' });
+export const array = (of: SchemaType, len: number): SchemaType => ({ kind: 'array', of, len });
+export const struct = (fields: Record<string, SchemaType>): SchemaType => ({ kind: 'struct', fields });
+export const enumType = (variants: string[]): EnumType => ({ kind: 'enum', variants });
+export const option = (of: ScalarType | EnumType): SchemaType => ({ kind: 'option', of });
+export const bytes = (maxLen: number): ArgType => ({ kind: 'bytes', maxLen });
+export const string = (maxLen: number): ArgType => ({ kind: 'string', maxLen });
+export const vec = (of: ScalarType, maxLen: number): ArgType => ({ kind: 'vec', of, maxLen });
 export const pubkey = (): ScalarType => ({ kind: 'pubkey' });
 
 export const arg = (name: string): ArgRef => ({ kind: 'arg', name });
-export const field = (account: string, name: string): FieldRef => ({
+export const field = (account: string, name: string, ...path: FieldPathStep[]): FieldRef => ({
   kind: 'field',
   account,
   name,
+  ...(path.length > 0 ? { path } : {}),
 });
 export const accountRef = (name: string): AccountRef => ({
   kind: 'account',
@@ -140,13 +350,55 @@ export const expr = {
     kind: 'const',
     value: BigInt(value),
   }),
+  bool: (value: boolean): Expr => ({ kind: 'bool', value }),
+  pubkey: (value: string): Expr => ({ kind: 'pubkey', value }),
+  account: (name: string): Expr => accountRef(name),
   arg: (name: string): Expr => arg(name),
-  field: (account: string, name: string): Expr => field(account, name),
+  field: (account: string, name: string, ...path: FieldPathStep[]): Expr => field(account, name, ...path),
+  local: (name: string): Expr => ({ kind: 'local', name }),
   add: (left: Expr, right: Expr): Expr => ({ kind: 'add', left, right }),
   sub: (left: Expr, right: Expr): Expr => ({ kind: 'sub', left, right }),
   mul: (left: Expr, right: Expr): Expr => ({ kind: 'mul', left, right }),
-  div: (left: Expr, right: Expr): Expr => ({ kind: 'div', left, right }),
+  div: (left: Expr, right: Expr, options: { rounding?: Rounding } = {}): Expr => ({
+    kind: 'div',
+    left,
+    right,
+    ...options,
+  }),
+  min: (left: Expr, right: Expr): Expr => ({ kind: 'min', left, right }),
+  max: (left: Expr, right: Expr): Expr => ({ kind: 'max', left, right }),
+  sqrt: (value: Expr): Expr => ({ kind: 'sqrt', value }),
+  cast: (value: Expr, type: ScalarType): Expr => ({ kind: 'cast', value, type }),
+  len: (value: Expr): Expr => ({ kind: 'len', value }),
+  at: (value: Expr, index: Expr): Expr => ({ kind: 'at', value, index }),
+  isSome: (value: Expr): Expr => ({ kind: 'isSome', value }),
+  some: (value: Expr): Expr => ({ kind: 'some', value }),
+  none: (): Expr => ({ kind: 'none' }),
+  variant: (name: string): Expr => ({ kind: 'variant', name }),
+  mulDiv: (
+    left: Expr,
+    right: Expr,
+    divisor: Expr,
+    options: { rounding?: Rounding } = {},
+  ): Expr => ({ kind: 'mulDiv', left, right, divisor, ...options }),
+  mulDivCeil: (left: Expr, right: Expr, divisor: Expr): Expr => ({
+    kind: 'mulDiv',
+    left,
+    right,
+    divisor,
+    rounding: 'ceil',
+  }),
+  bps: (value: Expr, bps: Expr): Expr => ({ kind: 'bps', left: value, right: bps }),
+  pow: (base: Expr, exponent: Expr): Expr => ({ kind: 'pow', left: base, right: exponent }),
   eq: (left: Expr, right: Expr): Expr => ({ kind: 'eq', left, right }),
+  ne: (left: Expr, right: Expr): Expr => ({ kind: 'ne', left, right }),
+  lt: (left: Expr, right: Expr): Expr => ({ kind: 'lt', left, right }),
+  lte: (left: Expr, right: Expr): Expr => ({ kind: 'lte', left, right }),
+  gt: (left: Expr, right: Expr): Expr => ({ kind: 'gt', left, right }),
+  gte: (left: Expr, right: Expr): Expr => ({ kind: 'gte', left, right }),
+  and: (left: Expr, right: Expr): Expr => ({ kind: 'and', left, right }),
+  or: (left: Expr, right: Expr): Expr => ({ kind: 'or', left, right }),
+  not: (value: Expr): Expr => ({ kind: 'not', value }),
   if: (cond: Expr, thenExpr: Expr, elseExpr: Expr): Expr => ({
     kind: 'if',
     cond,
@@ -161,6 +413,7 @@ export const account = (def: {
   name: string;
   schema: Schema;
   pda?: Pda;
+  discriminator?: AccountDiscriminator;
 }): AccountDef => ({ __kind: 'account', ...def });
 
 export const accountMeta = (name: string, meta: Omit<AccountMeta, 'name'>): AccountMeta => ({
@@ -203,17 +456,52 @@ export const programAccount = (
 
 export const tx = (def: {
   name: string;
-  args: Record<string, ScalarType>;
+  args: Record<string, ArgType>;
   accounts: AccountMeta[];
   ops: Op[];
+  returns?: Record<string, ViewRatio | Expr>;
 }): IxDef => ({ __kind: 'tx', ...def });
 
 export const view = (def: {
   name: string;
   args: Record<string, ScalarType>;
-  returns: Record<string, ScalarType | 'ratio'>;
+  returns: Record<string, ViewReturn>;
 }): ViewDef => ({ __kind: 'view', ...def });
 
+export const ratio = (numerator: Expr, denominator: Expr): ViewRatio => ({
+  kind: 'ratio',
+  numerator,
+  denominator,
+});
+
+export const require = (cond: Expr, error: string): RequireOp => ({
+  op: 'require',
+  cond,
+  error,
+});
+
+export const compute = (name: string, value: Expr): ComputeOp => ({
+  op: 'compute',
+  name,
+  value,
+});
+
+export const errors = (defs: Record<string, string>): ErrorsDef => ({
+  __kind: 'errors',
+  errors: defs,
+});
+
+export const events = (defs: Record<string, Record<string, ScalarType>>): EventsDef => ({
+  __kind: 'events',
+  events: defs,
+});
+
+export const emit = (name: string, data: EventOp['data']): EventOp => ({
+  op: 'event',
+  name,
+  data,
+});
+
 export const token = {
   transfer: (def: Omit<TokenTransferOp, 'op'>): TokenTransferOp => ({
     op: 'token.transfer',
@@ -227,6 +515,38 @@ export const token = {
     op: 'token.burn',
     ...def,
   }),
+  transferChecked: (def: Omit<TokenTransferCheckedOp, 'op'>): TokenTransferCheckedOp => ({
+    op: 'token.transferChecked',
+    ...def,
+  }),
+  approve: (def: Omit<TokenApproveOp, 'op'>): TokenApproveOp => ({
+    op: 'token.approve',
+    ...def,
+  }),
+  revoke: (def: Omit<TokenRevokeOp, 'op'>): TokenRevokeOp => ({
+    op: 'token.revoke',
+    ...def,
+  }),
+  setAuthority: (def: Omit<TokenSetAuthorityOp, 'op'>): TokenSetAuthorityOp => ({
+    op: 'token.setAuthority',
+    ...def,
+  }),
+  freezeAccount: (def: Omit<TokenFreezeAccountOp, 'op'>): TokenFreezeAccountOp => ({
+    op: 'token.freezeAccount',
+    ...def,
+  }),
+  thawAccount: (def: Omit<TokenFreezeAccountOp, 'op'>): TokenFreezeAccountOp => ({
+    op: 'token.thawAccount',
+    ...def,
+  }),
+  closeAccount: (def: Omit<TokenCloseAccountOp, 'op'>): TokenCloseAccountOp => ({
+    op: 'token.closeAccount',
+    ...def,
+  }),
+  syncNative: (def: Omit<TokenSyncNativeOp, 'op'>): TokenSyncNativeOp => ({
+    op: 'token.syncNative',
+    ...def,
+  }),
 };
 
 export const state = {
@@ -238,6 +558,15 @@ export const state = {
     account: accountName,
     fields,
   }),
+  initIfNeeded: (
+    accountName: string,
+    fields: Record<string, Expr | ArgRef | FieldRef | AccountRef | BumpRef>,
+  ): StateInitOp => ({
+    op: 'state.init',
+    account: accountName,
+    fields,
+    mode: 'initIfNeeded',
+  }),
   update: (accountName: string, fields: Record<string, Expr>): StateUpdateOp => ({
     op: 'state.update',
     account: accountName,
@@ -245,7 +574,7 @@ export const state = {
   }),
 };
 
-export const program = (def: { name: string; programId: string }): ProgramDef => ({
+export const program = (def: { name: string; programId: string; tokenProgram?: TokenProgram }): ProgramDef => ({
   __kind: 'program',
   ...def,
   accounts: {},
@@ -0,0 +1,62 @@
+import { account, accountMeta, arg, expr, i64, program, state, tx, u64 } from '@solana-ts-transpiler/sdk';
+
+// Reaches every math helper gen-pinocchio can emit, so the generated crate compiles all of
+// them and `cargo test --lib` runs their host tests, whatever the examples happen to use.
+export const Calc = program({
+  name: 'Calc',
+  programId: 'rJR1iafTNfbmvRWp7HkxKC3L8NirX4MV6EvZ2NHvWVJ',
+});
+
+Calc.accounts.calc = account({
+  name: 'CalcState',
+  schema: {
+    sum: u64(),
+    difference: u64(),
+    product: u64(),
+    quotient: u64(),
+    quotientCeil: u64(),
+    quotientNearest: u64(),
+    scaled: u64(),
+    scaledCeil: u64(),
+    scaledNearest: u64(),
+    fee: u64(),
+    power: u64(),
+    root: u64(),
+    geometricMean: u64(),
+    signedQuotient: i64(),
+  },
+});
+
+const a = expr.arg('a');
+const b = expr.arg('b');
+const c = expr.arg('c');
+
+Calc.calculate = tx({
+  name: 'calculate',
+  args: {
+    a: u64(),
+    b: u64(),
+    c: u64(),
+    signedA: i64(),
+    signedB: i64(),
+  },
+  accounts: [accountMeta('calc', { writable: true })],
+  ops: [
+    state.update('calc', {
+      sum: expr.add(a, b),
+      difference: expr.sub(a, b),
+      product: expr.mul(a, b),
+      quotient: expr.div(a, b),
+      quotientCeil: expr.div(a, b, { rounding: 'ceil' }),
+      quotientNearest: expr.div(a, b, { rounding: 'nearest' }),
+      scaled: expr.mulDiv(a, b, c),
+      scaledCeil: expr.mulDivCeil(a, b, c),
+      scaledNearest: expr.mulDiv(a, b, c, { rounding: 'nearest' }),
+      fee: expr.bps(a, b),
+      power: expr.pow(a, b),
+      root: expr.sqrt(a),
+      geometricMean: expr.sqrt(expr.mul(a, b)),
+      signedQuotient: expr.div(expr.arg('signedA'), expr.arg('signedB')),
+    }),
+  ],
+});
@@ -1,5 +1,5 @@
 import { describe, expect, it } from 'bun:test';
-import { compileToFile } from '@solana-ts-transpiler/compiler';
+import { buildIr, compileToFile } from '@solana-ts-transpiler/compiler';
 import { mkdtemp, readFile, access } from 'node:fs/promises';
 import { constants as fsConstants } from 'node:fs';
 import { tmpdir } from 'node:os';
@@ -33,13 +33,15 @@ const collectConstValues = (node: unknown, values: unknown[] = []): unknown[] =>
   return values;
 };
 
-const hasSystemProgram = (ix: { accounts: Array<{ name: string; kind?: string }> }) =>
-  ix.accounts.some((meta) => meta.name === 'systemProgram' && meta.kind === 'program');
+const hasSystemProgram = (ix: { accounts: Array<{ name: string; kind?: string; address?: unknown }> }) =>
+  ix.accounts.some(
+    (meta) => meta.name === 'systemProgram' && meta.kind === 'program' && meta.address === 'system',
+  );
 
-const runCli = async (inputPath: string, outputPath: string) =>
+const run = async (command: string, args: string[], cwd = repoRoot) =>
   await new Promise<{ stdout: string; stderr: string }>((resolve, reject) => {
-    const child = spawn(process.execPath, [path.join(repoRoot, 'packages/compiler/src/cli.ts'), inputPath, outputPath], {
-      cwd: repoRoot,
+    const child = spawn(command, args, {
+      cwd,
       stdio: ['ignore', 'pipe', 'pipe'],
     });
 
@@ -56,13 +58,16 @@ const runCli = async (inputPath: string, outputPath: string) =>
     child.on('error', (error) => reject(error));
     child.on('close', (code) => {
       if (code !== 0) {
-        reject(new Error(`CLI exited with code ${code}\n${stderr}`));
+        reject(new Error(`${path.basename(command)} exited with code ${code}\n${stdout}${stderr}`));
         return;
       }
       resolve({ stdout, stderr });
     });
   });
 
+const runCli = (inputPath: string, outputPath: string) =>
+  run(process.execPath, [path.join(repoRoot, 'packages/compiler/src/cli.ts'), inputPath, outputPath]);
+
 
 describe('transpiler integration', () => {
   it('compiles the vault example with stable instruction metadata', async () => {
@@ -70,10 +75,11 @@ describe('transpiler integration', () => {
 
     expect(ir.name).toBe('Vault');
     expect(ir.programId).toBe('GTcXWNZ8Ytmkcgzfr3V1R9X3tHxo7hC49DUh7ggMzvCV');
+    expect(ir.tokenProgram).toBe('token');
 
     const instructionNames = ir.instructions.map((ix) => ix.name);
-    expect(instructionNames).toEqual(['createVault', 'deposit', 'withdraw']);
-    expect(ir.instructions.map((ix) => ix.discriminator)).toEqual([0, 1, 2]);
+    expect(instructionNames).toEqual(['createVault', 'deposit', 'withdraw', 'withdrawUnderlying', 'setAdmin']);
+    expect(ir.instructions.map((ix) => ix.discriminator)).toEqual([0, 1, 2, 3, 4]);
 
     const createVault = ir.instructions.find((ix) => ix.name === 'createVault');
     const deposit = ir.instructions.find((ix) => ix.name === 'deposit');
@@ -84,7 +90,10 @@ describe('transpiler integration', () => {
     expect(hasSystemProgram(createVault!)).toBe(true);
     expect(hasSystemProgram(deposit!)).toBe(false);
 
-    expect(ir.views.map((view) => view.name)).toEqual(['vaultSummary']);
+    expect(ir.views.map((view) => view.name)).toEqual(['vaultSummary', 'shareValue']);
+    // Views are dispatched after the instructions
+    expect(ir.views.map((view) => view.discriminator)).toEqual([5, 6]);
+    expect(ir.accounts.vault.discriminator).toEqual([228]);
 
     const constValues = collectConstValues(json);
     expect(constValues.length).toBeGreaterThan(0);
@@ -97,6 +106,14 @@ describe('transpiler integration', () => {
     expect(ir.name).toBe('Amm');
     expect(ir.instructions).toHaveLength(5);
     expect(ir.views).toHaveLength(1);
+    // sha256("event:Swap")[..8], as emitted by Anchor
+    expect(ir.events).toEqual([
+      {
+        name: 'Swap',
+        fields: { amountIn: { kind: 'u64' }, amountOut: { kind: 'u64' } },
+        discriminator: [81, 108, 227, 190, 205, 208, 10, 196],
+      },
+    ]);
 
     const createPool = ir.instructions.find((ix) => ix.name === 'createPool');
     const addLiquidity = ir.instructions.find((ix) => ix.name === 'addLiquidity');
@@ -108,6 +125,97 @@ describe('transpiler integration', () => {
     expect(hasSystemProgram(addLiquidity!)).toBe(false);
   });
 
+  it('resolves account discriminators', () => {
+    const account = (name: string, discriminator?: unknown) => ({
+      __kind: 'account',
+      name,
+      schema: {},
+      ...(discriminator === undefined ? {} : { discriminator }),
+    });
+    const ir = buildIr({
+      __kind: 'program',
+      name: 'Multi',
+      programId: 'GTcXWNZ8Ytmkcgzfr3V1R9X3tHxo7hC49DUh7ggMzvCV',
+      accounts: {
+        config: account('Config'),
+        pool: account('Pool', 7),
+        position: account('Position', 'anchor'),
+      },
+    } as never);
+
+    // sha256("account:Config")[0]; independent of the position in `accounts`
+    expect(ir.accounts.config.discriminator).toEqual([155]);
+    expect(ir.accounts.pool.discriminator).toEqual([7]);
+    // sha256("account:Position")[..8], as emitted by Anchor
+    expect(ir.accounts.position.discriminator).toEqual([170, 188, 143, 228, 122, 64, 247, 208]);
+
+    expect(() =>
+      buildIr({
+        __kind: 'program',
+        name: 'Clash',
+        programId: 'GTcXWNZ8Ytmkcgzfr3V1R9X3tHxo7hC49DUh7ggMzvCV',
+        accounts: { a: account('A', 104), b: account('B') },
+      } as never),
+    ).toThrow('overlapping discriminators');
+  });
+
+  it('numbers built-in errors before user errors', () => {
+    const ir = buildIr({
+      __kind: 'program',
+      name: 'Errs',
+      programId: 'GTcXWNZ8Ytmkcgzfr3V1R9X3tHxo7hC49DUh7ggMzvCV',
+      accounts: {},
+      errors: { __kind: 'errors', errors: { Paused: 'Program is paused', Slippage: 'Slippage exceeded' } },
+    } as never);
+
+    expect(ir.errors.find((error) => error.name === 'Overflow')?.code).toBe(1000);
+    expect(ir.errors.find((error) => error.name === 'ConstraintEquals')?.code).toBe(1019);
+    expect(ir.errors.slice(-2)).toEqual([
+      { name: 'Paused', code: 6000, message: 'Program is paused' },
+      { name: 'Slippage', code: 6001, message: 'Slippage exceeded' },
+    ]);
+
+    expect(() =>
+      buildIr({
+        __kind: 'program',
+        name: 'Clash',
+        programId: 'GTcXWNZ8Ytmkcgzfr3V1R9X3tHxo7hC49DUh7ggMzvCV',
+        accounts: {},
+        errors: { __kind: 'errors', errors: { Overflow: 'again' } },
+      } as never),
+    ).toThrow('Duplicate error name Overflow');
+  });
+
+  // The examples only emit the helpers they call, so this fixture is what compiles the
+  // rest and runs their host tests.
+  it('generates every math helper and passes its host tests', async () => {
+    const { outputPath } = await compileExample('packages/transpiler-integration/tests/fixtures/math.ts', 'math.json');
+    const crateDir = await mkdtemp(path.join(tmpdir(), 'transpiler-math-'));
+    await run(process.execPath, [path.join(repoRoot, 'packages/gen-pinocchio/src/index.ts'), outputPath, crateDir]);
+
+    const lib = await readFile(path.join(crateDir, 'src', 'lib.rs'), 'utf8');
+    const helpers = lib.match(/^fn (checked_\w+|isqrt|sqrt|sqrt_mul)\b/gm)?.map((line) => line.slice(3));
+    expect(helpers).toEqual([
+      'checked_add',
+      'checked_sub',
+      'checked_mul',
+      'checked_div',
+      'checked_div_ceil',
+      'checked_div_round',
+      'checked_mul_div',
+      'checked_mul_div_ceil',
+      'checked_mul_div_round',
+      'checked_bps',
+      'checked_pow',
+      'isqrt',
+      'sqrt',
+      'sqrt_mul',
+    ]);
+
+    const { stdout } = await run('cargo', ['test', '--lib'], crateDir);
+    expect(stdout).toContain('test result: ok. 14 passed');
+  }, 900_000);
+
   it('writes IR output via the CLI entrypoint', async () => {
     const outDir = await mkdtemp(path.join(tmpdir(), 'transpiler-cli-'));
     const inputPath = path.join(repoRoot, 'examples/vault/vault.ts');